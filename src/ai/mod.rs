@@ -1,4 +1,7 @@
 // AI service module
 mod service;
 
-pub use service::AiService;
+pub use service::{
+    emoji_prefix_to_shortcode, first_line_only, format_elapsed, template_preset, AiProvider,
+    AiService, BodyStyle, ProviderListEntry, ProviderStatus, TEMPLATE_NAMES,
+};
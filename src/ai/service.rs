@@ -1,14 +1,19 @@
-use std::io::Write;
-use std::process::{Command, Stdio};
+use std::borrow::Cow;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
 
 use colored::Colorize;
+use regex::{Regex, RegexBuilder};
 
-use crate::config::{Config, ModelsConfig};
+use crate::config::{Config, ModelsConfig, ProviderArgsConfig, ProviderPathsConfig};
 use crate::error::AppError;
+use crate::git::GitService;
 use crate::state::State;
 
 /// AIプロバイダーの種類
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum AiProvider {
     Gemini,
     Codex,
@@ -16,7 +21,7 @@ pub enum AiProvider {
 }
 
 impl AiProvider {
-    fn name(&self) -> &'static str {
+    pub fn name(&self) -> &'static str {
         match self {
             AiProvider::Gemini => "Gemini CLI",
             AiProvider::Codex => "Codex CLI",
@@ -32,11 +37,42 @@ impl AiProvider {
         }
     }
 
+    /// 実行するコマンド（provider_paths の指定があればそのパス）
+    fn executable<'a>(&self, paths: &'a ProviderPathsConfig) -> &'a str {
+        let configured = match self {
+            AiProvider::Gemini => &paths.gemini,
+            AiProvider::Codex => &paths.codex,
+            AiProvider::Claude => &paths.claude,
+        };
+        configured
+            .as_deref()
+            .filter(|path| !path.trim().is_empty())
+            .unwrap_or(self.command())
+    }
+
+    /// インストール方法の案内
+    fn install_hint(&self) -> &'static str {
+        match self {
+            AiProvider::Gemini => "npm install -g @google/gemini-cli",
+            AiProvider::Codex => "npm install -g @openai/codex",
+            AiProvider::Claude => "npm install -g @anthropic-ai/claude-code",
+        }
+    }
+
     /// 設定ファイルで使用するキー名（状態管理にも使用）
     pub fn config_key(&self) -> &'static str {
         self.command()
     }
 
+    /// git-sc が自身で指定する引数（providers_args で上書きすると呼び出しが壊れるもの）
+    fn reserved_args(&self) -> &'static [&'static str] {
+        match self {
+            AiProvider::Gemini => &["-m", "--model", "-p", "--prompt"],
+            AiProvider::Codex => &["exec", "-m", "--model"],
+            AiProvider::Claude => &["--model", "-p", "--print"],
+        }
+    }
+
     /// 文字列からプロバイダーを解析
     fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
@@ -48,24 +84,779 @@ impl AiProvider {
     }
 }
 
+/// 対応している全プロバイダー
+const ALL_PROVIDERS: [AiProvider; 3] = [AiProvider::Gemini, AiProvider::Codex, AiProvider::Claude];
+
+/// プロバイダーのインストール状況（doctor 用）
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProviderStatus {
+    pub name: &'static str,
+    pub command: &'static str,
+    pub install_hint: &'static str,
+    pub installed: bool,
+}
+
+/// 試行順に並べたプロバイダーの状態（--list-providers 用）
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProviderListEntry {
+    pub name: &'static str,
+    pub installed: bool,
+    /// クールダウンで降格中であれば解除までの残り秒数
+    pub demoted_secs: Option<u64>,
+}
+
+/// 言語を直近のコミットから自動判定することを示す設定値
+pub const AUTO_LANGUAGE: &str = "auto";
+
+/// 自動判定でコミットがない場合に使用する言語
+const AUTO_LANGUAGE_FALLBACK: &str = "English";
+
+/// 言語名の別名・ISOコードと正式名（英語表記）の対応
+const LANGUAGE_ALIASES: &[(&[&str], &str)] = &[
+    (&["ja", "jp", "jpn", "japanese", "日本語"], "Japanese"),
+    (&["en", "eng", "english", "英語"], "English"),
+    (&["zh", "cn", "chi", "chinese", "中文", "中国語"], "Chinese"),
+    (&["ko", "kr", "kor", "korean", "한국어", "韓国語"], "Korean"),
+    (&["fr", "fra", "french", "français", "francais"], "French"),
+    (&["de", "deu", "ger", "german", "deutsch"], "German"),
+    (&["es", "spa", "spanish", "español", "espanol"], "Spanish"),
+    (
+        &["pt", "por", "portuguese", "português", "portugues"],
+        "Portuguese",
+    ),
+    (&["it", "ita", "italian", "italiano"], "Italian"),
+    (&["ru", "rus", "russian", "русский"], "Russian"),
+];
+
+/// 言語指定を正式名に正規化（"ja" や "日本語" → "Japanese"）
+///
+/// 対応表にない値はそのまま返す
+pub fn normalize_language(input: &str) -> String {
+    let trimmed = input.trim();
+    let key = trimmed.to_lowercase();
+
+    LANGUAGE_ALIASES
+        .iter()
+        .find(|(aliases, _)| aliases.contains(&key.as_str()))
+        .map(|(_, canonical)| canonical.to_string())
+        .unwrap_or_else(|| trimmed.to_string())
+}
+
+/// コミットメッセージ1件の言語を文字種から判定
+///
+/// かなを含めば日本語、ハングルを含めば韓国語、かなを含まない漢字は中国語、
+/// それ以外は英語とみなす（日本語のコミットでも英単語を含むことが多いため、
+/// 文字数の比率ではなく文字種の有無で判定する）
+fn classify_language(message: &str) -> &'static str {
+    let mut has_han = false;
+    for c in message.chars() {
+        match c {
+            '\u{3040}'..='\u{30FF}' | '\u{31F0}'..='\u{31FF}' | '\u{FF66}'..='\u{FF9F}' => {
+                return "Japanese";
+            }
+            '\u{AC00}'..='\u{D7AF}' | '\u{1100}'..='\u{11FF}' | '\u{3130}'..='\u{318F}' => {
+                return "Korean";
+            }
+            '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}' => has_han = true,
+            _ => {}
+        }
+    }
+    if has_han {
+        "Chinese"
+    } else {
+        "English"
+    }
+}
+
+/// AIの前置きとみなす行の書き出し（小文字、アポストロフィは ' に正規化）
+const PREAMBLE_STARTS: &[&str] = &[
+    "here is",
+    "here's",
+    "sure",
+    "based on",
+    "i'll",
+    "i will",
+    "let me",
+    "the commit message",
+];
+
+/// 末尾の締めの挨拶とみなす行の書き出し（小文字、アポストロフィは ' に正規化）
+const SIGN_OFF_STARTS: &[&str] = &[
+    "let me know",
+    "feel free",
+    "i hope this helps",
+    "hope this helps",
+];
+
+/// プロバイダー固有の出力の混入物を取り除く（汎用の clean_message の前に適用）
+fn postprocess(provider: &AiProvider, raw: &str) -> String {
+    match provider {
+        AiProvider::Gemini => strip_gemini_noise(raw),
+        AiProvider::Codex => strip_codex_noise(raw),
+        AiProvider::Claude => strip_claude_noise(raw),
+    }
+}
+
+/// Gemini CLI が標準出力に出す認証キャッシュの通知行を削除
+fn strip_gemini_noise(raw: &str) -> String {
+    raw.lines()
+        .skip_while(|line| {
+            let line = line.trim();
+            line.is_empty() || line == "Loaded cached credentials."
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Codex CLI の exec が出力する状態表示（`[時刻] codex` などの見出し、トークン数）を削除
+///
+/// 応答本体の見出し（`codex`）があれば、最後の見出しより後ろだけを残す
+fn strip_codex_noise(raw: &str) -> String {
+    let status = Regex::new(r"^(\[[^\]]+\]\s*)?(codex|thinking)$").unwrap();
+    let tokens = Regex::new(r"^(\[[^\]]+\]\s*)?tokens used:").unwrap();
+
+    let lines: Vec<&str> = raw
+        .lines()
+        .filter(|line| !tokens.is_match(line.trim()))
+        .collect();
+    let start = lines
+        .iter()
+        .rposition(|line| {
+            let line = line.trim();
+            status.is_match(line) && !line.ends_with("thinking")
+        })
+        .map(|pos| pos + 1)
+        .unwrap_or(0);
+    lines[start..]
+        .iter()
+        .skip_while(|line| line.trim().is_empty() || status.is_match(line.trim()))
+        .copied()
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Claude Code が付け加える末尾の挨拶や生成元の署名行を削除
+fn strip_claude_noise(raw: &str) -> String {
+    let mut lines: Vec<&str> = raw
+        .lines()
+        .filter(|line| {
+            let line = line.trim();
+            !line.contains("Generated with [Claude Code]")
+                && !line.starts_with("Co-Authored-By: Claude")
+        })
+        .collect();
+    while let Some(last) = lines.last() {
+        let lower = last.trim().to_lowercase().replace('’', "'");
+        if lower.is_empty() || SIGN_OFF_STARTS.iter().any(|s| lower.starts_with(s)) {
+            lines.pop();
+        } else {
+            break;
+        }
+    }
+    lines.join("\n")
+}
+
+/// メッセージに含まれる禁止語を大文字小文字を区別せずに検索し、最初に見つかったものを返す
+pub fn contains_blocked(msg: &str, terms: &[String]) -> Option<String> {
+    let lower = msg.to_lowercase();
+    terms
+        .iter()
+        .map(|t| t.trim())
+        .filter(|t| !t.is_empty())
+        .find(|t| lower.contains(&t.to_lowercase()))
+        .map(|t| t.to_string())
+}
+
+/// メッセージ中の禁止語をすべて "***" に置き換える（大文字小文字を区別しない）
+pub fn redact_blocked(msg: &str, terms: &[String]) -> String {
+    terms
+        .iter()
+        .map(|t| t.trim())
+        .filter(|t| !t.is_empty())
+        .fold(msg.to_string(), |acc, term| {
+            match RegexBuilder::new(&regex::escape(term))
+                .case_insensitive(true)
+                .build()
+            {
+                Ok(re) => re.replace_all(&acc, "***").into_owned(),
+                Err(_) => acc,
+            }
+        })
+}
+
+/// Conventional Commits 形式の件名から種類（`feat(x)!: ...` の `feat`）を取り出す
+pub fn extract_type(subject: &str) -> Option<String> {
+    let first_line = subject.lines().next()?.trim();
+    let re = Regex::new(r"^([A-Za-z]+)(\([^()]*\))?!?:").unwrap();
+    re.captures(first_line).map(|caps| caps[1].to_lowercase())
+}
+
+/// メッセージの種類が許可されているか（許可リストが空なら常に許可）
+fn is_allowed_type(message: &str, allowed: &[String]) -> bool {
+    if allowed.is_empty() {
+        return true;
+    }
+    extract_type(message).is_some_and(|t| allowed.iter().any(|a| a.eq_ignore_ascii_case(&t)))
+}
+
+/// 出力を1行ずつ読み取り、各行を on_line に渡しながら全体を組み立てる（--stream 用）
+fn read_streamed_lines<R: BufRead>(
+    mut reader: R,
+    mut on_line: impl FnMut(&str),
+) -> std::io::Result<String> {
+    let mut output = Vec::new();
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        on_line(String::from_utf8_lossy(&line).trim_end_matches(['\r', '\n']));
+        output.extend_from_slice(&line);
+    }
+    Ok(String::from_utf8_lossy(&output).into_owned())
+}
+
+/// 最初の空でない行（件名）のみを残す（--subject-only 用）
+pub fn first_line_only(msg: &str) -> String {
+    msg.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// 本文のみを求めた応答の先頭に件名が繰り返されていれば取り除く
+fn strip_repeated_subject(response: &str, subject: &str) -> String {
+    let response = response.trim();
+    match response.split_once('\n') {
+        Some((first, rest)) if first.trim() == subject.trim() => rest.trim().to_string(),
+        None if response == subject.trim() => String::new(),
+        _ => response.to_string(),
+    }
+}
+
+/// gitmoji テンプレートで使用する type と絵文字の対応
+const GITMOJI_TYPES: &[(&str, &str)] = &[
+    ("feat", "✨"),
+    ("fix", "🐛"),
+    ("docs", "📝"),
+    ("style", "🎨"),
+    ("refactor", "♻️"),
+    ("perf", "⚡️"),
+    ("test", "✅"),
+    ("build", "📦️"),
+    ("ci", "👷"),
+    ("chore", "🔧"),
+    ("revert", "⏪️"),
+];
+
+/// type に対応する gitmoji のショートコード（例: feat → `:sparkles:`）
+pub fn gitmoji_shortcode_for_type(t: &str) -> Option<&'static str> {
+    match t.to_lowercase().as_str() {
+        "feat" => Some(":sparkles:"),
+        "fix" => Some(":bug:"),
+        "docs" => Some(":memo:"),
+        "style" => Some(":art:"),
+        "refactor" => Some(":recycle:"),
+        "perf" => Some(":zap:"),
+        "test" => Some(":white_check_mark:"),
+        "build" => Some(":package:"),
+        "ci" => Some(":construction_worker:"),
+        "chore" => Some(":wrench:"),
+        "revert" => Some(":rewind:"),
+        _ => None,
+    }
+}
+
+/// 件名の先頭の gitmoji（Unicode）をショートコードに置き換える（emoji_style = "shortcode"）
+///
+/// 異体字セレクタ（U+FE0F）の有無は問わない。gitmoji で始まらない場合はそのまま返す
+pub fn emoji_prefix_to_shortcode(message: &str) -> String {
+    for (t, emoji) in GITMOJI_TYPES {
+        let bare = emoji.trim_end_matches('\u{FE0F}');
+        let Some(rest) = message
+            .strip_prefix(emoji)
+            .or_else(|| message.strip_prefix(bare))
+        else {
+            continue;
+        };
+        if let Some(shortcode) = gitmoji_shortcode_for_type(t) {
+            let rest = rest.strip_prefix('\u{FE0F}').unwrap_or(rest);
+            return format!("{}{}", shortcode, rest);
+        }
+    }
+    message.to_string()
+}
+
+/// --template で選択できるスタイルプリセット
+pub const TEMPLATE_NAMES: &[&str] = &["conventional", "gitmoji", "angular"];
+
+/// スタイルプリセット（prefix_type とプロンプトへの追加指示の組）
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplatePreset {
+    /// プリセットが使用する prefix_type
+    pub prefix_type: &'static str,
+    /// プロンプトの Instructions に追加する指示
+    pub guidance: String,
+}
+
+/// テンプレート名からプリセットを取得
+pub fn template_preset(name: &str) -> Option<TemplatePreset> {
+    match name.to_lowercase().as_str() {
+        "conventional" => Some(TemplatePreset {
+            prefix_type: "conventional",
+            guidance: "- Use one of these types: feat, fix, docs, style, refactor, perf, test, build, ci, chore, revert\n\
+- Write the subject in the imperative mood"
+                .to_string(),
+        }),
+        "angular" => Some(TemplatePreset {
+            prefix_type: "conventional",
+            guidance: "- Follow the Angular commit convention: type(scope): subject\n\
+- Always include a scope naming the affected module or package in parentheses\n\
+- Keep the subject line at most 72 characters\n\
+- Use the imperative, present tense; do not capitalize the first letter; no period at the end"
+                .to_string(),
+        }),
+        "gitmoji" => {
+            let mapping = GITMOJI_TYPES
+                .iter()
+                .map(|(t, emoji)| format!("{} {}", emoji, t))
+                .collect::<Vec<_>>()
+                .join(", ");
+            Some(TemplatePreset {
+                prefix_type: "emoji",
+                guidance: format!(
+                    "- Start the subject with the gitmoji matching the change type: {}\n\
+- Write any body as Conventional Commits style bullet points",
+                    mapping
+                ),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// --stat-only で送る変更内容を、コードを含まない形に揃える
+///
+/// 統一形式の差分が渡された場合はファイルごとの追加・削除行数に置き換え、コードを送らない
+fn stat_only_changes(changes: &str) -> Cow<'_, str> {
+    let is_unified_diff = changes
+        .lines()
+        .any(|line| line.starts_with("diff --git ") || line.starts_with("@@ "));
+    if !is_unified_diff {
+        return Cow::Borrowed(changes);
+    }
+
+    let summary: Vec<String> = GitService::split_diff_blocks(changes)
+        .iter()
+        .map(|block| {
+            let lines = || block.content.lines().skip_while(|l| !l.starts_with("@@"));
+            let added = lines()
+                .filter(|l| l.starts_with('+') && !l.starts_with("+++"))
+                .count();
+            let removed = lines()
+                .filter(|l| l.starts_with('-') && !l.starts_with("---"))
+                .count();
+            format!("{} | +{} -{}", block.path, added, removed)
+        })
+        .collect();
+    Cow::Owned(summary.join("\n"))
+}
+
+/// プロンプトに含める変更内容
+#[derive(Debug, Clone, Copy)]
+pub enum PromptChanges<'a> {
+    /// 差分（コードを含む）
+    Diff(&'a str),
+    /// ファイル一覧と変更量のみ（--stat-only、コードを含まない）
+    Stat(&'a str),
+    /// 変更なし（--allow-empty の目印コミット）
+    Empty,
+}
+
+impl PromptChanges<'_> {
+    /// プロンプト末尾の変更内容セクション
+    fn section(&self) -> String {
+        match self {
+            PromptChanges::Diff(diff) => format!("Changes:\n```diff\n{}\n```", diff),
+            PromptChanges::Stat(stat) => format!(
+                "Changed files (summary only, source code is not included):\n{}\n\nInfer the purpose of the change from the file names, statuses and change sizes.",
+                stat.trim_end()
+            ),
+            PromptChanges::Empty => "There are no file changes. This is an intentionally empty marker commit; describe its intent concisely based on the recent commits.".to_string(),
+        }
+    }
+}
+
+/// プロンプトに追加する任意の指示
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PromptExtras<'a> {
+    /// --template プリセットによる追加指示
+    pub guidance: Option<&'a str>,
+    /// リポジトリのコミットテンプレート（commit.template / .gitmessage）
+    pub commit_template: Option<&'a str>,
+    /// 作成者からの補足情報（--context）
+    pub context: &'a [String],
+    /// Conventional Commits で使用を許可する種類（空なら制限なし）
+    pub allowed_types: &'a [String],
+    /// 変更ファイルの共通ディレクトリから求めたスコープ候補
+    pub suggested_scope: Option<&'a str>,
+    /// Instructions の末尾に追加するチーム独自の指示（設定ファイルの prompt_suffix）
+    pub instruction_suffix: Option<&'a str>,
+    /// ステージ済みファイルの一覧（`git diff --cached --name-status` の出力）
+    pub changed_files: Option<&'a str>,
+    /// 現在のブランチ名（--use-branch-context）
+    pub branch: Option<&'a str>,
+}
+
+/// 本文（body）の書き方
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum BodyStyle {
+    /// "- " で始まる箇条書き
+    #[default]
+    Bullets,
+    /// 短い段落の文章
+    Paragraph,
+}
+
+impl BodyStyle {
+    /// 名前から本文スタイルを解析
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "bullets" => Some(BodyStyle::Bullets),
+            "paragraph" => Some(BodyStyle::Paragraph),
+            _ => None,
+        }
+    }
+
+    /// プロンプトに埋め込む本文の指示
+    fn instructions(&self) -> &'static str {
+        match self {
+            BodyStyle::Bullets => {
+                r#"
+Structure:
+- First line: Subject line (concise summary, ideally under 72 characters)
+- Second line: Empty (blank line)
+- Third line onwards: Body with bullet points describing key changes
+
+Body Guidelines:
+- Use bullet points starting with "- "
+- Each bullet point should describe a specific change
+- Include 2-5 bullet points based on the scope of changes
+- Be specific about what was added, changed, or removed
+- Do not write structural labels such as "Subject:" or "Body:""#
+            }
+            BodyStyle::Paragraph => {
+                r#"
+Structure:
+- First line: Subject line (concise summary, ideally under 72 characters)
+- Second line: Empty (blank line)
+- Third line onwards: Body written as prose
+
+Body Guidelines:
+- Write 1-3 short paragraphs describing the motivation and changes
+- Separate paragraphs with a blank line
+- Do NOT use bullet points or list markers
+- Be specific about what was added, changed, or removed
+- Do not write structural labels such as "Subject:" or "Body:""#
+            }
+        }
+    }
+
+    /// 日本語で生成する場合の本文の指示（英語の構成ラベルが混ざらないよう日本語で指示する）
+    fn japanese_instructions(&self) -> &'static str {
+        match self {
+            BodyStyle::Bullets => {
+                r#"
+構成:
+- 1行目: 件名（変更の簡潔な要約、72文字程度まで）
+- 2行目: 空行
+- 3行目以降: 主な変更点を箇条書きで説明する本文
+
+本文のガイドライン:
+- 各項目は "- " で始める
+- 1項目につき1つの具体的な変更を書く
+- 変更の規模に応じて2〜5項目にする
+- 追加・変更・削除した内容を具体的に書く
+- 「件名」「本文」「Subject」「Body」などの見出しやラベルは書かない"#
+            }
+            BodyStyle::Paragraph => {
+                r#"
+構成:
+- 1行目: 件名（変更の簡潔な要約、72文字程度まで）
+- 2行目: 空行
+- 3行目以降: 文章で書いた本文
+
+本文のガイドライン:
+- 変更の動機と内容を1〜3個の短い段落で説明する
+- 段落の間は空行で区切る
+- 箇条書きや記号による列挙は使わない
+- 追加・変更・削除した内容を具体的に書く
+- 「件名」「本文」「Subject」「Body」などの見出しやラベルは書かない"#
+            }
+        }
+    }
+}
+
+/// 生成言語に合わせた本文（または1行メッセージ）の指示
+///
+/// 対応する言語では指示自体をその言語で書き、それ以外は英語の指示を使う
+fn body_instructions_for(language: &str, with_body: bool, style: BodyStyle) -> String {
+    let japanese = normalize_language(language) == "Japanese";
+    let instructions = match (with_body, japanese) {
+        (true, true) => style.japanese_instructions(),
+        (true, false) => style.instructions(),
+        (false, true) => {
+            r#"
+ルール:
+- 1行のみで書く（複数行にしない）
+- 簡潔にする（72文字程度まで）"#
+        }
+        (false, false) => {
+            r#"
+Rules:
+- Write only a single line (no multi-line message)
+- Keep it concise (ideally under 72 characters)"#
+        }
+    };
+    instructions.to_string()
+}
+
 /// フォールバック機能付きのAIサービス
 pub struct AiService {
     providers: Vec<AiProvider>,
     language: String,
     models: ModelsConfig,
+    /// 各プロバイダーのCLIに追加で渡す引数
+    provider_args: ProviderArgsConfig,
+    /// 各プロバイダーの実行ファイルのパス
+    provider_paths: ProviderPathsConfig,
     cooldown_minutes: u64,
+    /// 各プロバイダー呼び出しの経過時間を表示するか
+    verbose: bool,
+    /// プロバイダーの出力を受信しながら表示するか（--stream）
+    stream: bool,
+    /// --template プリセットによる追加指示
+    template_guidance: Option<String>,
+    /// リポジトリのコミットテンプレート
+    commit_template: Option<String>,
+    /// 作成者からの補足情報（--context）
+    context: Vec<String>,
+    /// 本文の書き方（--body-style）
+    body_style: BodyStyle,
+    /// 差分の代わりにファイル一覧と変更量のみを送るか（--stat-only）
+    stat_only: bool,
+    /// 生成結果から件名以外の行を取り除くか（--subject-only）
+    subject_only: bool,
+    /// 変更ファイルの共通ディレクトリをスコープ候補としてプロンプトに含めるか
+    suggest_scope: bool,
+    /// Instructions の末尾に追加する指示
+    prompt_suffix: Option<String>,
+    /// プロンプトに含めるステージ済みファイルの一覧（name-status 形式）
+    changed_files: Option<String>,
+    /// プロンプトに含める現在のブランチ名（--use-branch-context）
+    branch_context: Option<String>,
+    /// コミットメッセージに含めてはいけない語
+    blocked_terms: Vec<String>,
+    /// Conventional Commits で使用を許可する種類（空なら制限なし）
+    allowed_types: Vec<String>,
+    /// 再生成後も許可されていない種類ならエラーにするか
+    strict_allowed_types: bool,
+    /// プロバイダーの状態をリポジトリ単位で管理するか
+    scope_state_to_repo: bool,
+    /// 状態ファイルのパス（None なら scope_state_to_repo に応じた既定の場所）
+    state_path: Option<PathBuf>,
+    /// プロバイダー一覧を何周まで試行するか（--retry-providers / provider_passes）
+    provider_passes: u32,
+}
+
+/// 動作確認済みの Gemini CLI のモデル名
+const KNOWN_GEMINI_MODELS: &[&str] = &[
+    "flash",
+    "pro",
+    "flash-lite",
+    "gemini-2.5-flash",
+    "gemini-2.5-pro",
+    "gemini-2.5-flash-lite",
+];
+
+/// 動作確認済みの Codex CLI のモデル名
+const KNOWN_CODEX_MODELS: &[&str] = &[
+    "gpt-5.1-codex-mini",
+    "gpt-5.1-codex",
+    "gpt-5.1",
+    "gpt-5-codex",
+    "gpt-5",
+];
+
+/// 動作確認済みの Claude Code のモデル名
+const KNOWN_CLAUDE_MODELS: &[&str] = &[
+    "haiku",
+    "sonnet",
+    "opus",
+    "claude-haiku-4-5",
+    "claude-sonnet-4-5",
+    "claude-opus-4-1",
+];
+
+/// プロバイダーごとの既知のモデル名
+pub fn known_models(provider: &AiProvider) -> &'static [&'static str] {
+    match provider {
+        AiProvider::Gemini => KNOWN_GEMINI_MODELS,
+        AiProvider::Codex => KNOWN_CODEX_MODELS,
+        AiProvider::Claude => KNOWN_CLAUDE_MODELS,
+    }
+}
+
+/// 既知のモデル名にない設定値の警告メッセージ
+///
+/// モデルは随時追加されるためエラーにはせず、タイプミスに気付けるよう警告のみ行う
+fn unknown_model_warnings(models: &ModelsConfig) -> Vec<String> {
+    [
+        (AiProvider::Gemini, models.gemini()),
+        (AiProvider::Codex, models.codex()),
+        (AiProvider::Claude, models.claude()),
+    ]
+    .into_iter()
+    .filter(|(provider, model)| !known_models(provider).contains(model))
+    .map(|(provider, model)| {
+        format!(
+            "models.{} = \"{}\" は既知のモデル名ではありません（既知: {}）",
+            provider.config_key(),
+            model,
+            known_models(&provider).join(", ")
+        )
+    })
+    .collect()
+}
+
+/// 設定されたプロバイダー名のうち認識できないものについての警告メッセージ
+///
+/// 全て認識できない場合は既定の順序にフォールバックすることも伝える
+fn unknown_provider_warning(configured: &[String]) -> Option<String> {
+    let unknown: Vec<&str> = configured
+        .iter()
+        .map(String::as_str)
+        .filter(|name| AiProvider::from_str(name).is_none())
+        .collect();
+    if unknown.is_empty() {
+        return None;
+    }
+    let action = if unknown.len() == configured.len() {
+        "既定の gemini, codex, claude を使用します"
+    } else {
+        "無視します"
+    };
+    Some(format!(
+        "providers に不明なプロバイダーがあります: {}（{}）",
+        unknown.join(", "),
+        action
+    ))
+}
+
+/// プロバイダー一巡の再試行までの待ち時間
+const PROVIDER_PASS_DELAY: Duration = Duration::from_secs(2);
+
+/// プロバイダー一覧を最大 passes 回まで順に試行し、最初に成功した結果を返す
+///
+/// call が None を返したプロバイダー（未インストール）は試行に数えない。
+/// 試行できるプロバイダーが1つもなければ再試行せずに終了する
+fn try_provider_passes<F>(
+    providers: &[AiProvider],
+    passes: u32,
+    delay: Duration,
+    silent: bool,
+    mut call: F,
+) -> Result<(String, AiProvider), AppError>
+where
+    F: FnMut(&AiProvider) -> Option<Result<String, AppError>>,
+{
+    let passes = passes.max(1);
+    let mut last_error = None;
+
+    for pass in 1..=passes {
+        if pass > 1 {
+            if !silent {
+                println!(
+                    "  {}",
+                    format!("Retrying providers (pass {}/{})...", pass, passes).dimmed()
+                );
+            }
+            std::thread::sleep(delay);
+        }
+
+        for provider in providers {
+            match call(provider) {
+                Some(Ok(message)) => return Ok((message, *provider)),
+                Some(Err(e)) => last_error = Some(e),
+                None => {}
+            }
+        }
+
+        if last_error.is_none() {
+            break;
+        }
+    }
+
+    Err(last_error.unwrap_or(AppError::NoAiProviderInstalled))
+}
+
+/// 経過時間を "key=value elapsed=1.23s" 形式に整形
+pub fn format_elapsed(key: &str, value: &str, elapsed: Duration) -> String {
+    format!("{}={} elapsed={:.2}s", key, value, elapsed.as_secs_f64())
 }
 
 impl AiService {
     /// 設定からAiServiceを作成
-    pub fn from_config(config: &Config) -> Self {
-        let provider_strings: Vec<String> = config.providers.clone();
+    ///
+    /// providers が明示的に空の場合はエラー。不明なプロバイダー名は警告して無視する
+    pub fn from_config(config: &Config) -> Result<Self, AppError> {
+        for warning in unknown_model_warnings(&config.models) {
+            eprintln!("{} {}", "⚠".yellow(), warning.yellow());
+        }
+
+        // providers 未指定なら既定の順序を使う
+        let configured: Vec<String> = if config.providers_detailed.is_empty() {
+            match &config.providers {
+                Some(providers) if providers.is_empty() => {
+                    return Err(AppError::ConfigError("no providers configured".to_string()))
+                }
+                Some(providers) => providers.clone(),
+                None => ALL_PROVIDERS
+                    .iter()
+                    .map(|p| p.config_key().to_string())
+                    .collect(),
+            }
+        } else {
+            config
+                .providers_detailed
+                .iter()
+                .map(|p| p.name.clone())
+                .collect()
+        };
+        if let Some(warning) = unknown_provider_warning(&configured) {
+            eprintln!("{} {}", "⚠".yellow(), warning.yellow());
+        }
 
         // 状態を読み込んで、クールダウン中のプロバイダーを降格
-        let reordered_strings = if let Ok(state) = State::load() {
-            state.reorder_providers(provider_strings, config.provider_cooldown_minutes)
+        // providers_detailed があれば優先度順に並べてから降格を適用
+        let state =
+            State::load_scoped(config.scope_state_to_repo.unwrap_or(false)).unwrap_or_default();
+        let reordered_strings = if config.providers_detailed.is_empty() {
+            state.reorder_providers(configured, config.provider_cooldown_minutes)
+        } else {
+            state.reorder_providers_with_priority(
+                &config.providers_detailed,
+                config.provider_cooldown_minutes,
+            )
+        };
+
+        // skip_demoted: クールダウン中のプロバイダーは試行しない（全滅時は並び替えのみ）
+        let reordered_strings = if config.skip_demoted.unwrap_or(false) {
+            state.skip_demoted_providers(reordered_strings, config.provider_cooldown_minutes)
         } else {
-            provider_strings
+            reordered_strings
         };
 
         let providers: Vec<AiProvider> = reordered_strings
@@ -80,12 +871,37 @@ impl AiService {
             providers
         };
 
-        Self {
+        Ok(Self {
             providers,
-            language: config.language.clone(),
+            language: normalize_language(&config.language),
             models: config.models.clone(),
+            provider_args: config.providers_args.clone(),
+            provider_paths: config.provider_paths.clone(),
             cooldown_minutes: config.provider_cooldown_minutes,
-        }
+            verbose: false,
+            stream: false,
+            template_guidance: None,
+            commit_template: None,
+            context: Vec::new(),
+            changed_files: None,
+            branch_context: None,
+            body_style: BodyStyle::default(),
+            stat_only: false,
+            subject_only: false,
+            suggest_scope: config.suggest_scope.unwrap_or(false),
+            prompt_suffix: config.prompt_suffix.clone(),
+            blocked_terms: config.blocked_terms.clone(),
+            allowed_types: config
+                .allowed_types
+                .iter()
+                .map(|t| t.trim().to_lowercase())
+                .filter(|t| !t.is_empty())
+                .collect(),
+            strict_allowed_types: config.strict_allowed_types.unwrap_or(false),
+            scope_state_to_repo: config.scope_state_to_repo.unwrap_or(false),
+            state_path: None,
+            provider_passes: config.provider_passes.unwrap_or(1),
+        })
     }
 
     /// デフォルトのフォールバック順序でAiServiceを作成
@@ -94,61 +910,292 @@ impl AiService {
             providers: vec![AiProvider::Gemini, AiProvider::Codex, AiProvider::Claude],
             language: "Japanese".to_string(),
             models: ModelsConfig::default(),
+            provider_args: ProviderArgsConfig::default(),
+            provider_paths: ProviderPathsConfig::default(),
             cooldown_minutes: 60, // デフォルト1時間
+            verbose: false,
+            stream: false,
+            template_guidance: None,
+            commit_template: None,
+            context: Vec::new(),
+            changed_files: None,
+            branch_context: None,
+            body_style: BodyStyle::default(),
+            stat_only: false,
+            subject_only: false,
+            suggest_scope: false,
+            prompt_suffix: None,
+            blocked_terms: Vec::new(),
+            allowed_types: Vec::new(),
+            strict_allowed_types: false,
+            scope_state_to_repo: false,
+            state_path: None,
+            provider_passes: 1,
+        }
+    }
+
+    /// 状態ファイルを読み込む（パスの指定があればそのファイル）
+    fn load_state(&self) -> Result<State, AppError> {
+        match &self.state_path {
+            Some(path) => State::load_from(path),
+            None => State::load_scoped(self.scope_state_to_repo),
+        }
+    }
+
+    /// 状態ファイルに保存する（パスの指定があればそのファイル）
+    fn save_state(&self, state: &State) -> Result<(), AppError> {
+        match &self.state_path {
+            Some(path) => state.save_to(path),
+            None => state.save_scoped(self.scope_state_to_repo),
         }
     }
 
     /// プロバイダーの失敗を記録
     fn record_provider_failure(&self, provider: &AiProvider) {
-        if let Ok(mut state) = State::load() {
+        if let Ok(mut state) = self.load_state() {
             state.record_failure(provider.config_key());
             // 期限切れのエントリをクリーンアップ
             state.cleanup_expired(self.cooldown_minutes);
             // 保存（エラーは無視）
-            let _ = state.save();
+            let _ = self.save_state(&state);
+        }
+    }
+
+    /// 生成の成功とレイテンシを記録（再生成や要約の呼び出しがあっても1回の生成につき1回）
+    fn record_provider_success(&self, provider: &AiProvider, elapsed: Duration) {
+        if let Ok(mut state) = self.load_state() {
+            state.record_success(provider.config_key(), elapsed.as_millis() as u64);
+            // 保存（エラーは無視）
+            let _ = self.save_state(&state);
         }
     }
 
+    /// 経過時間表示（--verbose）を設定
+    pub fn set_verbose(&mut self, verbose: bool) {
+        self.verbose = verbose;
+    }
+
+    /// プロバイダー出力の逐次表示（--stream）を設定
+    pub fn set_stream(&mut self, stream: bool) {
+        self.stream = stream;
+    }
+
+    /// テンプレートプリセットの追加指示を設定
+    pub fn set_template_guidance(&mut self, guidance: String) {
+        self.template_guidance = Some(guidance);
+    }
+
+    /// リポジトリのコミットテンプレートを設定
+    pub fn set_commit_template(&mut self, template: String) {
+        self.commit_template = Some(template);
+    }
+
+    /// 作成者からの補足情報を設定（--context）
+    pub fn set_context(&mut self, context: Vec<String>) {
+        self.context = context;
+    }
+
+    /// プロンプトに含めるステージ済みファイルの一覧を設定（name-status 形式）
+    pub fn set_changed_files(&mut self, name_status: String) {
+        self.changed_files = Some(name_status);
+    }
+
+    /// プロンプトに含める現在のブランチ名を設定
+    pub fn set_branch_context(&mut self, branch: String) {
+        self.branch_context = Some(branch);
+    }
+
+    /// プロバイダー一覧を何周まで試行するかを設定
+    pub fn set_provider_passes(&mut self, passes: u32) {
+        self.provider_passes = passes;
+    }
+
+    /// 本文の書き方を設定
+    pub fn set_body_style(&mut self, body_style: BodyStyle) {
+        self.body_style = body_style;
+    }
+
+    /// 件名のみを残すかを設定（--subject-only）
+    pub fn set_subject_only(&mut self, subject_only: bool) {
+        self.subject_only = subject_only;
+    }
+
+    /// 差分の代わりにファイル一覧と変更量のみを送るかを設定
+    ///
+    /// 有効な場合、生成系メソッドの `diff` 引数は `git diff --stat` 等の要約として扱う
+    pub fn set_stat_only(&mut self, stat_only: bool) {
+        self.stat_only = stat_only;
+    }
+
     /// 言語設定を上書き
     pub fn set_language(&mut self, language: String) {
-        self.language = language;
+        self.language = normalize_language(&language);
     }
 
     /// 言語設定を取得
+    #[cfg(test)]
     pub fn language(&self) -> &str {
         &self.language
     }
 
+    /// 言語設定が自動判定（"auto"）かどうか
+    pub fn is_auto_language(&self) -> bool {
+        self.language.eq_ignore_ascii_case(AUTO_LANGUAGE)
+    }
+
+    /// 直近のコミットメッセージから使用言語を推定
+    ///
+    /// 各コミットの言語を判定し、最も多い言語を返す。同数の場合はより新しい
+    /// コミットの言語を優先する。コミットがない場合は English を返す
+    pub fn detect_language(commits: &[String]) -> String {
+        let mut counts: Vec<(&'static str, usize)> = Vec::new();
+        for commit in commits {
+            let lang = classify_language(commit);
+            match counts.iter_mut().find(|(l, _)| *l == lang) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((lang, 1)),
+            }
+        }
+
+        let mut best: Option<(&'static str, usize)> = None;
+        for (lang, count) in counts {
+            if best.is_none_or(|(_, best_count)| count > best_count) {
+                best = Some((lang, count));
+            }
+        }
+
+        best.map(|(lang, _)| lang)
+            .unwrap_or(AUTO_LANGUAGE_FALLBACK)
+            .to_string()
+    }
+
     /// 少なくとも1つのAI CLIがインストールされていることを確認
     pub fn verify_installation(&self) -> Result<(), AppError> {
         for provider in &self.providers {
-            if Self::is_installed(provider) {
+            if Self::is_installed(provider, &self.provider_paths) {
                 return Ok(());
             }
         }
         Err(AppError::NoAiProviderInstalled)
     }
 
+    /// 対応している全プロバイダーのインストール状況を取得
+    pub fn provider_statuses(paths: &ProviderPathsConfig) -> Vec<ProviderStatus> {
+        ALL_PROVIDERS
+            .iter()
+            .map(|provider| ProviderStatus {
+                name: provider.name(),
+                command: provider.command(),
+                install_hint: provider.install_hint(),
+                installed: Self::is_installed(provider, paths),
+            })
+            .collect()
+    }
+
+    /// 試行順のプロバイダー一覧とインストール・降格状況を取得
+    pub fn provider_list(&self) -> Vec<ProviderListEntry> {
+        let state = State::load_scoped(self.scope_state_to_repo).unwrap_or_default();
+        self.providers
+            .iter()
+            .map(|provider| ProviderListEntry {
+                name: provider.name(),
+                installed: Self::is_installed(provider, &self.provider_paths),
+                demoted_secs: state
+                    .cooldown_remaining_secs(provider.config_key(), self.cooldown_minutes),
+            })
+            .collect()
+    }
+
     /// プロバイダーがインストールされているかチェック
-    fn is_installed(provider: &AiProvider) -> bool {
+    fn is_installed(provider: &AiProvider, paths: &ProviderPathsConfig) -> bool {
         // Windows uses "where", Unix uses "which"
         let check_cmd = if cfg!(windows) { "where" } else { "which" };
         Command::new(check_cmd)
-            .arg(provider.command())
+            .arg(provider.executable(paths))
             .output()
             .map(|o| o.status.success())
             .unwrap_or(false)
     }
 
-    /// AI用のプロンプトを構築
+    /// AI用のプロンプトを構築（テンプレート設定なし）
+    #[cfg(test)]
     pub fn build_prompt(
         diff: &str,
         recent_commits: &[String],
         language: &str,
         prefix_type: Option<&str>,
         with_body: bool,
+    ) -> String {
+        Self::build_prompt_with_guidance(
+            PromptChanges::Diff(diff),
+            recent_commits,
+            language,
+            prefix_type,
+            with_body.then_some(BodyStyle::default()),
+            &PromptExtras::default(),
+        )
+    }
+
+    /// 現在の言語とテンプレート設定でプロンプトを構築
+    pub fn build_prompt_for(
+        &self,
+        diff: &str,
+        recent_commits: &[String],
+        prefix_type: Option<&str>,
+        with_body: bool,
+    ) -> String {
+        let stat;
+        let changes = if diff.trim().is_empty() {
+            PromptChanges::Empty
+        } else if self.stat_only {
+            stat = stat_only_changes(diff);
+            PromptChanges::Stat(&stat)
+        } else {
+            PromptChanges::Diff(diff)
+        };
+        // suggest_scope: 差分のファイルパスからスコープ候補を求める（--stat-only ではパスがない）
+        let suggested_scope = if self.suggest_scope && !self.stat_only {
+            GitService::common_scope(diff)
+        } else {
+            None
+        };
+        Self::build_prompt_with_guidance(
+            changes,
+            recent_commits,
+            &self.language,
+            prefix_type,
+            with_body.then_some(self.body_style),
+            &PromptExtras {
+                guidance: self.template_guidance.as_deref(),
+                commit_template: self.commit_template.as_deref(),
+                context: &self.context,
+                allowed_types: &self.allowed_types,
+                suggested_scope: suggested_scope.as_deref(),
+                instruction_suffix: self.prompt_suffix.as_deref(),
+                changed_files: self.changed_files.as_deref(),
+                branch: self.branch_context.as_deref(),
+            },
+        )
+    }
+
+    /// テンプレートの追加指示付きでプロンプトを構築
+    ///
+    /// body_style が None の場合は件名のみ（1行）を生成させる
+    pub fn build_prompt_with_guidance(
+        changes: PromptChanges,
+        recent_commits: &[String],
+        language: &str,
+        prefix_type: Option<&str>,
+        body_style: Option<BodyStyle>,
+        extras: &PromptExtras,
     ) -> String {
         let format_section = match prefix_type {
+            Some("conventional") if !extras.allowed_types.is_empty() => {
+                format!(
+                    "Use Conventional Commits format.\nUse only one of these types: {}.",
+                    extras.allowed_types.join(", ")
+                )
+            }
             Some("conventional") => {
                 "Use Conventional Commits format (e.g., feat:, fix:, docs:, refactor:, test:, chore:).".to_string()
             }
@@ -177,7 +1224,7 @@ impl AiService {
                         recent_commits
                             .iter()
                             .enumerate()
-                            .map(|(i, c)| format!("{}. {}", i + 1, c))
+                            .map(|(i, c)| format!("{}. {}", i + 1, c.replace('\n', "\n   ")))
                             .collect::<Vec<_>>()
                             .join("\n")
                     )
@@ -185,24 +1232,70 @@ impl AiService {
             }
         };
 
-        let body_instructions = if with_body {
-            r#"
-Structure:
-- First line: Subject line (concise summary, ideally under 72 characters)
-- Second line: Empty (blank line)
-- Third line onwards: Body with bullet points describing key changes
+        // Conventional Commits の場合のみスコープ候補を示す
+        let format_section = match (prefix_type, extras.suggested_scope) {
+            (Some("conventional"), Some(scope)) => format!(
+                "{}\nSuggested scope (common directory of the changed files): {}",
+                format_section, scope
+            ),
+            _ => format_section,
+        };
 
-Body Guidelines:
-- Use bullet points starting with "- "
-- Each bullet point should describe a specific change
-- Include 2-5 bullet points based on the scope of changes
-- Be specific about what was added, changed, or removed"#
+        let body_instructions = body_instructions_for(
+            language,
+            body_style.is_some(),
+            body_style.unwrap_or_default(),
+        );
+
+        let template_instructions = extras
+            .guidance
+            .map(|g| format!("\n{}", g.trim_end()))
+            .unwrap_or_default();
+        let suffix_instructions = extras
+            .instruction_suffix
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| format!("\n{}", s))
+            .unwrap_or_default();
+        let commit_template_section = extras
+            .commit_template
+            .map(|t| t.trim())
+            .filter(|t| !t.is_empty())
+            .map(|t| {
+                format!(
+                    "Follow this template structure (keep its sections and trailers):\n---\n{}\n---\n\n",
+                    t
+                )
+            })
+            .unwrap_or_default();
+        let context_lines: Vec<String> = extras
+            .context
+            .iter()
+            .map(|c| c.trim())
+            .filter(|c| !c.is_empty())
+            .map(|c| format!("- {}", c))
+            .collect();
+        let context_section = if context_lines.is_empty() {
+            String::new()
         } else {
-            r#"
-Rules:
-- Write only a single line (no multi-line message)
-- Keep it concise (ideally under 72 characters)"#
+            format!(
+                "Additional context from the author:\n{}\n\n",
+                context_lines.join("\n")
+            )
         };
+        let branch_section = extras
+            .branch
+            .map(|b| b.trim())
+            .filter(|b| !b.is_empty())
+            .map(|b| format!("Current branch: {}\n\n", b))
+            .unwrap_or_default();
+        let changed_files_section = extras
+            .changed_files
+            .map(|f| f.trim())
+            .filter(|f| !f.is_empty())
+            .map(|f| format!("Files changed (authoritative):\n{}\n\n", f))
+            .unwrap_or_default();
+        let changes_section = changes.section();
 
         format!(
             r#"Generate a git commit message for the following changes.
@@ -212,18 +1305,15 @@ Rules:
 Instructions:
 - Match the commit message style shown above
 - Write the commit message in {language}
-{body_instructions}
+{body_instructions}{template_instructions}
 - Be specific about what changed
 - Output ONLY the commit message as plain text
 - Do NOT use any markdown formatting (no **, *, `, #, etc.)
 - Do NOT include any explanation, reasoning, or thinking process
 - Do NOT write phrases like "I will...", "Let me...", "Based on...", "Here is..."
-- Respond with the commit message immediately, no preamble
+- Respond with the commit message immediately, no preamble{suffix_instructions}
 
-Changes:
-```diff
-{diff}
-```"#
+{branch_section}{context_section}{commit_template_section}{changed_files_section}{changes_section}"#
         )
     }
 
@@ -242,7 +1332,7 @@ Changes:
         recent_commits: &[String],
         prefix_type: Option<&str>,
         with_body: bool,
-    ) -> Result<String, AppError> {
+    ) -> Result<(String, AiProvider), AppError> {
         self.generate_commit_message_internal(diff, recent_commits, prefix_type, with_body, false)
     }
 
@@ -255,9 +1345,10 @@ Changes:
         with_body: bool,
     ) -> Result<String, AppError> {
         self.generate_commit_message_internal(diff, recent_commits, prefix_type, with_body, true)
+            .map(|(message, _)| message)
     }
 
-    /// 内部実装: コミットメッセージ生成
+    /// 内部実装: コミットメッセージ生成（生成に成功したプロバイダーも返す）
     fn generate_commit_message_internal(
         &self,
         diff: &str,
@@ -265,23 +1356,225 @@ Changes:
         prefix_type: Option<&str>,
         with_body: bool,
         silent: bool,
-    ) -> Result<String, AppError> {
-        let prompt =
-            Self::build_prompt(diff, recent_commits, &self.language, prefix_type, with_body);
-        let mut last_error = None;
+    ) -> Result<(String, AiProvider), AppError> {
+        let prompt = self.build_prompt_for(diff, recent_commits, prefix_type, with_body);
+        let generated = self.generate_from_prompt(&prompt, silent)?;
 
-        for provider in &self.providers {
-            if !Self::is_installed(provider) {
-                continue;
-            }
+        // 禁止語が含まれていれば回避を指示して1回だけ再生成し、残りは伏せ字にする
+        let (message, provider, elapsed) = self.regenerate_if_blocked(&prompt, generated, silent);
 
+        // 許可されていない種類なら1回だけ再生成し、strict_allowed_types ならエラー
+        let (message, provider, elapsed) = if prefix_type == Some("conventional")
+            && !is_allowed_type(&message, &self.allowed_types)
+        {
             if !silent {
-                println!("  {} {}...", "Using".dimmed(), provider.name().cyan());
+                eprintln!(
+                    "  {} {}",
+                    "⚠".yellow(),
+                    "Message uses a type outside allowed_types, regenerating...".yellow()
+                );
+            }
+            let retry_prompt = Self::build_allowed_types_prompt(&prompt, &self.allowed_types);
+            let (message, provider, elapsed) = self
+                .generate_from_prompt(&retry_prompt, silent)
+                .unwrap_or((message, provider, elapsed));
+            if !is_allowed_type(&message, &self.allowed_types) {
+                let found = extract_type(&message).unwrap_or_default();
+                if self.strict_allowed_types {
+                    return Err(AppError::DisallowedCommitType(
+                        found,
+                        self.allowed_types.join(", "),
+                    ));
+                }
+                if !silent {
+                    eprintln!(
+                        "  {} {}",
+                        "⚠".yellow(),
+                        format!("Type \"{}\" is not in allowed_types", found).yellow()
+                    );
+                }
             }
+            (message, provider, elapsed)
+        } else {
+            (message, provider, elapsed)
+        };
+
+        // 再生成の有無にかかわらず、生成1回として記録する
+        self.record_provider_success(&provider, elapsed);
+
+        // --subject-only: 指示に反して付いた本文を取り除く（プレフィックス処理より前）
+        let message = if self.subject_only {
+            first_line_only(&message)
+        } else {
+            message
+        };
 
-            match self.call_provider(provider, &prompt) {
-                Ok(message) => return Ok(message),
-                Err(e) => {
+        Ok((redact_blocked(&message, &self.blocked_terms), provider))
+    }
+
+    /// 生成結果に禁止語が含まれていれば、回避を指示して1回だけ再生成する
+    ///
+    /// 再生成に失敗した場合は元の結果を返す。伏せ字への置換は呼び出し元で行う
+    fn regenerate_if_blocked(
+        &self,
+        prompt: &str,
+        generated: (String, AiProvider, Duration),
+        silent: bool,
+    ) -> (String, AiProvider, Duration) {
+        let Some(term) = contains_blocked(&generated.0, &self.blocked_terms) else {
+            return generated;
+        };
+        if !silent {
+            eprintln!(
+                "  {} {}",
+                "⚠".yellow(),
+                format!(
+                    "Message contains blocked term \"{}\", regenerating...",
+                    term
+                )
+                .yellow()
+            );
+        }
+        let retry_prompt = Self::build_avoid_terms_prompt(prompt, &self.blocked_terms);
+        self.generate_from_prompt(&retry_prompt, silent)
+            .unwrap_or(generated)
+    }
+
+    /// 許可された種類のみを使うよう指示を追加したプロンプトを構築
+    fn build_allowed_types_prompt(prompt: &str, allowed: &[String]) -> String {
+        format!(
+            "{}\n\nIMPORTANT: The commit type MUST be one of: {}",
+            prompt,
+            allowed.join(", ")
+        )
+    }
+
+    /// 禁止語を使わないよう指示を追加したプロンプトを構築
+    fn build_avoid_terms_prompt(prompt: &str, terms: &[String]) -> String {
+        let terms: Vec<&str> = terms
+            .iter()
+            .map(|t| t.trim())
+            .filter(|t| !t.is_empty())
+            .collect();
+        format!(
+            "{}\n\nIMPORTANT: Do NOT use any of the following words in the commit message: {}",
+            prompt,
+            terms.join(", ")
+        )
+    }
+
+    /// ファイル単位の差分を要約するプロンプトを構築
+    pub fn build_file_summary_prompt(path: &str, file_diff: &str, language: &str) -> String {
+        format!(
+            r#"Summarize the following changes to {path} in one short sentence.
+
+Instructions:
+- Write the summary in {language}
+- Output ONLY the summary as plain text on a single line
+- Do NOT include any explanation or preamble
+
+Changes:
+```diff
+{file_diff}
+```"#
+        )
+    }
+
+    /// ファイル単位の差分を1行に要約（大きな差分の分割処理用、出力なし）
+    pub fn summarize_file_diff(&self, path: &str, file_diff: &str) -> Result<String, AppError> {
+        let prompt = Self::build_file_summary_prompt(path, file_diff, &self.language);
+        let (summary, _, _) = self.generate_from_prompt(&prompt, true)?;
+        Ok(summary
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_string())
+    }
+
+    /// 件名を固定して本文のみを生成するプロンプトを構築（--regen-body 用）
+    ///
+    /// 本文の書き方は通常の生成と同じく言語と本文スタイルに合わせた指示を使う
+    pub fn build_body_prompt(
+        diff: &str,
+        subject: &str,
+        language: &str,
+        style: BodyStyle,
+    ) -> String {
+        let body_instructions = body_instructions_for(language, true, style);
+        format!(
+            r#"Write only the body of a git commit message for the following changes.
+
+The subject line is already decided and must not be changed:
+{subject}
+
+Instructions:
+- Write the body in {language}
+{body_instructions}
+- Output ONLY the body, without the subject line
+- Do NOT use any markdown formatting (no **, `, #, etc.)
+- Do NOT include any explanation or preamble
+
+Changes:
+```diff
+{diff}
+```"#
+        )
+    }
+
+    /// 件名を固定して本文のみを生成（--regen-body 用）
+    pub fn generate_body(
+        &self,
+        diff: &str,
+        subject: &str,
+    ) -> Result<(String, AiProvider), AppError> {
+        let prompt = Self::build_body_prompt(diff, subject, &self.language, self.body_style);
+        let generated = self.generate_from_prompt(&prompt, false)?;
+
+        // 本文にも禁止語のチェックを適用する（再生成しても残る語は伏せ字にする）
+        let (response, provider, elapsed) = self.regenerate_if_blocked(&prompt, generated, false);
+        self.record_provider_success(&provider, elapsed);
+        let body = strip_repeated_subject(&response, subject);
+        Ok((redact_blocked(&body, &self.blocked_terms), provider))
+    }
+
+    /// 構築済みのプロンプトをフォールバック付きでプロバイダーに送信
+    ///
+    /// 成功したプロバイダーとその呼び出しの経過時間も返す。成功の記録は呼び出し元で行う
+    fn generate_from_prompt(
+        &self,
+        prompt: &str,
+        silent: bool,
+    ) -> Result<(String, AiProvider, Duration), AppError> {
+        // 成功した呼び出しで終了するため、最後の経過時間が成功したプロバイダーのもの
+        let mut last_elapsed = Duration::ZERO;
+        let (message, provider) = try_provider_passes(
+            &self.providers,
+            self.provider_passes,
+            PROVIDER_PASS_DELAY,
+            silent,
+            |provider| {
+                if !Self::is_installed(provider, &self.provider_paths) {
+                    return None;
+                }
+
+                if !silent {
+                    println!("  {} {}...", "Using".dimmed(), provider.name().cyan());
+                }
+
+                let start = Instant::now();
+                // 進捗を出さない呼び出し（要約や --generate-for）では逐次表示しない
+                let result = self.call_provider(provider, prompt, self.stream && !silent);
+                let elapsed = start.elapsed();
+                last_elapsed = elapsed;
+                if self.verbose {
+                    eprintln!(
+                        "{}",
+                        format_elapsed("provider", provider.config_key(), elapsed).dimmed()
+                    );
+                }
+
+                if let Err(ref e) = result {
                     if !silent {
                         eprintln!(
                             "  {} {} failed: {}",
@@ -292,37 +1585,34 @@ Changes:
                     }
                     // 失敗を記録して次回の優先度を下げる
                     self.record_provider_failure(provider);
-                    last_error = Some(e);
                 }
-            }
-        }
-
-        Err(last_error.unwrap_or(AppError::NoAiProviderInstalled))
+                Some(result)
+            },
+        )?;
+        Ok((message, provider, last_elapsed))
     }
 
     /// 特定のAIプロバイダーを呼び出し
-    fn call_provider(&self, provider: &AiProvider, prompt: &str) -> Result<String, AppError> {
+    ///
+    /// stream が有効な場合は出力を受信しながら薄く表示する。表示は途中経過のみで、
+    /// 最終的なメッセージは出力全体を clean_message で整形して組み立てる
+    fn call_provider(
+        &self,
+        provider: &AiProvider,
+        prompt: &str,
+        stream: bool,
+    ) -> Result<String, AppError> {
         // Build command with stdin support to avoid command line length limits on Windows
         let mut cmd = if cfg!(windows) {
             let mut c = Command::new("cmd");
-            c.args(["/C", provider.command()]);
+            c.args(["/C", provider.executable(&self.provider_paths)]);
             c
         } else {
-            Command::new(provider.command())
+            Command::new(provider.executable(&self.provider_paths))
         };
 
         // Add provider-specific arguments (without the prompt)
-        match provider {
-            AiProvider::Gemini => {
-                cmd.args(["-m", &self.models.gemini]);
-            }
-            AiProvider::Codex => {
-                cmd.args(["exec", "--model", &self.models.codex]);
-            }
-            AiProvider::Claude => {
-                cmd.args(["--model", &self.models.claude, "-p"]);
-            }
-        };
+        cmd.args(self.provider_args(provider));
 
         // Pass prompt via stdin to avoid OS error 206 (filename too long) on Windows
         cmd.stdin(Stdio::piped());
@@ -344,17 +1634,25 @@ Changes:
                 .map_err(|e| AppError::AiProviderError(format!("Failed to write prompt: {}", e)))?;
         }
 
-        let output = child
-            .wait_with_output()
-            .map_err(|e| AppError::AiProviderError(format!("Failed to wait for process: {}", e)))?;
+        let (status, stdout, stderr) = if stream {
+            Self::wait_streamed(child)?
+        } else {
+            let output = child.wait_with_output().map_err(|e| {
+                AppError::AiProviderError(format!("Failed to wait for process: {}", e))
+            })?;
+            (
+                output.status,
+                String::from_utf8_lossy(&output.stdout).to_string(),
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            )
+        };
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        if !status.success() {
             let error_msg = Self::extract_error(&stderr, provider);
             return Err(AppError::AiProviderError(error_msg));
         }
 
-        let message = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        let message = postprocess(provider, stdout.trim());
         let message = Self::clean_message(&message);
 
         if message.is_empty() {
@@ -367,6 +1665,79 @@ Changes:
         Ok(message)
     }
 
+    /// 標準出力を1行ずつ表示しながら子プロセスの終了を待つ（--stream 用）
+    ///
+    /// 標準エラー出力のパイプが詰まらないよう、別スレッドで読み切る
+    fn wait_streamed(mut child: Child) -> Result<(ExitStatus, String, String), AppError> {
+        let stderr_reader = child.stderr.take().map(|mut stderr| {
+            std::thread::spawn(move || {
+                let mut buf = Vec::new();
+                let _ = stderr.read_to_end(&mut buf);
+                buf
+            })
+        });
+
+        let stdout = match child.stdout.take() {
+            Some(stdout) => read_streamed_lines(BufReader::new(stdout), |line| {
+                println!("    {}", line.dimmed());
+            })
+            .map_err(|e| AppError::AiProviderError(format!("Failed to read output: {}", e)))?,
+            None => String::new(),
+        };
+
+        let status = child
+            .wait()
+            .map_err(|e| AppError::AiProviderError(format!("Failed to wait for process: {}", e)))?;
+        let stderr = stderr_reader
+            .and_then(|reader| reader.join().ok())
+            .unwrap_or_default();
+
+        Ok((status, stdout, String::from_utf8_lossy(&stderr).to_string()))
+    }
+
+    /// プロバイダーCLIに渡す引数（プロンプトは stdin で渡すため含まない）
+    ///
+    /// 基本の引数の後に providers_args の追加引数を付与する
+    fn provider_args(&self, provider: &AiProvider) -> Vec<String> {
+        let (base, extra): (Vec<&str>, &[String]) = match provider {
+            AiProvider::Gemini => (vec!["-m", self.models.gemini()], &self.provider_args.gemini),
+            AiProvider::Codex => (
+                vec!["exec", "--model", self.models.codex()],
+                &self.provider_args.codex,
+            ),
+            AiProvider::Claude => (
+                vec!["--model", self.models.claude(), "-p"],
+                &self.provider_args.claude,
+            ),
+        };
+        base.into_iter()
+            .map(String::from)
+            .chain(extra.iter().cloned())
+            .collect()
+    }
+
+    /// providers_args に git-sc が指定する引数（モデル指定など）が含まれていないか検証
+    pub fn validate_provider_args(args: &ProviderArgsConfig) -> Result<(), AppError> {
+        for (provider, extra) in [
+            (AiProvider::Gemini, &args.gemini),
+            (AiProvider::Codex, &args.codex),
+            (AiProvider::Claude, &args.claude),
+        ] {
+            let reserved = provider.reserved_args();
+            if let Some(arg) = extra.iter().find(|arg| {
+                let flag = arg.split('=').next().unwrap_or_default();
+                reserved.contains(&flag)
+            }) {
+                return Err(AppError::ConfigError(format!(
+                    "providers_args.{} に \"{}\" は指定できません（git-sc が指定する引数です）",
+                    provider.config_key(),
+                    arg
+                )));
+            }
+        }
+        Ok(())
+    }
+
     /// stderrからエラーメッセージを抽出
     fn extract_error(stderr: &str, provider: &AiProvider) -> String {
         match provider {
@@ -390,21 +1761,75 @@ Changes:
         }
     }
 
+    /// 先頭に混入したAIの前置き（"Here is the commit message:" など）を削除
+    ///
+    /// 誤検出を避けるため、既知の書き出しで始まり、かつ末尾が ":" "!" "." で終わるか
+    /// "commit message" を含む行で、後続に本文がある場合のみ削除する
+    fn strip_preamble(message: &str) -> String {
+        let mut message = message.trim();
+
+        // "Sure!" の後に "Here is ...:" が続くケースに対応するため最大2行まで
+        for _ in 0..2 {
+            let (first, rest) = match message.split_once('\n') {
+                Some((first, rest)) => (first.trim(), rest.trim()),
+                None => break,
+            };
+            if rest.is_empty() || !Self::is_preamble_line(first) {
+                break;
+            }
+            message = rest;
+        }
+
+        message.to_string()
+    }
+
+    /// 行がAIの前置きかどうかを判定
+    fn is_preamble_line(line: &str) -> bool {
+        let lower = line.to_lowercase().replace('’', "'");
+        let starts_with_preamble = PREAMBLE_STARTS.iter().any(|p| {
+            lower
+                .strip_prefix(p)
+                .is_some_and(|rest| rest.is_empty() || !rest.starts_with(char::is_alphanumeric))
+        });
+
+        starts_with_preamble
+            && (lower.ends_with(':')
+                || lower.ends_with('!')
+                || lower.ends_with('.')
+                || lower.contains("commit message"))
+    }
+
+    /// コードブロックで始まるメッセージから最初のブロックの中身を取り出す
+    ///
+    /// 言語指定付きの開始行（```commit など）や、閉じた後の説明文にも対応する。
+    /// コードブロックで始まらない、または閉じていない場合は None
+    fn extract_fenced_block(message: &str) -> Option<String> {
+        if !message.starts_with("```") {
+            return None;
+        }
+
+        let lines: Vec<&str> = message.lines().collect();
+        let closing = lines
+            .iter()
+            .skip(1)
+            .position(|line| line.trim_start().starts_with("```"))?
+            + 1;
+
+        if closing < 2 {
+            return None;
+        }
+
+        Some(lines[1..closing].join("\n"))
+    }
+
     /// 生成されたメッセージをクリーンアップ
     fn clean_message(message: &str) -> String {
+        // AIの前置きを削除
+        let message = Self::strip_preamble(message);
         let message = message.trim();
 
-        // マークダウンのコードブロックがある場合は削除
-        let message = if message.starts_with("```") && message.ends_with("```") {
-            let lines: Vec<&str> = message.lines().collect();
-            if lines.len() > 2 {
-                lines[1..lines.len() - 1].join("\n")
-            } else {
-                message.to_string()
-            }
-        } else {
-            message.to_string()
-        };
+        // マークダウンのコードブロックがある場合は中身のみを取り出す
+        let message = Self::extract_fenced_block(message).unwrap_or_else(|| message.to_string());
 
         // 先頭と末尾の引用符がある場合は削除
         let message = message.trim_matches('"').trim_matches('\'');
@@ -447,6 +1872,7 @@ impl Default for AiService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::ProviderPriorityConfig;
     use pretty_assertions::assert_eq;
     use rstest::rstest;
 
@@ -457,6 +1883,112 @@ mod tests {
         assert_eq!(AiProvider::Claude.name(), "Claude Code");
     }
 
+    #[test]
+    fn test_ai_provider_executable() {
+        let paths = ProviderPathsConfig {
+            gemini: Some("gemini-2".to_string()),
+            codex: Some("  ".to_string()),
+            claude: None,
+        };
+        assert_eq!(AiProvider::Gemini.executable(&paths), "gemini-2");
+        // 空の指定や未指定はコマンド名にフォールバック
+        assert_eq!(AiProvider::Codex.executable(&paths), "codex");
+        assert_eq!(AiProvider::Claude.executable(&paths), "claude");
+    }
+
+    /// 受け取ったプロンプトを無視して固定のメッセージを返すスクリプトを作成
+    #[cfg(unix)]
+    fn fake_provider_script(dir: &std::path::Path) -> String {
+        use std::os::unix::fs::PermissionsExt;
+        let path = dir.join("fake-gemini");
+        std::fs::write(
+            &path,
+            "#!/bin/sh\ncat > /dev/null\necho 'feat: from configured path'\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_provider_paths_used_for_availability() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut paths = ProviderPathsConfig {
+            gemini: Some(fake_provider_script(dir.path())),
+            ..ProviderPathsConfig::default()
+        };
+        assert!(AiService::is_installed(&AiProvider::Gemini, &paths));
+
+        paths.gemini = Some(dir.path().join("missing").to_string_lossy().to_string());
+        assert!(!AiService::is_installed(&AiProvider::Gemini, &paths));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_provider_paths_used_for_spawning() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.provider_paths.gemini = Some(fake_provider_script(dir.path()));
+        let service = AiService::from_config(&config).unwrap();
+
+        let output = service
+            .call_provider(&AiProvider::Gemini, "prompt", false)
+            .unwrap();
+        assert_eq!(output, "feat: from configured path");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_retried_generation_counts_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config {
+            providers: Some(vec!["gemini".to_string()]),
+            // 生成結果に禁止語を含めて再生成させる
+            blocked_terms: vec!["configured".to_string()],
+            ..Config::default()
+        };
+        config.provider_paths.gemini = Some(fake_provider_script(dir.path()));
+        let mut service = AiService::from_config(&config).unwrap();
+        let state_path = dir.path().join("state");
+        service.state_path = Some(state_path.clone());
+
+        let (message, _) = service
+            .generate_commit_message("test diff", &[], None, false)
+            .unwrap();
+        assert_eq!(message, "feat: from *** path");
+
+        let state = State::load_from(&state_path).unwrap();
+        assert_eq!(state.provider_usage["gemini"], 1);
+        assert_eq!(state.last_success.unwrap().provider, "gemini");
+    }
+
+    // ============================================================
+    // generate_body のテスト
+    // ============================================================
+
+    #[cfg(unix)]
+    #[test]
+    fn test_generate_body_redacts_blocked_terms() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config {
+            providers: Some(vec!["gemini".to_string()]),
+            blocked_terms: vec!["configured".to_string()],
+            ..Config::default()
+        };
+        config.provider_paths.gemini = Some(fake_provider_script(dir.path()));
+        let mut service = AiService::from_config(&config).unwrap();
+        let state_path = dir.path().join("state");
+        service.state_path = Some(state_path.clone());
+
+        let (body, _) = service.generate_body("test diff", "fix: subject").unwrap();
+        assert_eq!(body, "feat: from *** path");
+
+        // 再生成しても生成1回として記録する
+        let state = State::load_from(&state_path).unwrap();
+        assert_eq!(state.provider_usage["gemini"], 1);
+    }
+
     #[test]
     fn test_ai_provider_command() {
         assert_eq!(AiProvider::Gemini.command(), "gemini");
@@ -488,6 +2020,20 @@ mod tests {
         assert_eq!(service.providers.len(), 3);
     }
 
+    #[test]
+    fn test_format_elapsed() {
+        let formatted = format_elapsed("provider", "gemini", Duration::from_millis(2300));
+        assert_eq!(formatted, "provider=gemini elapsed=2.30s");
+    }
+
+    #[test]
+    fn test_ai_service_set_verbose() {
+        let mut service = AiService::new();
+        assert!(!service.verbose);
+        service.set_verbose(true);
+        assert!(service.verbose);
+    }
+
     #[test]
     fn test_ai_service_set_language() {
         let mut service = AiService::new();
@@ -546,6 +2092,21 @@ mod tests {
         assert!(prompt.contains("match their style/format"));
     }
 
+    #[test]
+    fn test_build_prompt_auto_mode_indents_commit_bodies() {
+        let recent_commits = vec!["feat: add login\n\n- add form".to_string()];
+        let prompt = AiService::build_prompt("diff", &recent_commits, "English", None, false);
+        assert!(prompt.contains("1. feat: add login\n   \n   - add form"));
+    }
+
+    #[test]
+    fn test_build_prompt_for_empty_changes() {
+        let service = AiService::from_config(&Config::default()).unwrap();
+        let prompt = service.build_prompt_for("", &[], Some("conventional"), false);
+        assert!(prompt.contains("There are no file changes"));
+        assert!(!prompt.contains("```diff"));
+    }
+
     #[test]
     fn test_build_prompt_contains_diff() {
         let diff = "--- a/file.rs\n+++ b/file.rs\n+new line";
@@ -561,6 +2122,113 @@ mod tests {
         assert!(prompt.contains("```diff"));
     }
 
+    // ============================================================
+    // template_preset のテスト
+    // ============================================================
+
+    #[rstest]
+    #[case("conventional", "conventional", "feat, fix, docs")]
+    #[case("angular", "conventional", "type(scope): subject")]
+    #[case("angular", "conventional", "at most 72 characters")]
+    #[case("gitmoji", "emoji", "✨ feat")]
+    #[case("gitmoji", "emoji", "🐛 fix")]
+    #[case("Gitmoji", "emoji", "Conventional Commits style bullet points")]
+    fn test_template_preset(#[case] name: &str, #[case] prefix_type: &str, #[case] fragment: &str) {
+        let preset = template_preset(name).unwrap();
+        assert_eq!(preset.prefix_type, prefix_type);
+        assert!(preset.guidance.contains(fragment));
+    }
+
+    #[rstest]
+    #[case("feat", ":sparkles:")]
+    #[case("fix", ":bug:")]
+    #[case("docs", ":memo:")]
+    #[case("refactor", ":recycle:")]
+    #[case("perf", ":zap:")]
+    #[case("test", ":white_check_mark:")]
+    #[case("Chore", ":wrench:")]
+    fn test_gitmoji_shortcode_for_type(#[case] t: &str, #[case] expected: &str) {
+        assert_eq!(gitmoji_shortcode_for_type(t), Some(expected));
+    }
+
+    #[test]
+    fn test_gitmoji_shortcode_covers_all_types() {
+        for (t, _) in GITMOJI_TYPES {
+            assert!(gitmoji_shortcode_for_type(t).is_some(), "{}", t);
+        }
+    }
+
+    #[test]
+    fn test_gitmoji_shortcode_for_unknown_type() {
+        assert_eq!(gitmoji_shortcode_for_type("wip"), None);
+    }
+
+    #[rstest]
+    #[case("✨ add login form", ":sparkles: add login form")]
+    #[case("🐛 fix crash on empty input", ":bug: fix crash on empty input")]
+    #[case(
+        "📝 update README\n\n- add usage",
+        ":memo: update README\n\n- add usage"
+    )]
+    #[case("♻️ extract parser", ":recycle: extract parser")]
+    #[case("♻ extract parser", ":recycle: extract parser")]
+    #[case("⚡️ cache results", ":zap: cache results")]
+    #[case("✅ add tests", ":white_check_mark: add tests")]
+    #[case("🔧 tweak config", ":wrench: tweak config")]
+    fn test_emoji_prefix_to_shortcode(#[case] message: &str, #[case] expected: &str) {
+        assert_eq!(emoji_prefix_to_shortcode(message), expected);
+    }
+
+    #[rstest]
+    #[case("feat: add login form")]
+    #[case(":sparkles: add login form")]
+    #[case("add ✨ sparkle")]
+    fn test_emoji_prefix_to_shortcode_leaves_other_subjects(#[case] message: &str) {
+        assert_eq!(emoji_prefix_to_shortcode(message), message);
+    }
+
+    #[test]
+    fn test_template_preset_unknown() {
+        assert!(template_preset("unknown").is_none());
+    }
+
+    #[test]
+    fn test_template_names_all_resolve() {
+        for name in TEMPLATE_NAMES {
+            assert!(template_preset(name).is_some());
+        }
+    }
+
+    #[test]
+    fn test_build_prompt_for_includes_template_guidance() {
+        let mut service = AiService::new();
+        let preset = template_preset("angular").unwrap();
+        service.set_template_guidance(preset.guidance.clone());
+
+        let prompt = service.build_prompt_for("diff", &[], Some(preset.prefix_type), false);
+        assert!(prompt.contains("Use Conventional Commits format"));
+        assert!(prompt.contains("Always include a scope"));
+    }
+
+    #[test]
+    fn test_build_prompt_without_guidance_unchanged() {
+        let service = AiService::new();
+        let prompt = service.build_prompt_for("diff", &[], Some("conventional"), false);
+        assert_eq!(
+            prompt,
+            AiService::build_prompt("diff", &[], "Japanese", Some("conventional"), false)
+        );
+    }
+
+    #[test]
+    fn test_build_file_summary_prompt() {
+        let prompt =
+            AiService::build_file_summary_prompt("src/main.rs", "+fn main() {}", "Japanese");
+        assert!(prompt.contains("changes to src/main.rs"));
+        assert!(prompt.contains("in Japanese"));
+        assert!(prompt.contains("+fn main() {}"));
+    }
+
     #[test]
     fn test_build_prompt_contains_language() {
         let diff = "test diff";
@@ -589,13 +2257,8 @@ mod tests {
     fn test_build_prompt_with_body_true() {
         let diff = "test diff";
         let recent_commits: Vec<String> = vec![];
-        let prompt = AiService::build_prompt(
-            diff,
-            &recent_commits,
-            "Japanese",
-            Some("conventional"),
-            true,
-        );
+        let prompt =
+            AiService::build_prompt(diff, &recent_commits, "English", Some("conventional"), true);
         // Body モードでは body 関連の指示が含まれる
         assert!(prompt.contains("Body"));
         assert!(prompt.contains("bullet point"));
@@ -610,7 +2273,7 @@ mod tests {
         let prompt = AiService::build_prompt(
             diff,
             &recent_commits,
-            "Japanese",
+            "English",
             Some("conventional"),
             false,
         );
@@ -619,47 +2282,495 @@ mod tests {
         assert!(!prompt.contains("bullet point"));
     }
 
+    // ============================================================
+    // body_instructions_for のテスト
+    // ============================================================
+
+    #[rstest]
+    #[case("Japanese")]
+    #[case("ja")]
+    #[case("日本語")]
+    fn test_body_instructions_for_japanese(#[case] language: &str) {
+        let bullets = body_instructions_for(language, true, BodyStyle::Bullets);
+        assert!(bullets.contains("主な変更点を箇条書きで説明する本文"));
+        assert!(bullets.contains("見出しやラベルは書かない"));
+        assert!(!bullets.contains("Subject line"));
+
+        let paragraph = body_instructions_for(language, true, BodyStyle::Paragraph);
+        assert!(paragraph.contains("短い段落"));
+
+        let single = body_instructions_for(language, false, BodyStyle::Bullets);
+        assert!(single.contains("1行のみで書く"));
+    }
+
+    #[rstest]
+    #[case("English")]
+    #[case("Klingon")]
+    fn test_body_instructions_for_falls_back_to_english(#[case] language: &str) {
+        let bullets = body_instructions_for(language, true, BodyStyle::Bullets);
+        assert!(bullets.contains("Subject line"));
+        assert!(bullets.contains("bullet points starting with \"- \""));
+        assert!(bullets.contains("Do not write structural labels"));
+
+        let single = body_instructions_for(language, false, BodyStyle::Bullets);
+        assert!(single.contains("single line"));
+    }
+
+    #[test]
+    fn test_build_prompt_japanese_body_uses_localized_instructions() {
+        let prompt = AiService::build_prompt("test diff", &[], "Japanese", None, true);
+        assert!(prompt.contains("本文のガイドライン:"));
+        assert!(!prompt.contains("Body Guidelines:"));
+    }
+
+    #[test]
+    fn test_build_prompt_body_style_paragraph() {
+        let prompt = AiService::build_prompt_with_guidance(
+            PromptChanges::Diff("test diff"),
+            &[],
+            "English",
+            Some("conventional"),
+            Some(BodyStyle::Paragraph),
+            &PromptExtras::default(),
+        );
+        assert!(prompt.contains("Write 1-3 short paragraphs describing the motivation and changes"));
+        assert!(!prompt.contains("bullet points starting with \"- \""));
+        assert!(!prompt.contains("single line"));
+    }
+
+    #[test]
+    fn test_build_prompt_for_uses_body_style() {
+        let mut service = AiService::new();
+        service.set_language("English".to_string());
+        let bullets = service.build_prompt_for("diff", &[], Some("conventional"), true);
+        assert!(bullets.contains("bullet points starting with \"- \""));
+
+        service.set_body_style(BodyStyle::Paragraph);
+        let paragraph = service.build_prompt_for("diff", &[], Some("conventional"), true);
+        assert!(paragraph.contains("short paragraphs"));
+
+        // 本文なしでは本文スタイルは影響しない
+        let subject_only = service.build_prompt_for("diff", &[], Some("conventional"), false);
+        assert!(subject_only.contains("single line"));
+        assert!(!subject_only.contains("short paragraphs"));
+    }
+
+    #[test]
+    fn test_build_prompt_with_commit_template() {
+        let template = "Summary\n\nWhy:\n\nRefs: JIRA-";
+        let prompt = AiService::build_prompt_with_guidance(
+            PromptChanges::Diff("test diff"),
+            &[],
+            "English",
+            None,
+            Some(BodyStyle::Bullets),
+            &PromptExtras {
+                commit_template: Some(template),
+                ..PromptExtras::default()
+            },
+        );
+        assert!(prompt.contains(
+            "Follow this template structure (keep its sections and trailers):\n---\nSummary\n\nWhy:\n\nRefs: JIRA-\n---"
+        ));
+        // テンプレートは差分の前に置く
+        assert!(prompt.find("Follow this template").unwrap() < prompt.find("```diff").unwrap());
+    }
+
+    #[test]
+    fn test_build_prompt_with_context() {
+        let context = vec![
+            "part of the Q3 auth migration".to_string(),
+            "  ".to_string(),
+            "keeps the old endpoint for now".to_string(),
+        ];
+        let prompt = AiService::build_prompt_with_guidance(
+            PromptChanges::Diff("test diff"),
+            &[],
+            "English",
+            None,
+            None,
+            &PromptExtras {
+                context: &context,
+                ..PromptExtras::default()
+            },
+        );
+        assert!(prompt.contains(
+            "Additional context from the author:\n- part of the Q3 auth migration\n- keeps the old endpoint for now\n"
+        ));
+        // 補足情報は差分の前に置く
+        assert!(prompt.find("Additional context").unwrap() < prompt.find("```diff").unwrap());
+    }
+
+    #[test]
+    fn test_build_prompt_with_prompt_suffix() {
+        let config = Config {
+            prompt_suffix: Some(
+                "- Always mention the ticket number\n- No trailing period\n".to_string(),
+            ),
+            ..Config::default()
+        };
+        let service = AiService::from_config(&config).unwrap();
+        let prompt = service.build_prompt_for("+let ticket = 1;", &[], None, false);
+
+        assert!(prompt.contains(
+            "- Respond with the commit message immediately, no preamble\n- Always mention the ticket number\n- No trailing period\n"
+        ));
+        // 指示は Instructions 内に置き、差分のフェンスには入れない
+        let suffix_pos = prompt.find("Always mention the ticket number").unwrap();
+        assert!(prompt.find("Instructions:").unwrap() < suffix_pos);
+        assert!(suffix_pos < prompt.find("```diff").unwrap());
+        assert_eq!(
+            prompt.matches("Always mention the ticket number").count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_build_prompt_with_changed_files() {
+        let mut service = AiService::new();
+        service.set_changed_files("M\tsrc/app.rs\nA\tsrc/login.rs\nD\tsrc/old.rs\n".to_string());
+        let prompt = service.build_prompt_for("test diff", &[], None, false);
+
+        assert!(prompt.contains(
+            "Files changed (authoritative):\nM\tsrc/app.rs\nA\tsrc/login.rs\nD\tsrc/old.rs\n\nChanges:\n```diff"
+        ));
+
+        let prompt = AiService::new().build_prompt_for("test diff", &[], None, false);
+        assert!(!prompt.contains("Files changed (authoritative)"));
+    }
+
+    #[test]
+    fn test_build_prompt_with_branch_context() {
+        let mut service = AiService::new();
+        service.set_branch_context("fix/login-timeout".to_string());
+        let prompt = service.build_prompt_for("test diff", &[], None, false);
+
+        assert!(prompt.contains("Current branch: fix/login-timeout\n\nChanges:\n```diff"));
+
+        // ブランチがない場合（detached HEAD など）は含めない
+        let prompt = AiService::new().build_prompt_for("test diff", &[], None, false);
+        assert!(!prompt.contains("Current branch:"));
+    }
+
+    #[test]
+    fn test_build_prompt_without_context() {
+        let prompt = AiService::build_prompt("test diff", &[], "English", None, false);
+        assert!(!prompt.contains("Additional context from the author"));
+    }
+
+    #[test]
+    fn test_build_prompt_without_commit_template() {
+        let prompt = AiService::build_prompt("test diff", &[], "English", None, false);
+        assert!(!prompt.contains("Follow this template structure"));
+
+        let mut service = AiService::new();
+        service.set_commit_template("  \n".to_string());
+        let prompt = service.build_prompt_for("test diff", &[], None, false);
+        assert!(!prompt.contains("Follow this template structure"));
+    }
+
+    #[test]
+    fn test_build_prompt_stat_only_omits_diff_fence() {
+        let stat = " src/app.rs | 12 ++++++++----\n 1 file changed, 8 insertions(+), 4 deletions(-)\n\nM\tsrc/app.rs";
+        let prompt = AiService::build_prompt_with_guidance(
+            PromptChanges::Stat(stat),
+            &[],
+            "English",
+            Some("conventional"),
+            None,
+            &PromptExtras::default(),
+        );
+        assert!(!prompt.contains("```diff"));
+        assert!(prompt.contains("Changed files (summary only, source code is not included):"));
+        assert!(prompt.contains("M\tsrc/app.rs"));
+        assert!(prompt.contains(" src/app.rs | 12 ++++++++----"));
+    }
+
+    #[test]
+    fn test_build_prompt_for_stat_only() {
+        let mut service = AiService::new();
+        service.set_stat_only(true);
+        let prompt = service.build_prompt_for("M\tsrc/app.rs", &[], None, false);
+        assert!(!prompt.contains("```diff"));
+        assert!(prompt.contains("M\tsrc/app.rs"));
+    }
+
+    #[test]
+    fn test_build_prompt_for_stat_only_never_sends_code() {
+        let mut service = AiService::new();
+        service.set_stat_only(true);
+        let diff = "\
+diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,2 +1,2 @@
+-fn old() {}
++fn secret_algorithm() { let x = 1337; }
++// note
+diff --git a/b.rs b/b.rs
+new file mode 100644
+--- /dev/null
++++ b/b.rs
+@@ -0,0 +1 @@
++fn b() {}";
+        let prompt = service.build_prompt_for(diff, &[], None, false);
+
+        // 差分が渡されてもファイルごとの変更量だけを送る
+        assert!(prompt.contains(
+            "Changed files (summary only, source code is not included):\nsrc/lib.rs | +2 -1\nb.rs | +1 -0\n"
+        ));
+        assert!(!prompt.contains("secret_algorithm"));
+        assert!(!prompt.contains("fn b()"));
+    }
+
+    #[test]
+    fn test_build_body_prompt_locks_subject() {
+        let prompt = AiService::build_body_prompt(
+            "+fn parse() {}",
+            "feat: add config parser",
+            "English",
+            BodyStyle::Bullets,
+        );
+        assert!(prompt.contains(
+            "The subject line is already decided and must not be changed:\nfeat: add config parser"
+        ));
+        assert!(prompt.contains("Output ONLY the body, without the subject line"));
+        assert!(prompt.contains("Write the body in English"));
+        assert!(prompt.contains("Use bullet points starting with \"- \""));
+        assert!(prompt.contains("```diff\n+fn parse() {}\n```"));
+    }
+
+    #[test]
+    fn test_build_body_prompt_follows_language_and_style() {
+        let prompt = AiService::build_body_prompt(
+            "+fn parse() {}",
+            "feat: 設定パーサーを追加",
+            "Japanese",
+            BodyStyle::Bullets,
+        );
+        assert!(prompt.contains("本文のガイドライン:"));
+        assert!(!prompt.contains("Body Guidelines:"));
+
+        let prompt = AiService::build_body_prompt(
+            "+fn parse() {}",
+            "feat: add config parser",
+            "English",
+            BodyStyle::Paragraph,
+        );
+        assert!(prompt.contains("Do NOT use bullet points or list markers"));
+        assert!(!prompt.contains("Use bullet points starting with"));
+    }
+
+    #[rstest]
+    #[case("- add parser\n- add tests", "- add parser\n- add tests")]
+    #[case("feat: add parser\n\n- add parser", "- add parser")]
+    #[case("feat: add parser", "")]
+    fn test_strip_repeated_subject(#[case] response: &str, #[case] expected: &str) {
+        assert_eq!(
+            strip_repeated_subject(response, "feat: add parser"),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_body_style_from_name() {
+        assert_eq!(BodyStyle::from_name("bullets"), Some(BodyStyle::Bullets));
+        assert_eq!(
+            BodyStyle::from_name("Paragraph"),
+            Some(BodyStyle::Paragraph)
+        );
+        assert_eq!(BodyStyle::from_name("prose"), None);
+        assert_eq!(BodyStyle::default(), BodyStyle::Bullets);
+    }
+
+    #[test]
+    fn test_build_prompt_body_with_auto_mode() {
+        let diff = "test diff";
+        let recent_commits = vec!["feat: previous commit".to_string()];
+        let prompt = AiService::build_prompt(diff, &recent_commits, "English", None, true);
+        // Auto モードでも body 指示が含まれる
+        assert!(prompt.contains("Body"));
+        assert!(prompt.contains("bullet point"));
+    }
+
+    #[test]
+    fn test_clean_message_basic() {
+        let message = "feat: add new feature";
+        assert_eq!(AiService::clean_message(message), "feat: add new feature");
+    }
+
+    #[test]
+    fn test_clean_message_trim_whitespace() {
+        let message = "  feat: add new feature  \n";
+        assert_eq!(AiService::clean_message(message), "feat: add new feature");
+    }
+
+    #[test]
+    fn test_clean_message_remove_code_block() {
+        let message = "```\nfeat: add new feature\n```";
+        assert_eq!(AiService::clean_message(message), "feat: add new feature");
+    }
+
+    #[test]
+    fn test_clean_message_remove_quotes() {
+        let message = "\"feat: add new feature\"";
+        assert_eq!(AiService::clean_message(message), "feat: add new feature");
+
+        let message = "'feat: add new feature'";
+        assert_eq!(AiService::clean_message(message), "feat: add new feature");
+    }
+
+    #[test]
+    fn test_clean_message_code_block_with_language() {
+        let message = "```text\nfeat: add new feature\n```";
+        assert_eq!(AiService::clean_message(message), "feat: add new feature");
+    }
+
+    // ============================================================
+    // known_models / unknown_model_warnings のテスト
+    // ============================================================
+
+    #[test]
+    fn test_known_models_include_defaults() {
+        let defaults = ModelsConfig::default();
+        assert!(known_models(&AiProvider::Gemini).contains(&defaults.gemini()));
+        assert!(known_models(&AiProvider::Codex).contains(&defaults.codex()));
+        assert!(known_models(&AiProvider::Claude).contains(&defaults.claude()));
+    }
+
+    #[test]
+    fn test_unknown_model_warnings_valid_models() {
+        assert!(unknown_model_warnings(&ModelsConfig::default()).is_empty());
+    }
+
     #[test]
-    fn test_build_prompt_body_with_auto_mode() {
-        let diff = "test diff";
-        let recent_commits = vec!["feat: previous commit".to_string()];
-        let prompt = AiService::build_prompt(diff, &recent_commits, "English", None, true);
-        // Auto モードでも body 指示が含まれる
-        assert!(prompt.contains("Body"));
-        assert!(prompt.contains("bullet point"));
+    fn test_unknown_model_warnings_misspelled_model() {
+        let models = ModelsConfig {
+            gemini: Some("falsh".to_string()),
+            ..ModelsConfig::default()
+        };
+        let warnings = unknown_model_warnings(&models);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("models.gemini = \"falsh\""));
+        assert!(warnings[0].contains("flash"));
     }
 
+    // ============================================================
+    // try_provider_passes のテスト
+    // ============================================================
+
     #[test]
-    fn test_clean_message_basic() {
-        let message = "feat: add new feature";
-        assert_eq!(AiService::clean_message(message), "feat: add new feature");
+    fn test_try_provider_passes_success_on_second_pass_stops() {
+        let providers = [AiProvider::Gemini, AiProvider::Codex];
+        let mut calls = Vec::new();
+        let result = try_provider_passes(&providers, 3, Duration::ZERO, true, |provider| {
+            calls.push(*provider);
+            // 1周目は全て失敗、2周目の最初のプロバイダーで成功
+            if calls.len() == 3 {
+                Some(Ok("feat: add".to_string()))
+            } else {
+                Some(Err(AppError::AiProviderError("rate limited".to_string())))
+            }
+        });
+
+        assert_eq!(
+            result.unwrap(),
+            ("feat: add".to_string(), AiProvider::Gemini)
+        );
+        assert_eq!(
+            calls,
+            vec![AiProvider::Gemini, AiProvider::Codex, AiProvider::Gemini]
+        );
     }
 
     #[test]
-    fn test_clean_message_trim_whitespace() {
-        let message = "  feat: add new feature  \n";
-        assert_eq!(AiService::clean_message(message), "feat: add new feature");
+    fn test_try_provider_passes_gives_up_after_all_passes() {
+        let providers = [AiProvider::Gemini, AiProvider::Codex];
+        let mut count = 0;
+        let result = try_provider_passes(&providers, 2, Duration::ZERO, true, |_| {
+            count += 1;
+            Some(Err(AppError::AiProviderError("rate limited".to_string())))
+        });
+
+        assert!(matches!(result, Err(AppError::AiProviderError(_))));
+        assert_eq!(count, 4);
     }
 
     #[test]
-    fn test_clean_message_remove_code_block() {
-        let message = "```\nfeat: add new feature\n```";
-        assert_eq!(AiService::clean_message(message), "feat: add new feature");
+    fn test_try_provider_passes_single_pass_by_default() {
+        let providers = [AiProvider::Claude];
+        let mut count = 0;
+        let result = try_provider_passes(&providers, 0, Duration::ZERO, true, |_| {
+            count += 1;
+            Some(Err(AppError::AiProviderError("failed".to_string())))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(count, 1);
     }
 
     #[test]
-    fn test_clean_message_remove_quotes() {
-        let message = "\"feat: add new feature\"";
-        assert_eq!(AiService::clean_message(message), "feat: add new feature");
+    fn test_try_provider_passes_no_installed_provider_does_not_retry() {
+        let providers = [AiProvider::Gemini, AiProvider::Codex];
+        let mut count = 0;
+        let result = try_provider_passes(&providers, 3, Duration::ZERO, true, |_| {
+            count += 1;
+            None
+        });
+
+        assert!(matches!(result, Err(AppError::NoAiProviderInstalled)));
+        assert_eq!(count, 2);
+    }
 
-        let message = "'feat: add new feature'";
-        assert_eq!(AiService::clean_message(message), "feat: add new feature");
+    // ============================================================
+    // provider_args / validate_provider_args のテスト
+    // ============================================================
+
+    #[rstest]
+    #[case(AiProvider::Gemini, vec!["-m", "flash", "--temperature", "0.2"])]
+    #[case(AiProvider::Codex, vec!["exec", "--model", "gpt-5.1-codex-mini", "-c", "model_reasoning_effort=low"])]
+    #[case(AiProvider::Claude, vec!["--model", "haiku", "-p", "--output-format", "text"])]
+    fn test_provider_args_with_extra_args(
+        #[case] provider: AiProvider,
+        #[case] expected: Vec<&str>,
+    ) {
+        let mut config = Config::default();
+        config.providers_args.gemini = vec!["--temperature".to_string(), "0.2".to_string()];
+        config.providers_args.codex =
+            vec!["-c".to_string(), "model_reasoning_effort=low".to_string()];
+        config.providers_args.claude = vec!["--output-format".to_string(), "text".to_string()];
+        let service = AiService::from_config(&config).unwrap();
+
+        assert_eq!(service.provider_args(&provider), expected);
     }
 
     #[test]
-    fn test_clean_message_code_block_with_language() {
-        let message = "```text\nfeat: add new feature\n```";
-        assert_eq!(AiService::clean_message(message), "feat: add new feature");
+    fn test_provider_args_without_extra_args() {
+        let service = AiService::new();
+        assert_eq!(
+            service.provider_args(&AiProvider::Claude),
+            vec!["--model", "haiku", "-p"]
+        );
+    }
+
+    #[test]
+    fn test_validate_provider_args_accepts_unrelated_flags() {
+        let args = ProviderArgsConfig {
+            gemini: vec!["--temperature".to_string(), "0.2".to_string()],
+            ..Default::default()
+        };
+        assert!(AiService::validate_provider_args(&args).is_ok());
+    }
+
+    #[rstest]
+    #[case(ProviderArgsConfig { gemini: vec!["--model".to_string(), "pro".to_string()], ..Default::default() })]
+    #[case(ProviderArgsConfig { codex: vec!["exec".to_string()], ..Default::default() })]
+    #[case(ProviderArgsConfig { claude: vec!["--model=opus".to_string()], ..Default::default() })]
+    fn test_validate_provider_args_rejects_reserved(#[case] args: ProviderArgsConfig) {
+        assert!(matches!(
+            AiService::validate_provider_args(&args),
+            Err(AppError::ConfigError(_))
+        ));
     }
 
     #[test]
@@ -704,41 +2815,110 @@ mod tests {
     #[test]
     fn test_ai_service_from_config_default() {
         let config = Config::default();
-        let service = AiService::from_config(&config);
+        let service = AiService::from_config(&config).unwrap();
 
         assert_eq!(service.language, "Japanese");
         assert_eq!(service.providers.len(), 3);
-        assert_eq!(service.models.gemini, "flash");
-        assert_eq!(service.models.codex, "gpt-5.1-codex-mini");
-        assert_eq!(service.models.claude, "haiku");
+        assert_eq!(service.models.gemini(), "flash");
+        assert_eq!(service.models.codex(), "gpt-5.1-codex-mini");
+        assert_eq!(service.models.claude(), "haiku");
     }
 
     #[test]
     fn test_ai_service_from_config_custom_providers() {
-        let mut config = Config::default();
-        config.providers = vec!["claude".to_string(), "gemini".to_string()];
-        let service = AiService::from_config(&config);
+        let config = Config {
+            providers: Some(vec!["claude".to_string(), "gemini".to_string()]),
+            ..Config::default()
+        };
+        let service = AiService::from_config(&config).unwrap();
 
         assert_eq!(service.providers.len(), 2);
         assert_eq!(service.providers[0].name(), "Claude Code");
         assert_eq!(service.providers[1].name(), "Gemini CLI");
     }
 
+    #[test]
+    fn test_ai_service_from_config_providers_detailed() {
+        let config = Config {
+            providers_detailed: vec![
+                ProviderPriorityConfig {
+                    name: "gemini".to_string(),
+                    priority: 0,
+                },
+                ProviderPriorityConfig {
+                    name: "codex".to_string(),
+                    priority: 10,
+                },
+            ],
+            ..Config::default()
+        };
+        let service = AiService::from_config(&config).unwrap();
+
+        // providers_detailed が providers より優先される
+        assert_eq!(service.providers.len(), 2);
+        assert!(service.providers.contains(&AiProvider::Codex));
+        assert!(service.providers.contains(&AiProvider::Gemini));
+    }
+
     #[test]
     fn test_ai_service_from_config_invalid_providers_fallback() {
-        let mut config = Config::default();
-        config.providers = vec!["invalid".to_string(), "unknown".to_string()];
-        let service = AiService::from_config(&config);
+        let config = Config {
+            providers: Some(vec!["invalid".to_string(), "unknown".to_string()]),
+            ..Config::default()
+        };
+        let service = AiService::from_config(&config).unwrap();
 
         // 無効なプロバイダーのみの場合はデフォルトにフォールバック
         assert_eq!(service.providers.len(), 3);
     }
 
+    #[test]
+    fn test_ai_service_from_config_empty_providers_is_error() {
+        let config = Config {
+            providers: Some(Vec::new()),
+            ..Config::default()
+        };
+        let err = AiService::from_config(&config).err().unwrap();
+        assert_eq!(err.to_string(), "設定エラー: no providers configured");
+    }
+
+    #[test]
+    fn test_ai_service_from_config_partially_valid_providers() {
+        let config = Config {
+            providers: Some(vec!["claud".to_string(), "gemini".to_string()]),
+            ..Config::default()
+        };
+        let service = AiService::from_config(&config).unwrap();
+
+        // 不明なエントリは無視し、有効なものだけを使う
+        assert_eq!(service.providers, vec![AiProvider::Gemini]);
+    }
+
+    #[test]
+    fn test_unknown_provider_warning() {
+        let names = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            unknown_provider_warning(&names(&["gemini", "claude"])),
+            None
+        );
+        assert_eq!(
+            unknown_provider_warning(&names(&["claud", "gemini"])).unwrap(),
+            "providers に不明なプロバイダーがあります: claud（無視します）"
+        );
+        assert_eq!(
+            unknown_provider_warning(&names(&["invalid", "unknown"])).unwrap(),
+            "providers に不明なプロバイダーがあります: invalid, unknown（既定の gemini, codex, claude を使用します）"
+        );
+    }
+
     #[test]
     fn test_ai_service_from_config_custom_language() {
-        let mut config = Config::default();
-        config.language = "English".to_string();
-        let service = AiService::from_config(&config);
+        let config = Config {
+            language: "English".to_string(),
+            ..Config::default()
+        };
+        let service = AiService::from_config(&config).unwrap();
 
         assert_eq!(service.language, "English");
     }
@@ -746,14 +2926,14 @@ mod tests {
     #[test]
     fn test_ai_service_from_config_custom_models() {
         let mut config = Config::default();
-        config.models.gemini = "pro".to_string();
-        config.models.codex = "gpt-4".to_string();
-        config.models.claude = "opus".to_string();
-        let service = AiService::from_config(&config);
-
-        assert_eq!(service.models.gemini, "pro");
-        assert_eq!(service.models.codex, "gpt-4");
-        assert_eq!(service.models.claude, "opus");
+        config.models.gemini = Some("pro".to_string());
+        config.models.codex = Some("gpt-4".to_string());
+        config.models.claude = Some("opus".to_string());
+        let service = AiService::from_config(&config).unwrap();
+
+        assert_eq!(service.models.gemini(), "pro");
+        assert_eq!(service.models.codex(), "gpt-4");
+        assert_eq!(service.models.claude(), "opus");
     }
 
     // ============================================================
@@ -783,6 +2963,309 @@ mod tests {
         assert!(result.contains("feat: message"));
     }
 
+    // ============================================================
+    // blocked_terms のテスト
+    // ============================================================
+
+    fn terms(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[rstest]
+    #[case("feat: add Falcon integration", &["falcon"], Some("falcon"))]
+    #[case("feat: add FALCON integration", &["Falcon"], Some("Falcon"))]
+    #[case("feat: add login", &["falcon", "damn"], None)]
+    #[case("fix: damn typo", &["falcon", "damn"], Some("damn"))]
+    fn test_contains_blocked(
+        #[case] msg: &str,
+        #[case] blocked: &[&str],
+        #[case] expected: Option<&str>,
+    ) {
+        assert_eq!(
+            contains_blocked(msg, &terms(blocked)),
+            expected.map(|s| s.to_string())
+        );
+    }
+
+    #[test]
+    fn test_contains_blocked_empty_list() {
+        assert_eq!(contains_blocked("feat: add falcon", &[]), None);
+        assert_eq!(contains_blocked("feat: add falcon", &terms(&["  "])), None);
+    }
+
+    #[test]
+    fn test_redact_blocked() {
+        let result = redact_blocked(
+            "feat: add Falcon support\n\n- wire falcon client",
+            &terms(&["falcon"]),
+        );
+        assert_eq!(result, "feat: add *** support\n\n- wire *** client");
+    }
+
+    #[test]
+    fn test_redact_blocked_escapes_regex() {
+        assert_eq!(
+            redact_blocked("fix: c++ parser", &terms(&["c++"])),
+            "fix: *** parser"
+        );
+    }
+
+    #[test]
+    fn test_redact_blocked_empty_list_is_noop() {
+        assert_eq!(redact_blocked("feat: add login", &[]), "feat: add login");
+    }
+
+    #[test]
+    fn test_build_avoid_terms_prompt() {
+        let prompt = AiService::build_avoid_terms_prompt("base", &terms(&["falcon", "", "eagle"]));
+        assert!(prompt.starts_with("base"));
+        assert!(prompt.ends_with(
+            "Do NOT use any of the following words in the commit message: falcon, eagle"
+        ));
+    }
+
+    #[test]
+    fn test_ai_service_from_config_blocked_terms() {
+        let config = Config {
+            blocked_terms: terms(&["falcon"]),
+            ..Config::default()
+        };
+        let service = AiService::from_config(&config).unwrap();
+        assert_eq!(service.blocked_terms, terms(&["falcon"]));
+    }
+
+    // ============================================================
+    // first_line_only のテスト
+    // ============================================================
+
+    #[rstest]
+    #[case(
+        "feat: add parser\n\nThis adds a parser.\n- detail",
+        "feat: add parser"
+    )]
+    #[case("\n  \nfix: typo  \nexplanation", "fix: typo")]
+    #[case("chore: bump version", "chore: bump version")]
+    #[case("", "")]
+    fn test_first_line_only(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(first_line_only(input), expected);
+    }
+
+    // ============================================================
+    // read_streamed_lines のテスト
+    // ============================================================
+
+    #[test]
+    fn test_read_streamed_lines_assembles_full_output() {
+        let input = "feat: add parser\n\n- support nested blocks\n- last line without newline";
+        let mut seen = Vec::new();
+        let output = read_streamed_lines(std::io::Cursor::new(input), |line| {
+            seen.push(line.to_string())
+        })
+        .unwrap();
+
+        assert_eq!(output, input);
+        assert_eq!(
+            seen,
+            vec![
+                "feat: add parser",
+                "",
+                "- support nested blocks",
+                "- last line without newline"
+            ]
+        );
+        // 整形は組み立て後の出力全体に対して行う
+        assert_eq!(
+            AiService::clean_message(output.trim()),
+            "feat: add parser\n\n- support nested blocks\n- last line without newline"
+        );
+    }
+
+    // ============================================================
+    // postprocess のテスト
+    // ============================================================
+
+    #[test]
+    fn test_postprocess_gemini_credentials_notice() {
+        let raw = "Loaded cached credentials.\nfeat: add login\n\n- add form";
+        let message = AiService::clean_message(&postprocess(&AiProvider::Gemini, raw));
+        assert_eq!(message, "feat: add login\n\n- add form");
+    }
+
+    #[test]
+    fn test_postprocess_codex_status_lines() {
+        let raw = "[2025-06-01T10:00:00] thinking\n\n**Summarizing the diff**\n[2025-06-01T10:00:05] codex\n\nfeat: add login\n\n- add form\n[2025-06-01T10:00:06] tokens used: 1234";
+        let message = AiService::clean_message(&postprocess(&AiProvider::Codex, raw));
+        assert_eq!(message, "feat: add login\n\n- add form");
+
+        let raw = "codex\nfix: handle empty password\ntokens used: 42";
+        let message = AiService::clean_message(&postprocess(&AiProvider::Codex, raw));
+        assert_eq!(message, "fix: handle empty password");
+    }
+
+    #[test]
+    fn test_postprocess_claude_sign_off() {
+        let raw = "feat: add login\n\n- add form\n\nLet me know if you'd like any changes!";
+        let message = AiService::clean_message(&postprocess(&AiProvider::Claude, raw));
+        assert_eq!(message, "feat: add login\n\n- add form");
+
+        let raw = "fix: typo\n\n🤖 Generated with [Claude Code](https://claude.ai/code)\n\nCo-Authored-By: Claude <noreply@anthropic.com>";
+        let message = AiService::clean_message(&postprocess(&AiProvider::Claude, raw));
+        assert_eq!(message, "fix: typo");
+    }
+
+    #[rstest]
+    #[case(AiProvider::Gemini)]
+    #[case(AiProvider::Codex)]
+    #[case(AiProvider::Claude)]
+    fn test_postprocess_keeps_clean_output(#[case] provider: AiProvider) {
+        let raw = "feat: add login\n\n- add form\n- feel free to skip the remember-me option";
+        assert_eq!(postprocess(&provider, raw), raw);
+    }
+
+    // ============================================================
+    // allowed_types のテスト
+    // ============================================================
+
+    #[rstest]
+    #[case("feat(x): add parser", Some("feat"))]
+    #[case("fix: typo", Some("fix"))]
+    #[case("Refactor!: drop legacy API\n\n- body", Some("refactor"))]
+    #[case("feat(api)!: change response", Some("feat"))]
+    #[case("Add parser", None)]
+    #[case("", None)]
+    fn test_extract_type(#[case] subject: &str, #[case] expected: Option<&str>) {
+        assert_eq!(extract_type(subject), expected.map(|s| s.to_string()));
+    }
+
+    #[test]
+    fn test_is_allowed_type_catches_disallowed() {
+        let allowed = terms(&["feat", "fix", "chore"]);
+        assert!(is_allowed_type("feat(x): add parser", &allowed));
+        assert!(!is_allowed_type("docs: update README", &allowed));
+        // 種類が取り出せないメッセージも許可しない
+        assert!(!is_allowed_type("Update README", &allowed));
+    }
+
+    #[test]
+    fn test_is_allowed_type_empty_list_allows_any() {
+        assert!(is_allowed_type("docs: update README", &[]));
+        assert!(is_allowed_type("Update README", &[]));
+    }
+
+    #[test]
+    fn test_build_prompt_lists_allowed_types() {
+        let config = Config {
+            allowed_types: terms(&[" feat", "Fix", ""]),
+            ..Config::default()
+        };
+        let service = AiService::from_config(&config).unwrap();
+        let prompt = service.build_prompt_for("diff", &[], Some("conventional"), false);
+        assert!(prompt.contains("Use only one of these types: feat, fix."));
+
+        // conventional 以外の形式では指示しない
+        let prompt = service.build_prompt_for("diff", &[], Some("bracket"), false);
+        assert!(!prompt.contains("Use only one of these types"));
+    }
+
+    #[test]
+    fn test_build_prompt_suggested_scope() {
+        let config = Config {
+            suggest_scope: Some(true),
+            ..Config::default()
+        };
+        let service = AiService::from_config(&config).unwrap();
+        let diff = "diff --git a/src/auth/login.rs b/src/auth/login.rs\n+x";
+        let prompt = service.build_prompt_for(diff, &[], Some("conventional"), false);
+        assert!(prompt.contains("Suggested scope (common directory of the changed files): auth"));
+
+        // conventional 以外、または無効時は示さない
+        let prompt = service.build_prompt_for(diff, &[], Some("bracket"), false);
+        assert!(!prompt.contains("Suggested scope"));
+        let prompt = AiService::new().build_prompt_for(diff, &[], Some("conventional"), false);
+        assert!(!prompt.contains("Suggested scope"));
+    }
+
+    #[test]
+    fn test_build_allowed_types_prompt() {
+        let prompt = AiService::build_allowed_types_prompt("base", &terms(&["feat", "fix"]));
+        assert!(prompt.starts_with("base"));
+        assert!(prompt.ends_with("The commit type MUST be one of: feat, fix"));
+    }
+
+    // ============================================================
+    // strip_preamble のテスト
+    // ============================================================
+
+    #[rstest]
+    #[case("Here is the commit message:\nfeat: add login")]
+    #[case("Here's the commit message:\n\nfeat: add login")]
+    #[case("Sure! Here is a concise commit message:\nfeat: add login")]
+    #[case("Sure!\nHere is the commit message:\nfeat: add login")]
+    #[case("Based on the diff, here is the commit message:\nfeat: add login")]
+    #[case("I’ll write a commit message for these changes.\nfeat: add login")]
+    #[case("Let me summarize the changes:\nfeat: add login")]
+    #[case("The commit message is:\nfeat: add login")]
+    fn test_strip_preamble_variants(#[case] input: &str) {
+        assert_eq!(AiService::strip_preamble(input), "feat: add login");
+    }
+
+    #[rstest]
+    #[case("feat: add login")]
+    #[case("feat: add login\n\n- add form")]
+    // 前置きに似た件名でも末尾が句読点でなければ残す
+    #[case("Based on feedback, adjust retry delay\n\n- lower to 1s")]
+    // "Sure" で始まる別の単語は対象外
+    #[case("Surely handle empty diff:\n\n- return early")]
+    // 後続の本文がない場合は削除しない
+    #[case("Here is the commit message:")]
+    fn test_strip_preamble_keeps_legitimate_messages(#[case] input: &str) {
+        assert_eq!(AiService::strip_preamble(input), input);
+    }
+
+    #[test]
+    fn test_clean_message_strips_preamble_before_code_block() {
+        let input = "Here is the commit message:\n```\nfeat: add login\n```";
+        assert_eq!(AiService::clean_message(input), "feat: add login");
+    }
+
+    // ============================================================
+    // extract_fenced_block のテスト
+    // ============================================================
+
+    #[test]
+    fn test_clean_message_fence_with_trailing_note() {
+        let input = "```\nfeat: add login\n```\nThis message follows Conventional Commits.";
+        assert_eq!(AiService::clean_message(input), "feat: add login");
+    }
+
+    #[test]
+    fn test_clean_message_language_tagged_fence_with_trailing_note() {
+        let input =
+            "```commit\nfeat: add login\n\n- add form\n```\n\nLet me know if you need changes.";
+        assert_eq!(
+            AiService::clean_message(input),
+            "feat: add login\n\n- add form"
+        );
+    }
+
+    #[test]
+    fn test_extract_fenced_block_not_fenced() {
+        assert_eq!(AiService::extract_fenced_block("feat: add login"), None);
+    }
+
+    #[test]
+    fn test_extract_fenced_block_unclosed() {
+        assert_eq!(
+            AiService::extract_fenced_block("```\nfeat: add login"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_extract_fenced_block_empty_block() {
+        assert_eq!(AiService::extract_fenced_block("```\n```"), None);
+    }
+
     #[test]
     fn test_clean_message_empty() {
         let message = "";
@@ -860,6 +3343,127 @@ mod tests {
         assert_eq!(error, "API request failed");
     }
 
+    // ============================================================
+    // detect_language のテスト
+    // ============================================================
+
+    // ============================================================
+    // normalize_language のテスト
+    // ============================================================
+
+    #[rstest]
+    #[case("ja", "Japanese")]
+    #[case("jp", "Japanese")]
+    #[case("JA", "Japanese")]
+    #[case("日本語", "Japanese")]
+    #[case("japanese", "Japanese")]
+    #[case("english", "English")]
+    #[case("en", "English")]
+    #[case(" English ", "English")]
+    #[case("fr", "French")]
+    #[case("Français", "French")]
+    #[case("zh", "Chinese")]
+    #[case("ko", "Korean")]
+    fn test_normalize_language_aliases(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(normalize_language(input), expected);
+    }
+
+    #[test]
+    fn test_normalize_language_unknown_preserved() {
+        assert_eq!(normalize_language("Klingon"), "Klingon");
+        assert_eq!(normalize_language("auto"), "auto");
+    }
+
+    #[test]
+    fn test_set_language_normalizes() {
+        let mut service = AiService::new();
+        service.set_language("en".to_string());
+        assert_eq!(service.language(), "English");
+    }
+
+    #[test]
+    fn test_from_config_normalizes_language() {
+        let config = Config {
+            language: "jp".to_string(),
+            ..Config::default()
+        };
+        let service = AiService::from_config(&config).unwrap();
+        assert_eq!(service.language(), "Japanese");
+    }
+
+    #[test]
+    fn test_detect_language_japanese() {
+        let commits = vec![
+            "feat: ログイン機能を追加".to_string(),
+            "fix: セッション切れのバグを修正".to_string(),
+            "docs: READMEを更新".to_string(),
+        ];
+        assert_eq!(AiService::detect_language(&commits), "Japanese");
+    }
+
+    #[test]
+    fn test_detect_language_english() {
+        let commits = vec![
+            "feat: add login feature".to_string(),
+            "fix: resolve session timeout".to_string(),
+        ];
+        assert_eq!(AiService::detect_language(&commits), "English");
+    }
+
+    #[test]
+    fn test_detect_language_mixed_majority_wins() {
+        let commits = vec![
+            "fix: typo".to_string(),
+            "feat: 設定ファイルの読み込みを追加".to_string(),
+            "refactor: エラー処理を整理".to_string(),
+        ];
+        assert_eq!(AiService::detect_language(&commits), "Japanese");
+    }
+
+    #[test]
+    fn test_detect_language_mixed_english_majority() {
+        let commits = vec![
+            "feat: add retry option".to_string(),
+            "fix: 設定の読み込みを修正".to_string(),
+            "docs: update README".to_string(),
+            "chore: bump version".to_string(),
+        ];
+        assert_eq!(AiService::detect_language(&commits), "English");
+    }
+
+    #[test]
+    fn test_detect_language_tie_prefers_most_recent() {
+        let commits = vec![
+            "chore: update deps".to_string(),
+            "chore: 依存関係を更新".to_string(),
+        ];
+        assert_eq!(AiService::detect_language(&commits), "English");
+    }
+
+    #[test]
+    fn test_detect_language_empty_falls_back_to_english() {
+        assert_eq!(AiService::detect_language(&[]), "English");
+    }
+
+    #[rstest]
+    #[case("修复登录问题", "Chinese")]
+    #[case("로그인 기능 추가", "Korean")]
+    #[case("カタカナのみ", "Japanese")]
+    #[case("update config", "English")]
+    fn test_classify_language(#[case] message: &str, #[case] expected: &str) {
+        assert_eq!(classify_language(message), expected);
+    }
+
+    #[rstest]
+    #[case("auto", true)]
+    #[case("AUTO", true)]
+    #[case("Japanese", false)]
+    fn test_is_auto_language(#[case] language: &str, #[case] expected: bool) {
+        let mut service = AiService::new();
+        service.set_language(language.to_string());
+        assert_eq!(service.is_auto_language(), expected);
+    }
+
     #[test]
     fn test_extract_error_gemini_multiple_api_errors() {
         // 最初のAPI Errorを返す
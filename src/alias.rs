@@ -0,0 +1,177 @@
+use std::process::Command;
+
+use colored::Colorize;
+
+use crate::error::AppError;
+
+/// `git sc` で git-sc を呼び出すためのエイリアス名
+const ALIAS_KEY: &str = "alias.sc";
+
+/// エイリアスの値（シェルコマンドとして git-sc を実行）
+const ALIAS_VALUE: &str = "!git-sc";
+
+/// 既存のエイリアスに対して行う処理
+#[derive(Debug, Clone, PartialEq)]
+enum AliasAction {
+    /// 未設定、または --force で上書きする
+    Install,
+    /// 既に同じ値で設定済み
+    AlreadyInstalled,
+    /// 別の値が設定されている（--force なしでは上書きしない）
+    Conflict(String),
+}
+
+/// 設定先のスコープを git config のオプションに変換
+fn scope_flag(local: bool) -> &'static str {
+    if local {
+        "--local"
+    } else {
+        "--global"
+    }
+}
+
+/// 既存のエイリアスを読み取る git config の引数
+fn get_alias_args(local: bool) -> Vec<&'static str> {
+    vec!["config", scope_flag(local), "--get", ALIAS_KEY]
+}
+
+/// エイリアスを書き込む git config の引数
+fn set_alias_args(local: bool) -> Vec<&'static str> {
+    vec!["config", scope_flag(local), ALIAS_KEY, ALIAS_VALUE]
+}
+
+/// 既存の値と --force の有無から処理を決定
+fn plan_alias(existing: Option<&str>, force: bool) -> AliasAction {
+    match existing {
+        Some(value) if value == ALIAS_VALUE => AliasAction::AlreadyInstalled,
+        Some(value) if !force => AliasAction::Conflict(value.to_string()),
+        _ => AliasAction::Install,
+    }
+}
+
+/// 既存のエイリアスの値を取得（未設定なら None）
+fn read_alias(local: bool) -> Result<Option<String>, AppError> {
+    let output = Command::new("git")
+        .args(get_alias_args(local))
+        .output()
+        .map_err(|_| AppError::GitNotInstalled)?;
+
+    // `git config --get` はキーが存在しない場合に終了コード 1 を返す
+    match output.status.code() {
+        Some(0) => Ok(Some(
+            String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        )),
+        Some(1) => Ok(None),
+        _ => Err(AppError::GitError(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        )),
+    }
+}
+
+/// `git sc` で git-sc を実行できるようエイリアスを設定
+///
+/// local が true ならリポジトリの設定に、false ならユーザー全体の設定に書き込む。
+/// 別の値が設定済みの場合は force が true のときのみ上書きする
+pub fn install(local: bool, force: bool) -> Result<(), AppError> {
+    let scope = if local { "local" } else { "global" };
+    let existing = read_alias(local)?;
+    match plan_alias(existing.as_deref(), force) {
+        AliasAction::AlreadyInstalled => {
+            println!(
+                "{} {} is already set to '{}' ({})",
+                "✓".green(),
+                ALIAS_KEY,
+                ALIAS_VALUE,
+                scope
+            );
+            Ok(())
+        }
+        AliasAction::Conflict(value) => Err(AppError::AliasConflict(value)),
+        AliasAction::Install => {
+            let output = Command::new("git")
+                .args(set_alias_args(local))
+                .output()
+                .map_err(|_| AppError::GitNotInstalled)?;
+            if !output.status.success() {
+                return Err(AppError::GitError(
+                    String::from_utf8_lossy(&output.stderr).trim().to_string(),
+                ));
+            }
+            println!(
+                "{} Set {} = '{}' ({}). You can now run `git sc`.",
+                "✓".green(),
+                ALIAS_KEY,
+                ALIAS_VALUE,
+                scope
+            );
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    // ============================================================
+    // git config 引数のテスト
+    // ============================================================
+
+    #[test]
+    fn test_alias_args_global() {
+        assert_eq!(
+            get_alias_args(false),
+            vec!["config", "--global", "--get", "alias.sc"]
+        );
+        assert_eq!(
+            set_alias_args(false),
+            vec!["config", "--global", "alias.sc", "!git-sc"]
+        );
+    }
+
+    #[test]
+    fn test_alias_args_local() {
+        assert_eq!(
+            get_alias_args(true),
+            vec!["config", "--local", "--get", "alias.sc"]
+        );
+        assert_eq!(
+            set_alias_args(true),
+            vec!["config", "--local", "alias.sc", "!git-sc"]
+        );
+    }
+
+    // ============================================================
+    // plan_alias のテスト
+    // ============================================================
+
+    #[test]
+    fn test_plan_alias_not_set() {
+        assert_eq!(plan_alias(None, false), AliasAction::Install);
+    }
+
+    #[test]
+    fn test_plan_alias_already_installed() {
+        assert_eq!(
+            plan_alias(Some("!git-sc"), false),
+            AliasAction::AlreadyInstalled
+        );
+        assert_eq!(
+            plan_alias(Some("!git-sc"), true),
+            AliasAction::AlreadyInstalled
+        );
+    }
+
+    #[test]
+    fn test_plan_alias_conflict_requires_force() {
+        assert_eq!(
+            plan_alias(Some("status --short"), false),
+            AliasAction::Conflict("status --short".to_string())
+        );
+        assert_eq!(
+            plan_alias(Some("status --short"), true),
+            AliasAction::Install
+        );
+    }
+}
@@ -1,13 +1,23 @@
-use std::io::{self, Write};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use std::time::Instant;
 
 use colored::Colorize;
 use regex::Regex;
 
-use crate::ai::AiService;
-use crate::cli::Cli;
+use crate::ai::{
+    emoji_prefix_to_shortcode, first_line_only, format_elapsed, template_preset, AiProvider,
+    AiService, BodyStyle, ProviderListEntry, TEMPLATE_NAMES,
+};
+use crate::cli::{Cli, MIN_SUBJECT_LENGTH};
 use crate::config::{Config, PrefixRuleConfig, PrefixScriptConfig};
 use crate::error::AppError;
-use crate::git::{GitService, ScriptResult};
+use crate::git::{
+    CommitOptions, DiffBlock, GitService, ScriptResult, DIFF_ALGORITHMS, MAX_DIFF_CHARS,
+};
+use crate::heuristic::heuristic_message;
+use crate::state::State;
 
 /// プレフィックス判定結果
 pub enum PrefixMode {
@@ -21,6 +31,83 @@ pub enum PrefixMode {
     Auto,
 }
 
+/// 件名の先頭文字の大文字・小文字（設定ファイルの subject_case）
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SubjectCase {
+    /// 小文字で始める（feat: add login）
+    Lower,
+    /// 大文字で始める（feat: Add login）
+    Sentence,
+}
+
+impl SubjectCase {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "lower" => Some(SubjectCase::Lower),
+            "sentence" => Some(SubjectCase::Sentence),
+            _ => None,
+        }
+    }
+}
+
+/// --fill のひな形で要約に置き換えるプレースホルダー
+const FILL_PLACEHOLDER: &str = "{{summary}}";
+
+/// --explain で表示する判定の経緯
+#[derive(Debug, PartialEq)]
+struct ExplainTrace {
+    remote_url: Option<String>,
+    branch: Option<String>,
+    /// プレフィックスの決定方法（describe_prefix_mode の結果）
+    prefix: String,
+    /// 並べ替え後のプロバイダーの試行順
+    providers: Vec<&'static str>,
+    /// 生成に成功したプロバイダー（オフライン生成では None）
+    provider_used: Option<&'static str>,
+    diff_truncated: bool,
+}
+
+impl ExplainTrace {
+    /// 1項目1行の `[explain] key: value` 形式で出力する
+    fn render(&self) -> String {
+        let or_none = |value: Option<&str>| value.unwrap_or("(none)").to_string();
+        [
+            ("remote", or_none(self.remote_url.as_deref())),
+            ("branch", or_none(self.branch.as_deref())),
+            ("prefix", self.prefix.clone()),
+            ("providers", self.providers.join(", ")),
+            ("provider used", or_none(self.provider_used)),
+            (
+                "diff truncated",
+                if self.diff_truncated { "yes" } else { "no" }.to_string(),
+            ),
+        ]
+        .iter()
+        .map(|(key, value)| format!("[explain] {}: {}", key, value))
+        .collect::<Vec<_>>()
+        .join("\n")
+    }
+}
+
+/// プレフィックスの決定方法を説明する文字列（pattern は一致したスクリプト・ルールの url_pattern）
+fn describe_prefix_mode(mode: &PrefixMode, pattern: Option<&str>) -> String {
+    let pattern = pattern.unwrap_or("-");
+    match mode {
+        PrefixMode::Script(ScriptResult::Prefix(prefix)) => {
+            format!("script for {} → \"{}\"", pattern, prefix.trim())
+        }
+        PrefixMode::Script(ScriptResult::Empty) => {
+            format!("script for {} → (no prefix)", pattern)
+        }
+        PrefixMode::Script(ScriptResult::Failed) => {
+            format!("script for {} failed → AI-generated format", pattern)
+        }
+        PrefixMode::Rule(prefix_type) => format!("rule for {} → {}", pattern, prefix_type),
+        PrefixMode::Config(prefix_type) => format!("config prefix_type → {}", prefix_type),
+        PrefixMode::Auto => "auto (inferred from recent commits)".to_string(),
+    }
+}
+
 /// 有効な prefix_type 値
 const VALID_PREFIX_TYPES: &[&str] = &["conventional", "bracket", "colon", "emoji", "plain", "none"];
 
@@ -29,6 +116,782 @@ fn is_valid_prefix_type(prefix_type: &str) -> bool {
     VALID_PREFIX_TYPES.contains(&prefix_type)
 }
 
+/// --template と設定の prefix_type から使用する prefix_type を決定
+///
+/// プリセットと異なる prefix_type が明示されている場合はエラー
+fn resolve_template_prefix_type(
+    template: &str,
+    preset_prefix_type: &str,
+    configured: Option<&str>,
+) -> Result<String, AppError> {
+    match configured {
+        Some(prefix_type) if prefix_type != preset_prefix_type => Err(AppError::TemplateConflict(
+            template.to_string(),
+            prefix_type.to_string(),
+        )),
+        _ => Ok(preset_prefix_type.to_string()),
+    }
+}
+
+/// 確認プロンプトの入力元となる端末デバイス
+const TTY_PATH: &str = if cfg!(windows) { "CONIN$" } else { "/dev/tty" };
+
+/// 制御端末を開く（stdinがパイプの場合でも確認入力を受け付けるため）
+fn open_tty() -> Option<Box<dyn BufRead>> {
+    File::open(TTY_PATH)
+        .ok()
+        .map(|f| Box::new(BufReader::new(f)) as Box<dyn BufRead>)
+}
+
+/// 入力から1行読み取り、確認の回答を判定
+///
+/// 空行・"y"・"yes" は承認、EOF（入力なし）はキャンセルとして扱う
+fn read_confirmation(reader: &mut dyn BufRead) -> Result<bool, AppError> {
+    let mut input = String::new();
+    let bytes = reader
+        .read_line(&mut input)
+        .map_err(|e| AppError::GitError(e.to_string()))?;
+
+    if bytes == 0 {
+        return Ok(false);
+    }
+
+    let input = input.trim().to_lowercase();
+    Ok(input.is_empty() || input == "y" || input == "yes")
+}
+
+/// 入力から1行読み取り、明示的に承認されたかを判定
+///
+/// "y"・"yes" のみ承認し、空行や EOF はキャンセルとして扱う（既定で拒否する確認用）
+fn read_explicit_confirmation(reader: &mut dyn BufRead) -> Result<bool, AppError> {
+    let mut input = String::new();
+    reader
+        .read_line(&mut input)
+        .map_err(|e| AppError::GitError(e.to_string()))?;
+
+    let input = input.trim().to_lowercase();
+    Ok(input == "y" || input == "yes")
+}
+
+/// autosquash 用の fixup コミットメッセージを作成
+fn fixup_message(subject: &str) -> String {
+    format!("fixup! {}", subject.trim())
+}
+
+/// 設定ファイルの max_subject_length を検証（短すぎる値は件名を失うため拒否）
+fn validate_max_subject_length(value: Option<usize>) -> Result<Option<usize>, AppError> {
+    match value {
+        Some(max) if max < MIN_SUBJECT_LENGTH => Err(AppError::ConfigError(format!(
+            "max_subject_length must be at least {} (got {})",
+            MIN_SUBJECT_LENGTH, max
+        ))),
+        _ => Ok(value),
+    }
+}
+
+/// 件名を最大文字数に収まるよう単語境界で切り詰める（末尾に "…" を付与）
+///
+/// 文字数は Unicode 文字単位で数える。空白のない件名（日本語など）は文字単位で切る
+fn truncate_subject(subject: &str, max: usize) -> String {
+    if subject.chars().count() <= max {
+        return subject.to_string();
+    }
+    if max == 0 {
+        return String::new();
+    }
+
+    // 省略記号の分を残して文字数でカット
+    let truncated: String = subject.chars().take(max - 1).collect();
+
+    // 最後の空白で切り詰める（単語の途中で切らない）
+    let cut = match truncated.rfind(char::is_whitespace) {
+        Some(pos) if !truncated[..pos].trim_end().is_empty() => truncated[..pos].trim_end(),
+        _ => truncated.as_str(),
+    };
+
+    format!("{}…", cut)
+}
+
+/// 件名の説明部分（コロンより後ろ）の先頭文字を mode に合わせて揃える
+///
+/// コロンがない件名は全体を説明部分として扱う。2文字目以降は変更しない
+fn normalize_subject_case(subject: &str, mode: SubjectCase) -> String {
+    let (head, description) = match subject.find(':') {
+        Some(pos) => subject.split_at(pos + 1),
+        None => ("", subject),
+    };
+    let rest = description.trim_start();
+    let spacing = &description[..description.len() - rest.len()];
+
+    let mut chars = rest.chars();
+    let Some(first) = chars.next() else {
+        return subject.to_string();
+    };
+    let first: String = match mode {
+        SubjectCase::Lower => first.to_lowercase().collect(),
+        SubjectCase::Sentence => first.to_uppercase().collect(),
+    };
+    format!("{}{}{}{}", head, spacing, first, chars.as_str())
+}
+
+/// 差分のアルゴリズムを決定（--diff-algorithm > 設定ファイル）
+///
+/// 設定ファイルの値は CLI と違い clap で検証されないため、ここで既知のものか確認する
+fn resolve_diff_algorithm(
+    cli: Option<&str>,
+    config: Option<&str>,
+) -> Result<Option<String>, AppError> {
+    let Some(algorithm) = cli.or(config) else {
+        return Ok(None);
+    };
+    let algorithm = algorithm.to_lowercase();
+    if !DIFF_ALGORITHMS.contains(&algorithm.as_str()) {
+        return Err(AppError::ConfigError(format!(
+            "Unknown diff_algorithm: {} (expected one of: {})",
+            algorithm,
+            DIFF_ALGORITHMS.join(", ")
+        )));
+    }
+    Ok(Some(algorithm))
+}
+
+/// 本文の各行を width 桁で折り返す（件名とトレーラー行はそのまま）
+///
+/// 箇条書き（`- ` など）の続きの行はマーカーの幅だけ字下げする。
+/// 単語の途中では折り返さず、width より長い単語はそのまま1行に置く。width が 0 なら何もしない
+fn wrap_body(message: &str, width: usize) -> String {
+    let Some((subject, body)) = message.split_once('\n') else {
+        return message.to_string();
+    };
+    if width == 0 {
+        return message.to_string();
+    }
+
+    let trailer = Regex::new(r"^[A-Za-z][A-Za-z0-9-]*: \S").unwrap();
+    let mut lines = vec![subject.to_string()];
+    for line in body.lines() {
+        if line.chars().count() <= width || trailer.is_match(line) {
+            lines.push(line.to_string());
+        } else {
+            lines.extend(wrap_line(line, width));
+        }
+    }
+    lines.join("\n")
+}
+
+/// 1行を width 桁以内の複数行に折り返す（wrap_body 用）
+fn wrap_line(line: &str, width: usize) -> Vec<String> {
+    let content = line.trim_start();
+    let indent = &line[..line.len() - content.len()];
+    let marker = ["- ", "* ", "+ "]
+        .into_iter()
+        .find(|m| content.starts_with(m))
+        .unwrap_or("");
+    let continuation = format!("{}{}", indent, " ".repeat(marker.len()));
+
+    let mut lines = Vec::new();
+    let mut current = format!("{}{}", indent, marker);
+    let mut has_word = false;
+    for word in content[marker.len()..].split_whitespace() {
+        if has_word && current.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::replace(&mut current, continuation.clone()));
+            has_word = false;
+        }
+        if has_word {
+            current.push(' ');
+        }
+        current.push_str(word);
+        has_word = true;
+    }
+    lines.push(current);
+    lines
+}
+
+/// --context の内容にクリップボードのテキストを加える（空なら何も加えない）
+fn with_clipboard_context(mut context: Vec<String>, clipboard: Option<String>) -> Vec<String> {
+    if let Some(text) = clipboard {
+        let text = text.trim();
+        if !text.is_empty() {
+            context.push(text.to_string());
+        }
+    }
+    context
+}
+
+/// --from-clipboard 指定時にクリップボードのテキストを取得（読み取れなければ None）
+#[cfg(feature = "clipboard")]
+fn clipboard_context(cli: &Cli) -> Option<String> {
+    if !cli.from_clipboard {
+        return None;
+    }
+    let text = arboard::Clipboard::new().and_then(|mut c| c.get_text());
+    if let Err(ref e) = text {
+        eprintln!(
+            "{} {}",
+            "⚠".yellow(),
+            format!("Could not read the clipboard: {}", e).yellow()
+        );
+    }
+    text.ok()
+}
+
+/// クリップボード機能なしでビルドした場合は常に None
+#[cfg(not(feature = "clipboard"))]
+fn clipboard_context(_cli: &Cli) -> Option<String> {
+    None
+}
+
+/// AIに送る差分の表示用ブロック（--print-diff）
+///
+/// 区切り行の間は build_prompt に渡す文字列と完全に一致させる
+fn format_print_diff(diff: &str) -> String {
+    format!(
+        "=== Diff sent to AI ({} bytes) ===\n{}\n=== END DIFF ===",
+        diff.len(),
+        diff
+    )
+}
+
+/// --fail-on-empty と empty_changes_behavior から変更がないときの扱いを決定
+///
+/// Some(true) ならエラー、Some(false) なら正常終了、None なら従来どおり（--all のときのみ正常終了）
+fn resolve_fail_on_empty(cli: bool, config: Option<&str>) -> Result<Option<bool>, AppError> {
+    if cli {
+        return Ok(Some(true));
+    }
+    match config.map(str::to_lowercase).as_deref() {
+        None => Ok(None),
+        Some("ok") => Ok(Some(false)),
+        Some("error") => Ok(Some(true)),
+        Some(other) => Err(AppError::ConfigError(format!(
+            "Unknown empty_changes_behavior: {} (expected one of: ok, error)",
+            other
+        ))),
+    }
+}
+
+/// コミットする変更がないときの結果（Ok なら正常終了）
+fn empty_changes_result(stage_all: bool, fail_on_empty: Option<bool>) -> Result<(), AppError> {
+    if !fail_on_empty.unwrap_or(!stage_all) {
+        return Ok(());
+    }
+    if stage_all {
+        Err(AppError::NoChanges)
+    } else {
+        Err(AppError::NoStagedChanges)
+    }
+}
+
+/// コミットをコミット日時の古い順に並べる（同時刻は引数の順を保つ）
+fn sort_chronologically(
+    hashes: &[String],
+    commit_time: impl Fn(&str) -> Result<u64, AppError>,
+) -> Result<Vec<String>, AppError> {
+    let mut timed = hashes
+        .iter()
+        .map(|hash| Ok((commit_time(hash)?, hash.clone())))
+        .collect::<Result<Vec<_>, AppError>>()?;
+    timed.sort_by_key(|(time, _)| *time);
+    Ok(timed.into_iter().map(|(_, hash)| hash).collect())
+}
+
+/// emoji_style の設定値からショートコード表記にするかを判定（未指定は "unicode"）
+fn is_shortcode_emoji_style(style: Option<&str>) -> Result<bool, AppError> {
+    match style.map(str::to_lowercase).as_deref() {
+        None | Some("unicode") => Ok(false),
+        Some("shortcode") => Ok(true),
+        Some(other) => Err(AppError::ConfigError(format!(
+            "Unknown emoji_style: {} (expected one of: unicode, shortcode)",
+            other
+        ))),
+    }
+}
+
+/// diff_mode の設定値から単語単位の差分を使うかを判定（未指定は "line"）
+fn is_word_diff_mode(mode: Option<&str>) -> Result<bool, AppError> {
+    match mode.map(str::to_lowercase).as_deref() {
+        None | Some("line") => Ok(false),
+        Some("word") => Ok(true),
+        Some(other) => Err(AppError::ConfigError(format!(
+            "Unknown diff_mode: {} (expected one of: line, word)",
+            other
+        ))),
+    }
+}
+
+/// メッセージの件名（1行目）のみを切り詰め、本文はそのまま残す
+fn truncate_message_subject(message: &str, max: usize) -> String {
+    match message.split_once('\n') {
+        Some((subject, rest)) => format!("{}\n{}", truncate_subject(subject, max), rest),
+        None => truncate_subject(message, max),
+    }
+}
+
+/// メッセージ末尾に空行を挟んでトレーラー行を追加
+///
+/// 既存の末尾空白は取り除き、空行が重複しないようにする
+fn append_trailers(message: &str, trailers: &[String]) -> String {
+    let trailers: Vec<&str> = trailers
+        .iter()
+        .map(|t| t.trim())
+        .filter(|t| !t.is_empty())
+        .collect();
+    if trailers.is_empty() {
+        return message.to_string();
+    }
+
+    format!("{}\n\n{}", message.trim_end(), trailers.join("\n"))
+}
+
+/// コミットメッセージ末尾のトレーラー（`Signed-off-by:` など）を抽出
+///
+/// 最後の段落がすべて `Key: value` 形式の行で、件名以外の段落である場合のみトレーラーとみなす
+fn extract_trailers(full_message: &str) -> Vec<String> {
+    let trailer = Regex::new(r"^[A-Za-z][A-Za-z0-9-]*: \S").unwrap();
+    let paragraphs: Vec<&str> = full_message
+        .trim()
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .collect();
+    match paragraphs.as_slice() {
+        [_, .., last] if last.lines().all(|line| trailer.is_match(line.trim())) => {
+            last.lines().map(|line| line.trim().to_string()).collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// 再生成したメッセージに元のトレーラーを付け直す（既に含まれる行は重複させない）
+fn reattach_trailers(message: &str, preserved: &[String]) -> String {
+    let missing: Vec<String> = preserved
+        .iter()
+        .filter(|t| !message.lines().any(|line| line.trim() == t.as_str()))
+        .cloned()
+        .collect();
+    append_trailers(message, &missing)
+}
+
+/// footer_template のプレースホルダ（{branch}, {remote}, {date}）を置換
+///
+/// 取得できない値は空文字列になる
+fn render_footer(template: &str, branch: Option<&str>, remote: Option<&str>, date: &str) -> String {
+    template
+        .replace("{branch}", branch.unwrap_or_default())
+        .replace("{remote}", remote.unwrap_or_default())
+        .replace("{date}", date)
+}
+
+/// 今日の日付（UTC、YYYY-MM-DD）
+fn today_utc() -> String {
+    let days = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86_400)
+        .unwrap_or_default() as i64;
+    format_civil_date(days)
+}
+
+/// 1970-01-01 からの経過日数を YYYY-MM-DD に変換
+fn format_civil_date(days: i64) -> String {
+    // Howard Hinnant の civil_from_days アルゴリズム
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// git がコミットメッセージファイルに挿入するシザーズ行（この行以降は差分などの参考情報）
+const SCISSORS_MARKER: &str = "------------------------ >8 ------------------------";
+
+/// コミットメッセージファイルの内容をシザーズ行の前後に分割
+///
+/// シザーズ行がなければ (全体, None)、あれば (シザーズ行より前, シザーズ行以降)
+fn split_on_scissors(content: &str) -> (String, Option<String>) {
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        if trimmed.ends_with(SCISSORS_MARKER) && trimmed.len() > SCISSORS_MARKER.len() {
+            return (
+                content[..offset].to_string(),
+                Some(content[offset..].to_string()),
+            );
+        }
+        offset += line.len();
+    }
+    (content.to_string(), None)
+}
+
+/// 生成したメッセージをコミットメッセージファイルの先頭に挿入
+///
+/// 既存のコメント行は残し、シザーズ行以降はそのまま末尾に保持する
+fn insert_into_message_file(content: &str, message: &str) -> String {
+    let (above, below) = split_on_scissors(content);
+    let mut result = format!("{}\n", message.trim_end());
+    let above = above.trim_start_matches('\n');
+    if !above.is_empty() {
+        result.push('\n');
+        result.push_str(above);
+    }
+    if let Some(below) = below {
+        if !result.ends_with('\n') {
+            result.push('\n');
+        }
+        result.push_str(&below);
+    }
+    result
+}
+
+/// --per-commit の出力を整形（"短縮ハッシュ: メッセージ"、本文付きの場合は空行で区切る）
+fn format_per_commit_messages(entries: &[(String, String)]) -> String {
+    let multiline = entries.iter().any(|(_, message)| message.contains('\n'));
+    let separator = if multiline { "\n\n" } else { "\n" };
+    entries
+        .iter()
+        .map(|(hash, message)| format!("{}: {}", short_hash(hash), message))
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// --fill のひな形のプレースホルダーを要約で置き換える（プレースホルダーがなければ None）
+fn fill_skeleton(skeleton: &str, summary: &str) -> Option<String> {
+    skeleton.contains(FILL_PLACEHOLDER).then(|| {
+        skeleton
+            .replace(FILL_PLACEHOLDER, summary)
+            .trim_end()
+            .to_string()
+    })
+}
+
+/// 課題番号参照のデフォルトの正規表現（GitHub 形式）
+///
+/// 語の先頭（行頭・空白・括弧・`/` の直後）にある `#N` のみを対象にし、`ref` グループを参照とする
+const DEFAULT_ISSUE_REF_PATTERN: &str = r"(?:^|[\s(\[/])(?P<ref>#\d+)\b";
+
+/// 課題番号フッターのデフォルトのキーワード
+const DEFAULT_ISSUE_REF_KEYWORD: &str = "Closes";
+
+/// 数字のみのカラーコード（`#000000` など）らしい参照かを判定
+///
+/// 課題番号は 0 から始まらず、6桁・8桁の数字は CSS の色指定であることが多い
+fn looks_like_color_code(reference: &str) -> bool {
+    let digits = reference.trim_start_matches('#');
+    digits.starts_with('0') || matches!(digits.len(), 6 | 8)
+}
+
+/// ブランチ名（scan_diff なら差分の追加行も）から課題番号の参照を出現順・重複なしで抽出
+///
+/// 正規表現に `ref` という名前のグループがあればその部分を参照とする。
+/// 差分の追加行ではカラーコードらしい参照を除く
+fn extract_issue_refs(
+    diff: &str,
+    branch: Option<&str>,
+    pattern: &Regex,
+    scan_diff: bool,
+) -> Vec<String> {
+    let added_lines = diff
+        .lines()
+        .filter(|line| scan_diff && line.starts_with('+') && !line.starts_with("+++"));
+
+    let mut refs: Vec<String> = Vec::new();
+    let sources = branch
+        .into_iter()
+        .map(|b| (b, false))
+        .chain(added_lines.map(|line| (line, true)));
+    for (text, from_diff) in sources {
+        for caps in pattern.captures_iter(text) {
+            let Some(m) = caps.name("ref").or_else(|| caps.get(0)) else {
+                continue;
+            };
+            if from_diff && looks_like_color_code(m.as_str()) {
+                continue;
+            }
+            if !refs.iter().any(|r| r == m.as_str()) {
+                refs.push(m.as_str().to_string());
+            }
+        }
+    }
+    refs
+}
+
+/// 課題番号の参照を "Closes #N" 形式のフッター行に変換
+fn issue_ref_footers(refs: &[String], keyword: &str) -> Vec<String> {
+    refs.iter().map(|r| format!("{} {}", keyword, r)).collect()
+}
+
+/// 件名が Conventional Commits 形式（`type(scope)!: description`）かを判定
+fn is_conventional(subject: &str) -> bool {
+    let re = Regex::new(r"^[a-z]+(\([^()\s]+\))?!?: \S").unwrap();
+    re.is_match(subject)
+}
+
+/// 生成に使用したプロバイダーを示す成功メッセージの補足行
+fn committed_with(provider: AiProvider) -> String {
+    format!("Committed with {}", provider.name())
+}
+
+/// --list-providers の1行を整形（position は1始まりの試行順）
+fn format_provider_entry(position: usize, entry: &ProviderListEntry) -> String {
+    let mut status = vec![if entry.installed {
+        "installed".to_string()
+    } else {
+        "not installed".to_string()
+    }];
+    if let Some(secs) = entry.demoted_secs {
+        // 残り時間は分単位で切り上げ
+        status.push(format!("demoted for {}m", secs.div_ceil(60)));
+    }
+    format!("{}. {} ({})", position, entry.name, status.join(", "))
+}
+
+/// `--coauthor` の値を検証し `Co-authored-by:` トレーラー行に変換
+///
+/// 値は `Name <email>` 形式である必要がある
+fn coauthor_trailer(value: &str) -> Result<String, AppError> {
+    let identity =
+        normalize_identity(value).ok_or_else(|| AppError::InvalidCoauthor(value.to_string()))?;
+    Ok(format!("Co-authored-by: {}", identity))
+}
+
+/// `--author` の値を検証して `Name <email>` 形式に正規化
+fn validate_author(value: &str) -> Result<String, AppError> {
+    normalize_identity(value).ok_or_else(|| AppError::InvalidAuthor(value.to_string()))
+}
+
+/// `Name <email>` 形式の値を正規化（形式が不正なら None）
+fn normalize_identity(value: &str) -> Option<String> {
+    let re = Regex::new(r"^([^<>]+?)\s*<([^<>\s@]+@[^<>\s@]+)>$").unwrap();
+    let caps = re.captures(value.trim())?;
+    Some(format!("{} <{}>", caps[1].trim(), &caps[2]))
+}
+
+/// `--append` と `--coauthor` からメッセージ末尾に付与するトレーラー行をまとめる
+fn build_trailers(append: &[String], coauthors: &[String]) -> Result<Vec<String>, AppError> {
+    let mut trailers = append.to_vec();
+    for coauthor in coauthors {
+        trailers.push(coauthor_trailer(coauthor)?);
+    }
+    Ok(trailers)
+}
+
+/// ハッシュを表示用の短縮形にする
+fn short_hash(hash: &str) -> &str {
+    hash.get(..7).unwrap_or(hash)
+}
+
+/// 書式参照用の直近コミット数のデフォルト
+const DEFAULT_STYLE_REFERENCE_COMMITS: usize = 5;
+
+/// 書式参照用の直近コミット数を決定（CLI > 設定ファイル > デフォルト）
+fn resolve_style_reference_commits(cli: Option<usize>, config: Option<usize>) -> usize {
+    cli.or(config).unwrap_or(DEFAULT_STYLE_REFERENCE_COMMITS)
+}
+
+/// git や GitHub が自動生成するマージコミットの件名の先頭
+const MERGE_SUBJECT_PREFIXES: [&str; 5] = [
+    "Merge branch ",
+    "Merge remote-tracking branch ",
+    "Merge pull request ",
+    "Merge tag ",
+    "Merge commit ",
+];
+
+/// 書式参照から自動生成された revert・マージコミットを取り除く（順序は維持）
+fn filter_style_reference(commits: &[String]) -> Vec<String> {
+    commits
+        .iter()
+        .filter(|commit| {
+            let subject = commit.lines().next().unwrap_or_default();
+            !subject.starts_with("Revert \"")
+                && !MERGE_SUBJECT_PREFIXES
+                    .iter()
+                    .any(|prefix| subject.starts_with(prefix))
+        })
+        .cloned()
+        .collect()
+}
+
+/// 書式参照用の直近コミットを取得（--no-recent-commits では取得せず空にする）
+///
+/// 空の場合、Auto モードのプロンプトは Conventional Commits を既定とする
+fn fetch_style_reference(
+    no_recent_commits: bool,
+    fetch: impl FnOnce() -> Result<Vec<String>, AppError>,
+) -> Result<Vec<String>, AppError> {
+    if no_recent_commits {
+        Ok(Vec::new())
+    } else {
+        fetch()
+    }
+}
+
+/// squash のベースを決定（--squash-upstream では追跡ブランチ、未設定なら NoBaseBranch）
+fn resolve_squash_base(
+    explicit: Option<&str>,
+    use_upstream: bool,
+    upstream: impl FnOnce() -> Option<String>,
+) -> Result<String, AppError> {
+    match explicit {
+        Some(base) => Ok(base.to_string()),
+        None if use_upstream => upstream().ok_or(AppError::NoBaseBranch),
+        None => Err(AppError::NoBaseBranch),
+    }
+}
+
+/// --squash-last N の起点となるコミット（HEAD~N）を決定
+///
+/// 2件未満の指定、HEAD~N が存在しない場合、範囲にマージコミットを含む場合はエラー
+fn resolve_squash_last(
+    n: usize,
+    resolve: impl FnOnce(&str) -> Option<String>,
+    has_merges: impl FnOnce(usize) -> Result<bool, AppError>,
+) -> Result<String, AppError> {
+    if n < 2 {
+        return Err(AppError::NoCommitsToSquash);
+    }
+    let rev = format!("HEAD~{}", n);
+    let base = resolve(&rev).ok_or_else(|| {
+        AppError::GitError(format!(
+            "{} does not exist (the branch has {} or fewer commits)",
+            rev, n
+        ))
+    })?;
+    if has_merges(n)? {
+        return Err(AppError::HasMergeCommits);
+    }
+    Ok(base)
+}
+
+/// フィルタ後の差分が空のとき、ファイルがステージされていればファイル一覧の概要を返す
+///
+/// バイナリ・.git-sc-ignore の対象・空白のみの変更だけでもメッセージを生成できるようにする。
+/// 何もステージされていなければ None（summary は呼ばない）
+fn filtered_out_summary(
+    staged_files: &[String],
+    summary: impl FnOnce() -> Result<String, AppError>,
+) -> Result<Option<String>, AppError> {
+    if staged_files.is_empty() {
+        return Ok(None);
+    }
+    summary().map(Some)
+}
+
+/// unified diff らしい内容かどうか（--patch の入力検証用）
+///
+/// `diff ` で始まる行、または `--- ` の直後に `+++ ` が続く行があれば差分とみなす。
+/// git format-patch のメールヘッダーは読み飛ばされる
+fn looks_like_diff(content: &str) -> bool {
+    let lines: Vec<&str> = content.lines().collect();
+    lines.iter().enumerate().any(|(i, line)| {
+        line.starts_with("diff ")
+            || (line.starts_with("--- ")
+                && lines
+                    .get(i + 1)
+                    .is_some_and(|next| next.starts_with("+++ ")))
+    })
+}
+
+/// 維持する件名と生成した本文からメッセージを組み立てる（--regen-body / --keep-subject）
+///
+/// 既存の本文は使わず、生成した本文で置き換える
+fn message_with_body(subject: &str, body: &str) -> String {
+    let body = body.trim();
+    if body.is_empty() {
+        subject.to_string()
+    } else {
+        format!("{}\n\n{}", subject, body)
+    }
+}
+
+/// squash のドライランで表示する git 操作の計画
+fn squash_plan(merge_base: &str, commit_count: usize) -> String {
+    format!(
+        "Would soft-reset to {} and commit {} commit(s) as one",
+        short_hash(merge_base),
+        commit_count
+    )
+}
+
+/// reword のドライランで表示する git 操作の計画
+///
+/// 直前のコミットは rebase せず amend で書き換える
+fn reword_plan(rebase_target: &str, position: usize, hash: &str) -> String {
+    if position == 1 {
+        format!("Would amend HEAD ({})", short_hash(hash))
+    } else {
+        format!(
+            "Would rebase -i {} changing commit {} ({})",
+            rebase_target,
+            position,
+            short_hash(hash)
+        )
+    }
+}
+
+/// amend-at のドライランで表示する git 操作の計画
+///
+/// 直前のコミットは rebase せず amend で取り込む
+fn amend_at_plan(rebase_target: &str, position: usize, hash: &str) -> String {
+    if position == 1 {
+        format!(
+            "Would amend HEAD ({}) with staged changes",
+            short_hash(hash)
+        )
+    } else {
+        format!(
+            "Would rebase -i {} stopping at commit {} ({}) to fold in staged changes",
+            rebase_target,
+            position,
+            short_hash(hash)
+        )
+    }
+}
+
+/// 分割処理で要約するファイル数の上限（1ファイルごとにAI呼び出しが発生するため）
+const MAX_SUMMARIZED_FILES: usize = 5;
+
+/// 文字数制限で切り捨てられるブロックのうち、大きい順に要約対象を選ぶ
+///
+/// 戻り値は `blocks` のインデックス（サイズの降順）
+fn select_blocks_to_summarize(blocks: &[DiffBlock], limit: usize, max_files: usize) -> Vec<usize> {
+    let mut offset = 0;
+    let mut cut: Vec<(usize, usize)> = Vec::new();
+
+    for (index, block) in blocks.iter().enumerate() {
+        let size = block.content.chars().count();
+        // 改行分を含めた累積文字数が制限を超えるブロックは切り捨て対象
+        if offset + size > limit {
+            cut.push((index, size));
+        }
+        offset += size + 1;
+    }
+
+    cut.sort_by_key(|&(_, size)| std::cmp::Reverse(size));
+    cut.into_iter()
+        .take(max_files)
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// ファイル単位の要約と切り詰めた差分を結合してプロンプト用の差分を作る
+fn build_chunked_diff(summaries: &[(String, String)], truncated_diff: &str) -> String {
+    let summary_lines: Vec<String> = summaries
+        .iter()
+        .map(|(path, summary)| format!("# {}: {}", path, summary))
+        .collect();
+
+    format!(
+        "# Summaries of large files that are cut off below:\n{}\n\n{}",
+        summary_lines.join("\n"),
+        truncated_diff
+    )
+}
+
 /// アプリケーションのメインオーケストレーター
 pub struct App {
     git: GitService,
@@ -39,41 +902,242 @@ pub struct App {
     prefix_type: Option<String>,
     /// 設定ファイルで指定された auto_push
     auto_push: Option<bool>,
+    /// デバッグ出力を行うかどうか（--debug）
+    debug: bool,
+    /// 件名の最大文字数（--max-subject または設定ファイル）
+    max_subject_length: Option<usize>,
+    /// 件名の先頭文字の大文字・小文字（設定ファイルの subject_case）
+    subject_case: Option<SubjectCase>,
+    /// 絵文字プレフィックスをショートコードで書くか（設定ファイルの emoji_style）
+    emoji_shortcode: bool,
+    /// 本文を折り返す桁数（--wrap または設定ファイル、0 なら折り返さない）
+    body_wrap_width: Option<usize>,
+    /// 大きな差分をファイル単位で要約するか
+    chunk_large_diffs: bool,
+    /// メッセージ末尾に付与するトレーラー行（--append / --coauthor）
+    trailers: Vec<String>,
+    /// メッセージ末尾に付与するフッターのテンプレート（設定ファイルの footer_template）
+    footer_template: Option<String>,
+    /// 課題番号参照の正規表現（issue_refs 無効時は None）
+    issue_ref_pattern: Option<Regex>,
+    /// 差分の追加行も課題番号参照の検索対象にするか
+    issue_refs_from_diff: bool,
+    /// 課題番号フッターのキーワード
+    issue_ref_keyword: String,
+    /// 件名が Conventional Commits 形式でなければエラーにするか（--force-conventional）
+    force_conventional: bool,
+    /// 書式参照用の直近コミットに本文も含めるか
+    recent_commit_bodies: bool,
+    /// 書式参照用に取得する直近コミットの件数
+    style_reference_commits: usize,
+    /// 直近コミットを書式参照に使わないか（--no-recent-commits）
+    no_recent_commits: bool,
+    /// 書式参照から revert・マージコミットを除くか
+    filter_style_reference: bool,
+    /// ステージ済みファイルの一覧をプロンプトに含めるか
+    list_changed_files: bool,
+    /// 変更がないときにエラーにするか（None なら --all のときのみ正常終了）
+    fail_on_empty: Option<bool>,
+    /// 状態ファイルをリポジトリ単位で管理するか（--undo の記録に使用）
+    scope_state_to_repo: bool,
+    /// コミットの作成者（--author）
+    author: Option<String>,
 }
 
 impl App {
     /// 新しいAppインスタンスを作成
     pub fn new(cli: &Cli) -> Result<Self, AppError> {
-        let config = Config::load()?;
+        // --no-config 指定時は設定ファイルを読まずデフォルト + CLIオプションのみで動作
+        let config = Config::load_with(cli.no_config)?;
 
         // デバッグモード: 設定ファイル情報を表示
         if cli.debug {
-            Self::print_config_debug(&config)?;
+            Self::print_config_debug(&config, cli.no_config)?;
         }
 
-        let mut ai = AiService::from_config(&config);
+        AiService::validate_provider_args(&config.providers_args)?;
+        let mut ai = AiService::from_config(&config)?;
+        ai.set_verbose(cli.verbose);
+        ai.set_stream(cli.stream);
+        // 設定ファイルの subject_only は --body 指定時には適用しない
+        ai.set_subject_only(
+            cli.subject_only || (config.subject_only.unwrap_or(false) && !cli.with_body),
+        );
+        ai.set_stat_only(cli.stat_only);
+        ai.set_context(with_clipboard_context(
+            cli.context.clone(),
+            clipboard_context(cli),
+        ));
+        if let Some(passes) = cli.retry_providers {
+            ai.set_provider_passes(passes);
+        }
 
         // CLIで言語が指定されていれば上書き
         if let Some(ref lang) = cli.language {
             ai.set_language(lang.clone());
         }
 
+        // --body-style: 本文の書き方
+        if let Some(ref name) = cli.body_style {
+            let style = BodyStyle::from_name(name).ok_or_else(|| {
+                AppError::ConfigError(format!(
+                    "Unknown body style: {} (expected one of: bullets, paragraph)",
+                    name
+                ))
+            })?;
+            ai.set_body_style(style);
+        }
+
+        // --template: プリセットの prefix_type と追加指示を適用
+        let mut prefix_type = config.prefix_type.clone();
+        if let Some(ref template) = cli.template {
+            let preset = template_preset(template).ok_or_else(|| {
+                AppError::ConfigError(format!(
+                    "Unknown template: {} (expected one of: {})",
+                    template,
+                    TEMPLATE_NAMES.join(", ")
+                ))
+            })?;
+            prefix_type = Some(resolve_template_prefix_type(
+                template,
+                preset.prefix_type,
+                prefix_type.as_deref(),
+            )?);
+            ai.set_template_guidance(preset.guidance);
+        }
+
+        // subject_case: 設定値を検証しておく
+        let subject_case = config
+            .subject_case
+            .as_deref()
+            .map(|name| {
+                SubjectCase::from_name(name).ok_or_else(|| {
+                    AppError::ConfigError(format!(
+                        "Unknown subject_case: {} (expected one of: lower, sentence)",
+                        name
+                    ))
+                })
+            })
+            .transpose()?;
+
+        let emoji_shortcode = is_shortcode_emoji_style(config.emoji_style.as_deref())?;
+
+        // --append / --coauthor / --author は生成前に検証しておく
+        let trailers = build_trailers(&cli.append, &cli.coauthor)?;
+        let author = cli.author.as_deref().map(validate_author).transpose()?;
+
+        // issue_refs: 課題番号参照の正規表現を検証しておく
+        let issue_ref_pattern = if config.issue_refs.unwrap_or(false) {
+            let pattern = config
+                .issue_ref_pattern
+                .as_deref()
+                .unwrap_or(DEFAULT_ISSUE_REF_PATTERN);
+            Some(Regex::new(pattern).map_err(|e| {
+                AppError::ConfigError(format!("Invalid issue_ref_pattern '{}': {}", pattern, e))
+            })?)
+        } else {
+            None
+        };
+
+        let mut git = GitService::new();
+        git.set_ignore_whitespace(
+            config.ignore_whitespace && !cli.include_whitespace && !cli.no_filter,
+        );
+        git.set_no_filter(cli.no_filter);
+        git.set_word_diff(is_word_diff_mode(config.diff_mode.as_deref())?);
+        git.set_diff_algorithm(resolve_diff_algorithm(
+            cli.diff_algorithm.as_deref(),
+            config.diff_algorithm.as_deref(),
+        )?);
+        git.set_max_file_diff_lines(config.max_file_diff_lines);
+        git.set_strict_secret_scan(config.strict_secret_scan.unwrap_or(false));
+        git.set_amend_keep_date(cli.keep_date || config.amend_keep_date.unwrap_or(false));
+
+        // --use-branch-context: ブランチ名を補足情報として渡す（detached HEAD では省略）
+        if cli.use_branch_context || config.use_branch_context.unwrap_or(false) {
+            if let Some(branch) = git.get_current_branch() {
+                ai.set_branch_context(branch);
+            }
+        }
+
+        // commit.template / .gitmessage があればその構造に従わせる
+        if let Some(template) = git.get_commit_template() {
+            ai.set_commit_template(template);
+        }
+
+        // 書式参照に使う直近コミットの件数（--ref-commits > 設定ファイル > 5）
+        let style_reference_commits =
+            resolve_style_reference_commits(cli.ref_commits, config.style_reference_commits);
+
+        // 言語が "auto" の場合は直近のコミットから推定
+        if ai.is_auto_language() {
+            let recent_commits = git
+                .get_recent_commits(style_reference_commits)
+                .unwrap_or_default();
+            let detected = AiService::detect_language(&recent_commits);
+            if cli.debug {
+                println!(
+                    "  Language: {} (auto-detected from {} recent commit(s))",
+                    detected.cyan(),
+                    recent_commits.len()
+                );
+            }
+            ai.set_language(detected);
+        }
+
         Ok(Self {
-            git: GitService::new(),
+            git,
             ai,
             prefix_scripts: config.prefix_scripts.clone(),
             prefix_rules: config.prefix_rules.clone(),
-            prefix_type: config.prefix_type.clone(),
+            prefix_type,
             auto_push: config.auto_push,
+            debug: cli.debug,
+            max_subject_length: cli
+                .max_subject
+                .or(validate_max_subject_length(config.max_subject_length)?),
+            subject_case,
+            emoji_shortcode,
+            body_wrap_width: cli.wrap.or(config.body_wrap_width),
+            chunk_large_diffs: config.chunk_large_diffs.unwrap_or(false),
+            trailers,
+            footer_template: config.footer_template.clone(),
+            issue_ref_pattern,
+            issue_refs_from_diff: config.issue_refs_from_diff.unwrap_or(false),
+            issue_ref_keyword: config
+                .issue_ref_keyword
+                .clone()
+                .unwrap_or_else(|| DEFAULT_ISSUE_REF_KEYWORD.to_string()),
+            force_conventional: cli.force_conventional,
+            recent_commit_bodies: cli.recent_bodies || config.recent_commit_bodies.unwrap_or(false),
+            scope_state_to_repo: config.scope_state_to_repo.unwrap_or(false),
+            style_reference_commits,
+            no_recent_commits: cli.no_recent_commits || config.no_recent_commits.unwrap_or(false),
+            filter_style_reference: config.filter_style_reference.unwrap_or(true),
+            list_changed_files: config.list_changed_files.unwrap_or(true),
+            fail_on_empty: resolve_fail_on_empty(
+                cli.fail_on_empty,
+                config.empty_changes_behavior.as_deref(),
+            )?,
+            author,
         })
     }
 
-    /// デバッグモード: 設定ファイル情報を表示
-    fn print_config_debug(config: &Config) -> Result<(), AppError> {
-        println!();
-        println!("{}", "=== DEBUG: Config Settings ===".yellow().bold());
-        println!("{}", "─".repeat(50).dimmed());
+    /// プロバイダーを試行順に一覧表示（クールダウンによる降格を反映）
+    fn run_list_providers(&self) {
+        println!("{}", "Providers (in order of use):".cyan());
+        for (i, entry) in self.ai.provider_list().iter().enumerate() {
+            let line = format_provider_entry(i + 1, entry);
+            if entry.installed && entry.demoted_secs.is_none() {
+                println!("  {}", line);
+            } else {
+                println!("  {}", line.dimmed());
+            }
+        }
+    }
 
+    /// デバッグモード: 読み込んだ設定ファイルのパスを表示
+    fn print_config_paths() {
         // グローバル設定ファイルパス
         if let Ok(global_path) = Config::global_config_path() {
             if global_path.exists() {
@@ -98,16 +1162,78 @@ impl App {
         } else {
             println!("  Project config: {}", "(not found)".dimmed());
         }
+    }
+
+    /// デバッグモード: 設定ファイル情報を表示
+    fn print_config_debug(config: &Config, no_config: bool) -> Result<(), AppError> {
+        println!();
+        println!("{}", "=== DEBUG: Config Settings ===".yellow().bold());
+        println!("{}", "─".repeat(50).dimmed());
+
+        if no_config {
+            println!("  Config files: {}", "ignored (--no-config)".dimmed());
+        } else {
+            Self::print_config_paths();
+        }
 
         println!("{}", "─".repeat(50).dimmed());
         println!("{}", "Effective settings:".yellow());
         println!("  providers: {:?}", config.providers);
+        for provider in &config.providers_detailed {
+            println!(
+                "  providers_detailed: {} (priority {})",
+                provider.name, provider.priority
+            );
+        }
         println!("  language: {}", config.language);
-        println!("  models.gemini: {}", config.models.gemini);
-        println!("  models.codex: {}", config.models.codex);
-        println!("  models.claude: {}", config.models.claude);
+        println!("  models.gemini: {}", config.models.gemini());
+        println!("  models.codex: {}", config.models.codex());
+        println!("  models.claude: {}", config.models.claude());
+        println!("  providers_args: {:?}", config.providers_args);
+        println!("  provider_paths: {:?}", config.provider_paths);
         println!("  prefix_type: {:?}", config.prefix_type);
         println!("  auto_push: {:?}", config.auto_push);
+        println!("  chunk_large_diffs: {:?}", config.chunk_large_diffs);
+        println!("  subject_only: {:?}", config.subject_only);
+        println!("  no_recent_commits: {:?}", config.no_recent_commits);
+        println!(
+            "  filter_style_reference: {:?}",
+            config.filter_style_reference
+        );
+        println!("  list_changed_files: {:?}", config.list_changed_files);
+        println!("  use_branch_context: {:?}", config.use_branch_context);
+        println!(
+            "  empty_changes_behavior: {:?}",
+            config.empty_changes_behavior
+        );
+        println!("  suggest_scope: {:?}", config.suggest_scope);
+        println!("  ignore_whitespace: {}", config.ignore_whitespace);
+        println!("  diff_algorithm: {:?}", config.diff_algorithm);
+        println!("  diff_mode: {:?}", config.diff_mode);
+        println!("  blocked_terms: {} term(s)", config.blocked_terms.len());
+        println!("  allowed_types: {:?}", config.allowed_types);
+        println!("  strict_allowed_types: {:?}", config.strict_allowed_types);
+        println!("  max_subject_length: {:?}", config.max_subject_length);
+        println!("  prompt_suffix: {:?}", config.prompt_suffix);
+        println!("  body_wrap_width: {:?}", config.body_wrap_width);
+        println!("  subject_case: {:?}", config.subject_case);
+        println!("  emoji_style: {:?}", config.emoji_style);
+        println!("  max_file_diff_lines: {:?}", config.max_file_diff_lines);
+        println!("  scope_state_to_repo: {:?}", config.scope_state_to_repo);
+        println!("  strict_secret_scan: {:?}", config.strict_secret_scan);
+        println!("  amend_keep_date: {:?}", config.amend_keep_date);
+        println!("  recent_commit_bodies: {:?}", config.recent_commit_bodies);
+        println!(
+            "  style_reference_commits: {:?}",
+            config.style_reference_commits
+        );
+        println!("  footer_template: {:?}", config.footer_template);
+        println!("  issue_refs: {:?}", config.issue_refs);
+        println!("  issue_refs_from_diff: {:?}", config.issue_refs_from_diff);
+        println!("  issue_ref_pattern: {:?}", config.issue_ref_pattern);
+        println!("  issue_ref_keyword: {:?}", config.issue_ref_keyword);
+        println!("  provider_passes: {:?}", config.provider_passes);
+        println!("  skip_demoted: {:?}", config.skip_demoted);
         println!("  prefix_scripts: {} rule(s)", config.prefix_scripts.len());
         println!("  prefix_rules: {} rule(s)", config.prefix_rules.len());
         println!(
@@ -121,6 +1247,16 @@ impl App {
         Ok(())
     }
 
+    /// --verbose 時にフェーズの経過時間を標準エラー出力に表示
+    fn report_phase(cli: &Cli, phase: &str, start: Instant) {
+        if cli.verbose {
+            eprintln!(
+                "{}",
+                format_elapsed("phase", phase, start.elapsed()).dimmed()
+            );
+        }
+    }
+
     /// プレフィックスモードを判定
     ///
     /// 優先順位:
@@ -157,12 +1293,25 @@ impl App {
                         );
                     }
                     if let Some(branch_name) = &branch {
-                        if let Some(result) = self.git.run_prefix_script(
+                        if let Some(output) = self.git.run_prefix_script_with_output(
                             &script_config.script,
                             &remote_url,
                             branch_name,
                         ) {
-                            return PrefixMode::Script(result);
+                            if self.debug {
+                                let exit_code = output
+                                    .exit_code
+                                    .map_or("none".to_string(), |c| c.to_string());
+                                eprintln!(
+                                    "{}",
+                                    format!(
+                                        "[debug] prefix script {} exit={} stderr={:?}",
+                                        script_config.script, exit_code, output.stderr
+                                    )
+                                    .dimmed()
+                                );
+                            }
+                            return PrefixMode::Script(output.result);
                         }
                     }
                 }
@@ -215,19 +1364,186 @@ impl App {
         PrefixMode::Auto
     }
 
-    /// コミットメッセージにプレフィックスを適用
-    fn apply_prefix(&self, message: &str, prefix: &str) -> String {
-        // Conventional Commits形式（type: message）の場合、typeを削除してprefixに置き換え
-        if let Some(colon_pos) = message.find(':') {
-            let body = message[colon_pos + 1..].trim_start();
-            format!("{}{}", prefix, body)
-        } else {
-            // コロンがない場合はそのまま結合
-            format!("{}{}", prefix, message)
+    /// --explain の判定経緯を組み立てる
+    ///
+    /// プレフィックスの判定はメッセージ生成時と同じ手順を表示なしでやり直す
+    fn explain_trace(&self, provider: Option<AiProvider>, diff_truncated: bool) -> ExplainTrace {
+        let remote_url = self.git.get_remote_url();
+        let mode = self.get_prefix_mode_silent();
+        let matches = |pattern: &str| {
+            remote_url
+                .as_deref()
+                .zip(Regex::new(pattern).ok())
+                .is_some_and(|(url, re)| re.is_match(url))
+        };
+        let pattern = match mode {
+            PrefixMode::Script(_) => self
+                .prefix_scripts
+                .iter()
+                .map(|s| s.url_pattern.as_str())
+                .find(|p| matches(p)),
+            PrefixMode::Rule(_) => self
+                .prefix_rules
+                .iter()
+                .map(|r| r.url_pattern.as_str())
+                .find(|p| matches(p)),
+            _ => None,
+        };
+
+        ExplainTrace {
+            prefix: describe_prefix_mode(&mode, pattern),
+            remote_url,
+            branch: self.git.get_current_branch(),
+            providers: self.ai.provider_list().iter().map(|e| e.name).collect(),
+            provider_used: provider.map(|p| p.name()),
+            diff_truncated,
         }
     }
 
-    /// コミットメッセージから型プレフィックスを削除（本文のみ取得）
+    /// 文字数制限を超える差分について、切り捨てられる大きなファイルを要約して補う
+    ///
+    /// chunk_large_diffs が無効、または制限内の場合は通常の切り詰め済み差分を返す
+    fn prepare_staged_diff(&self, diff: String, pathspec: &[String]) -> Result<String, AppError> {
+        if !self.chunk_large_diffs {
+            return Ok(diff);
+        }
+
+        let full_diff = self.git.get_staged_diff_untruncated(pathspec)?;
+        if full_diff.chars().count() <= MAX_DIFF_CHARS {
+            return Ok(diff);
+        }
+
+        let blocks = GitService::split_diff_blocks(&full_diff);
+        let targets = select_blocks_to_summarize(&blocks, MAX_DIFF_CHARS, MAX_SUMMARIZED_FILES);
+        if targets.is_empty() {
+            return Ok(diff);
+        }
+
+        println!(
+            "{}",
+            format!("Summarizing {} large file(s)...", targets.len()).cyan()
+        );
+
+        let mut summaries = Vec::new();
+        for index in targets {
+            let block = &blocks[index];
+            let file_diff = GitService::truncate_diff(&block.content);
+            match self.ai.summarize_file_diff(&block.path, &file_diff) {
+                Ok(summary) if !summary.is_empty() => {
+                    summaries.push((block.path.clone(), summary));
+                }
+                // 要約に失敗したファイルはスキップ（通常の切り詰めにフォールバック）
+                _ => {}
+            }
+        }
+
+        if summaries.is_empty() {
+            return Ok(diff);
+        }
+
+        Ok(build_chunked_diff(&summaries, &diff))
+    }
+
+    /// ブランチ名（と差分）の課題番号参照から "Closes #N" フッターを作成（issue_refs 無効時は空）
+    fn issue_footers(&self, diff: &str) -> Vec<String> {
+        match self.issue_ref_pattern {
+            Some(ref pattern) => {
+                let branch = self.git.get_current_branch();
+                let refs =
+                    extract_issue_refs(diff, branch.as_deref(), pattern, self.issue_refs_from_diff);
+                issue_ref_footers(&refs, &self.issue_ref_keyword)
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// --append / --coauthor のトレーラー、footer_template のフッター、課題番号フッターを追加
+    /// （コミットを作成するモード用）
+    fn append_trailers(&self, message: &str, issue_footers: &[String]) -> String {
+        let mut trailers = self.trailers.clone();
+        if let Some(ref template) = self.footer_template {
+            let footer = render_footer(
+                template,
+                self.git.get_current_branch().as_deref(),
+                self.git.get_remote_url().as_deref(),
+                &today_utc(),
+            );
+            trailers.extend(footer.lines().map(|line| line.trim_end().to_string()));
+        }
+        trailers.extend(issue_footers.iter().cloned());
+        append_trailers(message, &trailers)
+    }
+
+    /// 書式参照用の直近コミットを style_reference_commits 件取得（recent_commit_bodies 有効時は本文も含める）
+    ///
+    /// skip には書き換え対象など、参照から除く新しい側のコミット数を指定する
+    fn reference_commits(&self, skip: usize) -> Result<Vec<String>, AppError> {
+        fetch_style_reference(self.no_recent_commits, || {
+            let count = self.style_reference_commits + skip;
+            let commits = if self.recent_commit_bodies {
+                self.git.get_recent_commits_full(count)?
+            } else {
+                self.git.get_recent_commits(count)?
+            };
+            let commits: Vec<String> = commits.into_iter().skip(skip).collect();
+            Ok(if self.filter_style_reference {
+                filter_style_reference(&commits)
+            } else {
+                commits
+            })
+        })
+    }
+
+    /// 生成・加工済みのメッセージに最終的な整形を適用
+    ///
+    /// --force-conventional 指定時は件名が Conventional Commits 形式でなければエラー
+    fn finalize_message(&self, message: String) -> Result<String, AppError> {
+        let message = if self.emoji_shortcode {
+            emoji_prefix_to_shortcode(&message)
+        } else {
+            message
+        };
+        let message = match self.subject_case {
+            Some(mode) => match message.split_once('\n') {
+                Some((subject, rest)) => {
+                    format!("{}\n{}", normalize_subject_case(subject, mode), rest)
+                }
+                None => normalize_subject_case(&message, mode),
+            },
+            None => message,
+        };
+        let message = match self.body_wrap_width {
+            Some(width) => wrap_body(&message, width),
+            None => message,
+        };
+        let message = match self.max_subject_length {
+            Some(max) => truncate_message_subject(&message, max),
+            None => message,
+        };
+
+        if self.force_conventional {
+            let subject = message.lines().next().unwrap_or_default();
+            if !is_conventional(subject) {
+                return Err(AppError::InvalidCommitFormat(subject.to_string()));
+            }
+        }
+
+        Ok(message)
+    }
+
+    /// コミットメッセージにプレフィックスを適用
+    fn apply_prefix(&self, message: &str, prefix: &str) -> String {
+        // Conventional Commits形式（type: message）の場合、typeを削除してprefixに置き換え
+        if let Some(colon_pos) = message.find(':') {
+            let body = message[colon_pos + 1..].trim_start();
+            format!("{}{}", prefix, body)
+        } else {
+            // コロンがない場合はそのまま結合
+            format!("{}{}", prefix, message)
+        }
+    }
+
+    /// コミットメッセージから型プレフィックスを削除（本文のみ取得）
     fn strip_type_prefix(&self, message: &str) -> String {
         if let Some(colon_pos) = message.find(':') {
             message[colon_pos + 1..].trim_start().to_string()
@@ -267,21 +1583,30 @@ impl App {
         (prefix_type, commits)
     }
 
-    /// デバッグモード時にプロンプトを表示
-    fn print_debug_prompt(
+    /// PrefixModeに基づいてAIに送るプロンプトを構築（--debug と --print-prompt で共用）
+    fn prompt_for_prefix_mode(
         &self,
         diff: &str,
         recent_commits: &[String],
-        prefix_type: Option<&str>,
+        prefix_mode: &PrefixMode,
+        is_squash: bool,
         with_body: bool,
-    ) {
-        let prompt = AiService::build_prompt(
-            diff,
-            recent_commits,
-            self.ai.language(),
-            prefix_type,
-            with_body,
-        );
+    ) -> String {
+        let (prefix_type, commits) =
+            Self::get_debug_params_for_prefix_mode(prefix_mode, recent_commits, is_squash);
+        self.ai
+            .build_prompt_for(diff, commits, prefix_type, with_body)
+    }
+
+    /// 生成に使用したプロバイダーを成功メッセージの後に表示
+    fn print_committed_with(provider: Option<AiProvider>) {
+        if let Some(provider) = provider {
+            println!("{}", committed_with(provider).dimmed());
+        }
+    }
+
+    /// デバッグモード時にプロンプトを表示
+    fn print_debug_prompt(prompt: &str) {
         println!();
         println!("{}", "=== DEBUG: AI Prompt ===".yellow().bold());
         println!("{}", "─".repeat(50).dimmed());
@@ -291,6 +1616,13 @@ impl App {
         println!();
     }
 
+    /// --print-diff 指定時、AIに送る差分を標準エラー出力に表示（標準出力はメッセージ表示に使う）
+    fn print_diff_if_requested(cli: &Cli, diff: &str) {
+        if cli.print_diff {
+            eprintln!("{}", format_print_diff(diff));
+        }
+    }
+
     /// デバッグモード時にPrefixModeに基づいてプロンプトを表示
     fn debug_print_for_prefix_mode(
         &self,
@@ -300,18 +1632,126 @@ impl App {
         is_squash: bool,
         with_body: bool,
     ) {
-        let (prefix_type, commits) =
-            Self::get_debug_params_for_prefix_mode(prefix_mode, recent_commits, is_squash);
-        self.print_debug_prompt(diff, commits, prefix_type, with_body);
+        let prompt =
+            self.prompt_for_prefix_mode(diff, recent_commits, prefix_mode, is_squash, with_body);
+        Self::print_debug_prompt(&prompt);
+    }
+
+    /// 送信されるプロンプトのみを標準出力に表示して終了（--print-prompt）
+    ///
+    /// AIの呼び出し、ステージング、コミットは行わない
+    fn run_print_prompt(&mut self, cli: &Cli) -> Result<(), AppError> {
+        let mut is_squash = false;
+        let (diff, recent_commits) = if let Some(ref hashes) = cli.generate_for {
+            (
+                self.get_generate_for_diff(hashes)?,
+                self.reference_commits(0)?,
+            )
+        } else if let Some(ref hash) = cli.reword {
+            let n = self.git.get_commit_position_by_hash(hash)?;
+            let recent = self.reference_commits(n)?;
+            (self.git.get_commit_diff_by_hash(hash)?, recent)
+        } else if cli.amend || cli.amend_staged || cli.amend_all {
+            let diff = if cli.amend_all || (cli.amend_staged && cli.stage_all) {
+                self.git.get_amend_all_diff()?
+            } else if cli.amend_staged {
+                self.git.get_amend_staged_diff()?
+            } else {
+                self.git.get_last_commit_diff()?
+            };
+            let recent = self.reference_commits(1)?;
+            (diff, recent)
+        } else if let Some(n) = cli.squash_last {
+            is_squash = true;
+            let base = self.squash_last_base(n)?;
+            (self.git.get_diff_from_base(&base)?, Vec::new())
+        } else if cli.squash.is_some() || cli.squash_upstream {
+            is_squash = true;
+            let merge_base = self.git.get_merge_base(&self.squash_base(cli)?, "HEAD")?;
+            (self.git.get_diff_from_base(&merge_base)?, Vec::new())
+        } else {
+            let diff = self.git.get_staged_diff(&cli.pathspec)?;
+            let diff = if !diff.trim().is_empty() {
+                if cli.stat_only {
+                    self.git.get_staged_stat_summary(&cli.pathspec)?
+                } else {
+                    diff
+                }
+            } else if let Some(summary) = self.file_list_fallback(&cli.pathspec)? {
+                summary
+            } else if cli.allow_empty {
+                diff
+            } else {
+                return Err(AppError::NoStagedChanges);
+            };
+            (diff, self.reference_commits(0)?)
+        };
+
+        if diff.trim().is_empty() && !cli.allow_empty {
+            return Err(AppError::NoChanges);
+        }
+
+        let prefix_mode = self.get_prefix_mode_silent();
+        let prompt = self.prompt_for_prefix_mode(
+            &diff,
+            &recent_commits,
+            &prefix_mode,
+            is_squash,
+            cli.with_body,
+        );
+        println!("{}", prompt);
+        Ok(())
     }
 
     /// メインワークフローを実行
-    pub fn run(&self, cli: &Cli) -> Result<(), AppError> {
-        // Gitリポジトリかどうかを確認
+    pub fn run(&mut self, cli: &Cli) -> Result<(), AppError> {
+        // --list-providersはリポジトリ外でも使えるようgitの確認より前に処理
+        if cli.list_providers {
+            self.run_list_providers();
+            return Ok(());
+        }
+
+        // gitが実行できるか、Gitリポジトリかどうかを確認
+        GitService::verify_git_installed()?;
         self.git.verify_repository()?;
 
+        // --undoはAIを使用しないため先に処理
+        if cli.undo {
+            return self.run_undo(cli);
+        }
+
+        // --fixupモードはAIを使用しないため先に処理
+        if cli.fixup.is_some() {
+            return self.run_fixup(cli);
+        }
+
+        // --print-promptはプロンプトを表示するだけなのでAIの確認は不要
+        if cli.print_prompt {
+            return self.run_print_prompt(cli);
+        }
+
         // AI CLIがインストールされているか確認
-        self.ai.verify_installation()?;
+        // 通常のコミットではAIが使えなければオフライン生成にフォールバック
+        let offline = if cli.offline {
+            true
+        } else if let Err(e) = self.ai.verify_installation() {
+            if !Self::supports_offline(cli) {
+                return Err(e);
+            }
+            eprintln!(
+                "{} {}",
+                "⚠".yellow(),
+                "No AI provider available. Falling back to offline message generation.".yellow()
+            );
+            true
+        } else {
+            false
+        };
+
+        // --patchモードは別処理（出力のみ）
+        if let Some(ref path) = cli.patch {
+            return self.run_patch(cli, path);
+        }
 
         // --generate-forモードは別処理（排他チェック付き）
         if cli.generate_for.is_some() {
@@ -333,110 +1773,116 @@ impl App {
             return self.run_reword(cli);
         }
 
-        // --amendモードは別処理
-        if cli.amend {
+        // --amend-atモードは別処理
+        if cli.amend_at.is_some() {
+            return self.run_amend_at(cli);
+        }
+
+        // --regen-body / --amend --keep-subjectモードは別処理
+        if cli.regen_body || cli.keep_subject {
+            return self.run_regen_body(cli);
+        }
+
+        // --amend / --amend-stagedモードは別処理
+        if cli.amend || cli.amend_staged || cli.amend_all {
             return self.run_amend(cli);
         }
 
         // --squashモードは別処理
-        if cli.squash.is_some() {
+        if cli.squash.is_some() || cli.squash_upstream || cli.squash_last.is_some() {
             return self.run_squash(cli);
         }
 
+        // --fill: AIを呼ぶ前にひな形を読み込んでプレースホルダーを確認
+        let fill_template = cli
+            .fill
+            .as_deref()
+            .map(Self::read_fill_template)
+            .transpose()?;
+
         // --allフラグがあれば全変更をステージング
         if cli.stage_all {
             println!("{}", "Staging all changes...".cyan());
+            let start = Instant::now();
             self.git.stage_all()?;
+            Self::report_phase(cli, "staging", start);
+        }
+
+        // --interactive: git add -p でハンクを選んでからステージ済みの差分を使う
+        if cli.interactive {
+            self.git.add_patch(&cli.pathspec)?;
         }
 
         // ステージ済みのdiffを取得
-        let staged_diff = self.git.get_staged_diff()?;
+        let start = Instant::now();
+        let staged_diff = self.git.get_staged_diff(&cli.pathspec)?;
+        Self::report_phase(cli, "diff", start);
+        let mut files_only = false;
         let diff = if !staged_diff.trim().is_empty() {
             staged_diff
-        } else if cli.stage_all {
-            // --allフラグ指定時で変更がない場合は正常終了
+        } else if let Some(summary) = self.file_list_fallback(&cli.pathspec)? {
+            files_only = true;
+            summary
+        } else if cli.allow_empty {
+            // --allow-empty: 変更なしでも直近のコミットから意図を説明するメッセージを生成
+            println!("{}", "No staged changes, creating an empty commit.".cyan());
+            staged_diff
+        } else {
+            // 変更がない場合（既定では --all のときのみ正常終了）
+            empty_changes_result(cli.stage_all, self.fail_on_empty)?;
             println!("{}", "変更がありません。".cyan());
             return Ok(());
-        } else {
-            // デフォルト: ステージ済みのみ
-            return Err(AppError::NoStagedChanges);
         };
 
-        // プレフィックスモードを判定
-        let prefix_mode = self.get_prefix_mode();
-
-        // フォーマット検出用に直近のコミットを取得（Autoモードの場合のみ表示）
-        let recent_commits = self.git.get_recent_commits(5)?;
-
-        // Autoモードの場合のみ参照用に直近のコミットを表示
-        if matches!(prefix_mode, PrefixMode::Auto) {
-            if recent_commits.is_empty() {
-                println!(
-                    "{} {}",
-                    "No recent commits found.".cyan(),
-                    "Using Conventional Commits format.".yellow()
-                );
-            } else {
-                println!("{}", "Recent commits (for format reference):".cyan());
-                for commit in &recent_commits {
-                    println!("  {}", commit.dimmed());
-                }
-            }
+        // 差分が切り詰められてもファイル名を捏造しないよう、ファイル一覧を明示する
+        // （--stat-only の概要にはファイル一覧が既に含まれる）
+        if self.list_changed_files && !cli.stat_only && !files_only && !diff.trim().is_empty() {
+            let name_status = self.git.get_staged_name_status(&cli.pathspec)?;
+            self.ai.set_changed_files(name_status);
         }
 
-        // コミットメッセージを生成
-        println!("{}", "Generating commit message...".cyan());
+        // 課題番号フッターは差分を生成に渡す前に抽出しておく
+        let issue_footers = self.issue_footers(&diff);
+        let diff_truncated = GitService::is_truncated(&diff);
+
+        let (message, provider) = if offline {
+            // オフラインモード: 変更ファイルの概要から決定的に生成
+            println!("{}", "Generating commit message offline...".cyan());
+            let message = heuristic_message(&self.git.get_staged_name_status(&cli.pathspec)?);
+            (message, None)
+        } else if cli.stat_only {
+            // --stat-only: コードは送らずファイル一覧と変更量のみから生成
+            let stat = self.git.get_staged_stat_summary(&cli.pathspec)?;
+            let (message, provider) = self.generate_staged_message(cli, stat)?;
+            (message, Some(provider))
+        } else {
+            let (message, provider) = self.generate_staged_message(cli, diff)?;
+            (message, Some(provider))
+        };
 
-        // デバッグモード: プロンプトを表示
-        if cli.debug {
-            self.debug_print_for_prefix_mode(
-                &diff,
-                &recent_commits,
-                &prefix_mode,
-                false,
-                cli.with_body,
+        // --explain: プレフィックスとプロバイダーの判定経緯を表示
+        if cli.explain {
+            eprintln!(
+                "{}",
+                self.explain_trace(provider, diff_truncated)
+                    .render()
+                    .dimmed()
             );
         }
 
-        let mut message = match &prefix_mode {
-            PrefixMode::Script(_) => {
-                // スクリプトモード: プレフィックスなしで生成（後でスクリプトのプレフィックスを適用）
-                self.ai
-                    .generate_commit_message(&diff, &[], Some("plain"), cli.with_body)?
-            }
-            PrefixMode::Rule(prefix_type) | PrefixMode::Config(prefix_type) => {
-                // ルール/設定モード: 指定されたprefix_typeで生成
-                self.ai.generate_commit_message(
-                    &diff,
-                    &recent_commits,
-                    Some(prefix_type),
-                    cli.with_body,
-                )?
-            }
-            PrefixMode::Auto => {
-                // 自動判定モード: 過去コミットから推論
-                self.ai
-                    .generate_commit_message(&diff, &recent_commits, None, cli.with_body)?
+        // 件名の長さ制限などの最終整形
+        let message = self.finalize_message(message)?;
+
+        // --fill: 生成した要約（件名）だけをひな形に埋め込む
+        let message = match fill_template {
+            Some(ref template) => {
+                fill_skeleton(template, &first_line_only(&message)).unwrap_or(message)
             }
+            None => message,
         };
 
-        // スクリプトモードの場合はメッセージを加工
-        if let PrefixMode::Script(result) = prefix_mode {
-            match result {
-                ScriptResult::Prefix(prefix) => {
-                    message = self.apply_prefix(&message, &prefix);
-                    println!("{}", format!("Applied prefix: {}", prefix.trim()).cyan());
-                }
-                ScriptResult::Empty => {
-                    message = self.strip_type_prefix(&message);
-                    println!("{}", "No prefix applied (script returned empty).".cyan());
-                }
-                ScriptResult::Failed => {
-                    // AI生成のメッセージをそのまま使用
-                    println!("{}", "Using AI-generated format.".cyan());
-                }
-            }
-        }
+        // --append / footer_template / 課題番号のトレーラーを追加
+        let message = self.append_trailers(&message, &issue_footers);
 
         // 生成されたメッセージを表示
         println!();
@@ -452,14 +1898,41 @@ impl App {
             return Ok(());
         }
 
+        // --message-file: コミットせずにメッセージファイルへ書き込む（prepare-commit-msg フック用）
+        if let Some(ref path) = cli.message_file {
+            return Self::write_message_file(path, &message);
+        }
+
+        // コミット対象のファイル数を表示（意図しないステージングの確認用）
+        if let Ok(summary) = self.git.get_staged_summary(&cli.pathspec) {
+            println!(
+                "{} {} file(s) ({})",
+                "Staged:".cyan(),
+                summary.total(),
+                summary
+            );
+        }
+
         // 確認してコミット
         if cli.auto_confirm || self.confirm_commit()? {
-            self.git.commit(&message)?;
+            let start = Instant::now();
+            self.git.commit(
+                &message,
+                &CommitOptions {
+                    pathspec: &cli.pathspec,
+                    allow_empty: cli.allow_empty,
+                    author: self.author.as_deref(),
+                },
+            )?;
+            Self::report_phase(cli, "commit", start);
             println!("{}", "✓ Commit created successfully!".green().bold());
+            Self::print_committed_with(provider);
 
             // auto-push が有効な場合は push も実行
             if self.git.is_auto_push_enabled(self.auto_push) {
+                let start = Instant::now();
                 self.git.push()?;
+                Self::report_phase(cli, "push", start);
                 println!("{}", "✓ Pushed to remote successfully!".green().bold());
             }
         } else {
@@ -470,25 +1943,42 @@ impl App {
         Ok(())
     }
 
-    /// amendワークフローを実行
-    fn run_amend(&self, cli: &Cli) -> Result<(), AppError> {
-        println!(
-            "{}",
-            "Amend mode: regenerating message for last commit...".cyan()
-        );
+    /// オフラインモードに対応したモードかどうか（通常のコミットのみ）
+    fn supports_offline(cli: &Cli) -> bool {
+        cli.generate_for.is_none()
+            && cli.reword.is_none()
+            && !cli.amend
+            && !cli.amend_staged
+            && !cli.amend_all
+            && !cli.regen_body
+            && cli.squash.is_none()
+            && !cli.squash_upstream
+            && cli.squash_last.is_none()
+            && cli.amend_at.is_none()
+            && cli.patch.is_none()
+    }
 
-        // 直前のコミットのdiffを取得
-        let diff = self.git.get_last_commit_diff()?;
-        if diff.trim().is_empty() {
-            return Err(AppError::NoChanges);
-        }
+    /// ステージ済みの差分からAIでコミットメッセージを生成（プレフィックス処理を含む）
+    fn generate_staged_message(
+        &self,
+        cli: &Cli,
+        diff: String,
+    ) -> Result<(String, AiProvider), AppError> {
+        // 大きな差分はファイル単位の要約で補う（chunk_large_diffs 有効時、--stat-only では行わない）
+        let diff = if cli.stat_only {
+            diff
+        } else {
+            self.prepare_staged_diff(diff, &cli.pathspec)?
+        };
+
+        // --print-diff: プロンプトに埋め込む差分をそのまま表示
+        Self::print_diff_if_requested(cli, &diff);
 
         // プレフィックスモードを判定
         let prefix_mode = self.get_prefix_mode();
 
-        // フォーマット検出用に直近のコミットを取得（amendするコミットはスキップ）
-        let recent_commits = self.git.get_recent_commits(6)?;
-        let recent_commits: Vec<String> = recent_commits.into_iter().skip(1).collect();
+        // フォーマット検出用に直近のコミットを取得（Autoモードの場合のみ表示）
+        let recent_commits = self.reference_commits(0)?;
 
         // Autoモードの場合のみ参照用に直近のコミットを表示
         if matches!(prefix_mode, PrefixMode::Auto) {
@@ -501,7 +1991,7 @@ impl App {
             } else {
                 println!("{}", "Recent commits (for format reference):".cyan());
                 for commit in &recent_commits {
-                    println!("  {}", commit.dimmed());
+                    println!("  {}", commit.lines().next().unwrap_or_default().dimmed());
                 }
             }
         }
@@ -520,7 +2010,7 @@ impl App {
             );
         }
 
-        let mut message = match &prefix_mode {
+        let (mut message, provider) = match &prefix_mode {
             PrefixMode::Script(_) => {
                 // スクリプトモード: プレフィックスなしで生成（後でスクリプトのプレフィックスを適用）
                 self.ai
@@ -536,6 +2026,7 @@ impl App {
                 )?
             }
             PrefixMode::Auto => {
+                // 自動判定モード: 過去コミットから推論
                 self.ai
                     .generate_commit_message(&diff, &recent_commits, None, cli.with_body)?
             }
@@ -559,37 +2050,366 @@ impl App {
             }
         }
 
-        // 生成されたメッセージを表示
+        Ok((message, provider))
+    }
+
+    /// --fill のひな形を読み込む（プレースホルダーがなければエラー）
+    fn read_fill_template(path: &Path) -> Result<String, AppError> {
+        let template = std::fs::read_to_string(path)
+            .map_err(|e| AppError::InvalidFillTemplate(format!("{}: {}", path.display(), e)))?;
+        if !template.contains(FILL_PLACEHOLDER) {
+            return Err(AppError::InvalidFillTemplate(format!(
+                "{}: no {} placeholder",
+                path.display(),
+                FILL_PLACEHOLDER
+            )));
+        }
+        Ok(template)
+    }
+
+    /// コミットメッセージファイルに生成したメッセージを書き込む（シザーズ行以降は保持）
+    fn write_message_file(path: &Path, message: &str) -> Result<(), AppError> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => {
+                return Err(AppError::MessageFileError(format!(
+                    "{}: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+        std::fs::write(path, insert_into_message_file(&content, message))
+            .map_err(|e| AppError::MessageFileError(format!("{}: {}", path.display(), e)))?;
+        println!(
+            "{}",
+            format!("✓ Message written to {}", path.display())
+                .green()
+                .bold()
+        );
+        Ok(())
+    }
+
+    /// fixupワークフローを実行（AIを使わず fixup! コミットを作成）
+    fn run_fixup(&self, cli: &Cli) -> Result<(), AppError> {
+        let hash = cli.fixup.as_ref().ok_or(AppError::InvalidRewordTarget)?;
+
+        // 対象コミットの件名を取得（ハッシュの検証も兼ねる）
+        let subject = self.git.get_commit_message_by_hash(hash)?;
+
+        if cli.stage_all {
+            println!("{}", "Staging all changes...".cyan());
+            self.git.stage_all()?;
+        }
+
+        if !self.git.has_staged_changes()? {
+            return Err(AppError::NoStagedChanges);
+        }
+
+        let message = fixup_message(&subject);
+
         println!();
-        println!("{}", "Generated commit message:".green().bold());
+        println!("{}", "Fixup commit message:".green().bold());
         println!("{}", "─".repeat(50).dimmed());
         println!("{}", message);
         println!("{}", "─".repeat(50).dimmed());
         println!();
 
-        // ドライランモードの処理
         if cli.dry_run {
-            println!("{}", "Dry run mode - commit was not amended.".yellow());
+            println!("{}", "Dry run mode - no commit was made.".yellow());
             return Ok(());
         }
 
-        // 確認してamend
-        if cli.auto_confirm || self.confirm_amend()? {
-            self.git.amend_commit(&message)?;
-            println!("{}", "✓ Commit amended successfully!".green().bold());
-        } else {
-            println!("{}", "Amend cancelled.".yellow());
-            return Err(AppError::UserCancelled);
-        }
+        self.git.commit(&message, &self.commit_options())?;
+        println!("{}", "✓ Fixup commit created successfully!".green().bold());
+        println!(
+            "{}",
+            "Run 'git rebase -i --autosquash' to fold it into the target commit.".dimmed()
+        );
 
         Ok(())
     }
 
-    /// squashワークフローを実行
-    fn run_squash(&self, cli: &Cli) -> Result<(), AppError> {
-        // ベースブランチを取得（必須）
-        let base_branch = cli.squash.as_ref().ok_or(AppError::NoBaseBranch)?;
-
+    /// 各コミットのdiffを取得して結合（--generate-for 用）
+    fn get_generate_for_diff(&self, hashes: &[String]) -> Result<String, AppError> {
+        let mut combined_diff = String::new();
+        for hash in hashes {
+            let diff = self.git.get_commit_diff_by_hash(hash)?;
+            if !diff.trim().is_empty() {
+                if !combined_diff.is_empty() {
+                    combined_diff.push('\n');
+                }
+                combined_diff.push_str(&diff);
+            }
+        }
+        Ok(combined_diff)
+    }
+
+    /// amendワークフローを実行
+    fn run_amend(&self, cli: &Cli) -> Result<(), AppError> {
+        self.guard_pushed_amend(cli)?;
+
+        let diff = if cli.amend_all {
+            println!(
+                "{}",
+                "Amend mode: folding all changes into last commit...".cyan()
+            );
+
+            if !self.git.has_uncommitted_changes()? {
+                return Err(AppError::NoChanges);
+            }
+
+            // ステージングは確認後に行う（キャンセルや生成失敗で全変更がステージされたまま残らないように）
+            self.git.get_amend_all_diff()?
+        } else if cli.amend_staged {
+            println!(
+                "{}",
+                "Amend mode: folding staged changes into last commit...".cyan()
+            );
+
+            // --allフラグがあれば全変更を対象にする（ステージングは確認後に行う）
+            if cli.stage_all {
+                if !self.git.has_uncommitted_changes()? {
+                    return Err(AppError::NothingToAmendStaged);
+                }
+                self.git.get_amend_all_diff()?
+            } else {
+                if !self.git.has_staged_changes()? {
+                    return Err(AppError::NothingToAmendStaged);
+                }
+
+                // 直前のコミットとステージ済みの変更を合わせたdiffを取得
+                self.git.get_amend_staged_diff()?
+            }
+        } else {
+            println!(
+                "{}",
+                "Amend mode: regenerating message for last commit...".cyan()
+            );
+
+            // 直前のコミットのdiffを取得
+            self.git.get_last_commit_diff()?
+        };
+        if diff.trim().is_empty() {
+            return Err(AppError::NoChanges);
+        }
+
+        // --print-diff: プロンプトに埋め込む差分をそのまま表示
+        Self::print_diff_if_requested(cli, &diff);
+
+        // プレフィックスモードを判定
+        let prefix_mode = self.get_prefix_mode();
+
+        // フォーマット検出用に直近のコミットを取得（amendするコミットはスキップ）
+        let recent_commits = self.reference_commits(1)?;
+
+        // Autoモードの場合のみ参照用に直近のコミットを表示
+        if matches!(prefix_mode, PrefixMode::Auto) {
+            if recent_commits.is_empty() {
+                println!(
+                    "{} {}",
+                    "No recent commits found.".cyan(),
+                    "Using Conventional Commits format.".yellow()
+                );
+            } else {
+                println!("{}", "Recent commits (for format reference):".cyan());
+                for commit in &recent_commits {
+                    println!("  {}", commit.lines().next().unwrap_or_default().dimmed());
+                }
+            }
+        }
+
+        // コミットメッセージを生成
+        println!("{}", "Generating commit message...".cyan());
+
+        // デバッグモード: プロンプトを表示
+        if cli.debug {
+            self.debug_print_for_prefix_mode(
+                &diff,
+                &recent_commits,
+                &prefix_mode,
+                false,
+                cli.with_body,
+            );
+        }
+
+        let (mut message, provider) = match &prefix_mode {
+            PrefixMode::Script(_) => {
+                // スクリプトモード: プレフィックスなしで生成（後でスクリプトのプレフィックスを適用）
+                self.ai
+                    .generate_commit_message(&diff, &[], Some("plain"), cli.with_body)?
+            }
+            PrefixMode::Rule(prefix_type) | PrefixMode::Config(prefix_type) => {
+                // ルール/設定モード: 指定されたprefix_typeで生成
+                self.ai.generate_commit_message(
+                    &diff,
+                    &recent_commits,
+                    Some(prefix_type),
+                    cli.with_body,
+                )?
+            }
+            PrefixMode::Auto => {
+                self.ai
+                    .generate_commit_message(&diff, &recent_commits, None, cli.with_body)?
+            }
+        };
+
+        // スクリプトモードの場合はメッセージを加工
+        if let PrefixMode::Script(result) = prefix_mode {
+            match result {
+                ScriptResult::Prefix(prefix) => {
+                    message = self.apply_prefix(&message, &prefix);
+                    println!("{}", format!("Applied prefix: {}", prefix.trim()).cyan());
+                }
+                ScriptResult::Empty => {
+                    message = self.strip_type_prefix(&message);
+                    println!("{}", "No prefix applied (script returned empty).".cyan());
+                }
+                ScriptResult::Failed => {
+                    // AI生成のメッセージをそのまま使用
+                    println!("{}", "Using AI-generated format.".cyan());
+                }
+            }
+        }
+
+        // 件名の長さ制限などの最終整形
+        let message = self.finalize_message(message)?;
+
+        // --append / footer_template / 課題番号のトレーラーを追加
+        let message = self.append_trailers(&message, &self.issue_footers(&diff));
+
+        // 生成されたメッセージを表示
+        println!();
+        println!("{}", "Generated commit message:".green().bold());
+        println!("{}", "─".repeat(50).dimmed());
+        println!("{}", message);
+        println!("{}", "─".repeat(50).dimmed());
+        println!();
+
+        // ドライランモードの処理
+        if cli.dry_run {
+            println!("{}", "Dry run mode - commit was not amended.".yellow());
+            return Ok(());
+        }
+
+        // 確認してamend
+        if cli.auto_confirm || self.confirm_amend()? {
+            if cli.amend_all || (cli.amend_staged && cli.stage_all) {
+                println!("{}", "Staging all changes...".cyan());
+                self.git.stage_all()?;
+            }
+            let head_before = self.git.get_head_hash()?;
+            self.git.amend_commit(&message, self.author.as_deref())?;
+            self.record_rewrite("amend", &head_before);
+            println!("{}", "✓ Commit amended successfully!".green().bold());
+            Self::print_committed_with(Some(provider));
+        } else {
+            println!("{}", "Amend cancelled.".yellow());
+            return Err(AppError::UserCancelled);
+        }
+
+        Ok(())
+    }
+
+    /// 直前のコミットの件名を維持して本文のみを再生成し、amend する
+    fn run_regen_body(&self, cli: &Cli) -> Result<(), AppError> {
+        println!(
+            "{}",
+            "Regenerating body for last commit (subject kept)...".cyan()
+        );
+
+        self.guard_pushed_amend(cli)?;
+
+        let diff = self.git.get_last_commit_diff()?;
+        if diff.trim().is_empty() {
+            return Err(AppError::NoChanges);
+        }
+
+        // --print-diff: プロンプトに埋め込む差分をそのまま表示
+        Self::print_diff_if_requested(cli, &diff);
+
+        let subject = self.git.get_commit_message_by_hash("HEAD")?;
+        println!("{}", "Subject:".cyan());
+        println!("  {}", subject.dimmed());
+
+        println!("{}", "Generating commit body...".cyan());
+        let (body, provider) = self.ai.generate_body(&diff, &subject)?;
+        let message = message_with_body(&subject, &body);
+
+        // 本文を body_wrap_width で折り返す（件名は既存のものを維持するため切り詰めない）
+        let message = match self.body_wrap_width {
+            Some(width) => wrap_body(&message, width),
+            None => message,
+        };
+
+        // --append / footer_template / 課題番号のトレーラーを追加
+        let message = self.append_trailers(&message, &self.issue_footers(&diff));
+
+        // 元のコミットのトレーラー（Co-authored-by / Signed-off-by など）を引き継ぐ
+        let preserved = extract_trailers(&self.git.get_full_commit_message_by_hash("HEAD")?);
+        let message = reattach_trailers(&message, &preserved);
+
+        // 生成されたメッセージを表示
+        println!();
+        println!("{}", "Generated commit message:".green().bold());
+        println!("{}", "─".repeat(50).dimmed());
+        println!("{}", message);
+        println!("{}", "─".repeat(50).dimmed());
+        println!();
+
+        // ドライランモードの処理
+        if cli.dry_run {
+            println!("{}", "Dry run mode - commit was not amended.".yellow());
+            return Ok(());
+        }
+
+        // 確認してamend
+        if cli.auto_confirm || self.confirm_amend()? {
+            let head_before = self.git.get_head_hash()?;
+            self.git.amend_commit(&message, self.author.as_deref())?;
+            self.record_rewrite("amend", &head_before);
+            println!("{}", "✓ Commit amended successfully!".green().bold());
+            Self::print_committed_with(Some(provider));
+        } else {
+            println!("{}", "Amend cancelled.".yellow());
+            return Err(AppError::UserCancelled);
+        }
+
+        Ok(())
+    }
+
+    /// フィルタ後の差分が空でもファイルがステージされていれば、ファイル一覧から生成するよう切り替える
+    ///
+    /// 概要を返した場合、以降のプロンプトは --stat-only と同じ形式になる
+    fn file_list_fallback(&mut self, pathspec: &[String]) -> Result<Option<String>, AppError> {
+        let staged_files = self.git.get_staged_file_names(pathspec)?;
+        let summary =
+            filtered_out_summary(&staged_files, || self.git.get_staged_stat_summary(pathspec))?;
+        if summary.is_some() {
+            eprintln!(
+                "{} {}",
+                "⚠".yellow(),
+                "Staged changes have no diff content after filtering (binary, ignored or whitespace-only files). Generating from the file list; use --include-whitespace to include whitespace-only changes."
+                    .yellow()
+            );
+            self.ai.set_stat_only(true);
+        }
+        Ok(summary)
+    }
+
+    /// --squash のベース、または --squash-upstream の追跡ブランチ
+    fn squash_base(&self, cli: &Cli) -> Result<String, AppError> {
+        resolve_squash_base(cli.squash.as_deref(), cli.squash_upstream, || {
+            self.git.get_upstream_ref()
+        })
+    }
+
+    /// ベースブランチとの merge-base（--squash / --squash-upstream）
+    fn squash_branch_base(&self, cli: &Cli) -> Result<String, AppError> {
+        // ベースブランチを取得（必須）
+        let base_branch = &self.squash_base(cli)?;
+
         // ベースブランチの存在確認
         if !self.git.branch_exists(base_branch) {
             return Err(AppError::GitError(format!(
@@ -621,7 +2441,30 @@ impl App {
         );
 
         // merge-baseを取得
-        let merge_base = self.git.get_merge_base(base_branch, "HEAD")?;
+        self.git.get_merge_base(base_branch, "HEAD")
+    }
+
+    /// --squash-last N の起点（HEAD~N のコミットハッシュ）
+    fn squash_last_base(&self, n: usize) -> Result<String, AppError> {
+        resolve_squash_last(
+            n,
+            |rev| self.git.resolve_commit(rev),
+            |n| self.git.has_merge_commits_in_range(n),
+        )
+    }
+
+    /// squashワークフローを実行
+    fn run_squash(&self, cli: &Cli) -> Result<(), AppError> {
+        let merge_base = match cli.squash_last {
+            Some(n) => {
+                println!(
+                    "{}",
+                    format!("Squash mode: combining the last {} commits into one...", n).cyan()
+                );
+                self.squash_last_base(n)?
+            }
+            None => self.squash_branch_base(cli)?,
+        };
 
         // コミット数を確認
         let commit_count = self.git.count_commits_from_base(&merge_base)?;
@@ -637,6 +2480,9 @@ impl App {
             return Err(AppError::NoChanges);
         }
 
+        // --print-diff: プロンプトに埋め込む差分をそのまま表示
+        Self::print_diff_if_requested(cli, &diff);
+
         // プレフィックスモードを判定
         let prefix_mode = self.get_prefix_mode();
 
@@ -648,7 +2494,7 @@ impl App {
             self.debug_print_for_prefix_mode(&diff, &[], &prefix_mode, true, cli.with_body);
         }
 
-        let mut message = match &prefix_mode {
+        let (mut message, provider) = match &prefix_mode {
             PrefixMode::Script(_) => {
                 // スクリプトモード: プレフィックスなしで生成
                 self.ai
@@ -683,6 +2529,12 @@ impl App {
             }
         }
 
+        // 件名の長さ制限などの最終整形
+        let message = self.finalize_message(message)?;
+
+        // --append / footer_template / 課題番号のトレーラーを追加
+        let message = self.append_trailers(&message, &self.issue_footers(&diff));
+
         // 生成されたメッセージを表示
         println!();
         println!("{}", "Generated commit message:".green().bold());
@@ -694,20 +2546,24 @@ impl App {
         // ドライランモードの処理
         if cli.dry_run {
             println!("{}", "Dry run mode - no squash was performed.".yellow());
+            println!("{}", squash_plan(&merge_base, commit_count).dimmed());
             return Ok(());
         }
 
         // 確認してsquash実行
         if cli.auto_confirm || self.confirm_squash(commit_count)? {
             // soft resetしてコミット
+            let head_before = self.git.get_head_hash()?;
             self.git.soft_reset_to(&merge_base)?;
-            self.git.commit(&message)?;
+            self.git.commit(&message, &self.commit_options())?;
+            self.record_rewrite("squash", &head_before);
             println!(
                 "{}",
                 format!("✓ {} commits squashed successfully!", commit_count)
                     .green()
                     .bold()
             );
+            Self::print_committed_with(Some(provider));
 
             // auto-push が有効な場合は push も実行
             if self.git.is_auto_push_enabled(self.auto_push) {
@@ -733,38 +2589,72 @@ impl App {
             return Err(AppError::InvalidCommitHash("(empty)".to_string()));
         }
 
-        // 各コミットのdiffを取得して結合
-        let mut combined_diff = String::new();
-        for hash in hashes {
-            let diff = self.git.get_commit_diff_by_hash(hash)?;
-            if !diff.trim().is_empty() {
-                if !combined_diff.is_empty() {
-                    combined_diff.push('\n');
-                }
-                combined_diff.push_str(&diff);
-            }
+        // --chronological: 引数の順ではなく古いコミットから順に並べる
+        let sorted;
+        let hashes = if cli.chronological {
+            sorted = sort_chronologically(hashes, |hash| self.git.get_commit_time(hash))?;
+            &sorted
+        } else {
+            hashes
+        };
+
+        // --per-commit: コミットごとに個別のメッセージを生成
+        if cli.per_commit {
+            return self.run_generate_for_per_commit(cli, hashes);
         }
 
+        let combined_diff = self.get_generate_for_diff(hashes)?;
         if combined_diff.trim().is_empty() {
             return Err(AppError::NoChanges);
         }
 
+        self.print_message_for_diff(cli, &combined_diff)
+    }
+
+    /// --patch ワークフローを実行（差分ファイルからメッセージを生成し標準出力に出力）
+    fn run_patch(&self, cli: &Cli, path: &Path) -> Result<(), AppError> {
+        let diff = self
+            .git
+            .filter_external_diff(&Self::read_patch_file(path)?)?;
+        if diff.trim().is_empty() {
+            return Err(AppError::NoChanges);
+        }
+
+        self.print_message_for_diff(cli, &diff)
+    }
+
+    /// --patch の差分ファイルを読み込む（unified diff でなければエラー）
+    fn read_patch_file(path: &Path) -> Result<String, AppError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| AppError::InvalidPatchFile(format!("{}: {}", path.display(), e)))?;
+        if !looks_like_diff(&content) {
+            return Err(AppError::InvalidPatchFile(format!(
+                "{}: not a unified diff",
+                path.display()
+            )));
+        }
+        Ok(content)
+    }
+
+    /// 差分からメッセージを生成し、標準出力にメッセージのみを出力（--generate-for / --patch）
+    fn print_message_for_diff(&self, cli: &Cli, combined_diff: &str) -> Result<(), AppError> {
+        // --print-diff: プロンプトに埋め込む差分をそのまま表示
+        Self::print_diff_if_requested(cli, combined_diff);
+
         // プレフィックスモードを判定（サイレントモード）
         let prefix_mode = self.get_prefix_mode_silent();
 
         // フォーマット検出用に直近のコミットを取得
-        let recent_commits = self.git.get_recent_commits(5)?;
+        let recent_commits = self.reference_commits(0)?;
 
         // デバッグモード: プロンプトを標準エラー出力に表示（標準出力はメッセージのみ）
         if cli.debug {
             eprintln!();
-            let (prefix_type, commits) =
-                Self::get_debug_params_for_prefix_mode(&prefix_mode, &recent_commits, false);
-            let prompt = AiService::build_prompt(
-                &combined_diff,
-                commits,
-                self.ai.language(),
-                prefix_type,
+            let prompt = self.prompt_for_prefix_mode(
+                combined_diff,
+                &recent_commits,
+                &prefix_mode,
+                false,
                 cli.with_body,
             );
             eprintln!("{}", "=== DEBUG: AI Prompt ===".yellow().bold());
@@ -775,36 +2665,49 @@ impl App {
             eprintln!();
         }
 
+        let message =
+            self.generate_for_message(combined_diff, &recent_commits, &prefix_mode, cli.with_body)?;
+
+        // 標準出力にメッセージのみを出力（余計な装飾なし）
+        println!("{}", message);
+
+        Ok(())
+    }
+
+    /// --generate-for 用にサイレントモードでメッセージを生成（プレフィックス処理と最終整形を含む）
+    fn generate_for_message(
+        &self,
+        diff: &str,
+        recent_commits: &[String],
+        prefix_mode: &PrefixMode,
+        with_body: bool,
+    ) -> Result<String, AppError> {
         // コミットメッセージを生成（サイレントモード）
-        let mut message = match &prefix_mode {
-            PrefixMode::Script(_) => self.ai.generate_commit_message_silent(
-                &combined_diff,
-                &[],
-                Some("plain"),
-                cli.with_body,
-            )?,
+        let mut message = match prefix_mode {
+            PrefixMode::Script(_) => {
+                self.ai
+                    .generate_commit_message_silent(diff, &[], Some("plain"), with_body)?
+            }
             PrefixMode::Rule(prefix_type) | PrefixMode::Config(prefix_type) => {
                 // ルール/設定モード: 指定されたprefix_typeで生成
                 self.ai.generate_commit_message_silent(
-                    &combined_diff,
-                    &recent_commits,
+                    diff,
+                    recent_commits,
                     Some(prefix_type),
-                    cli.with_body,
+                    with_body,
                 )?
             }
-            PrefixMode::Auto => self.ai.generate_commit_message_silent(
-                &combined_diff,
-                &recent_commits,
-                None,
-                cli.with_body,
-            )?,
+            PrefixMode::Auto => {
+                self.ai
+                    .generate_commit_message_silent(diff, recent_commits, None, with_body)?
+            }
         };
 
         // スクリプトモードの場合はメッセージを加工
         if let PrefixMode::Script(result) = prefix_mode {
             match result {
                 ScriptResult::Prefix(prefix) => {
-                    message = self.apply_prefix(&message, &prefix);
+                    message = self.apply_prefix(&message, prefix);
                 }
                 ScriptResult::Empty => {
                     message = self.strip_type_prefix(&message);
@@ -815,9 +2718,32 @@ impl App {
             }
         }
 
-        // 標準出力にメッセージのみを出力（余計な装飾なし）
-        println!("{}", message);
+        // 件名の長さ制限などの最終整形
+        self.finalize_message(message)
+    }
+
+    /// --generate-for --per-commit: 各コミットのメッセージを "hash: message" 形式で出力
+    fn run_generate_for_per_commit(&self, cli: &Cli, hashes: &[String]) -> Result<(), AppError> {
+        let prefix_mode = self.get_prefix_mode_silent();
+        let recent_commits = self.reference_commits(0)?;
+
+        let mut entries = Vec::with_capacity(hashes.len());
+        for hash in hashes {
+            let diff = self.git.get_commit_diff_by_hash(hash)?;
+            if diff.trim().is_empty() {
+                continue;
+            }
+            Self::print_diff_if_requested(cli, &diff);
+            let message =
+                self.generate_for_message(&diff, &recent_commits, &prefix_mode, cli.with_body)?;
+            entries.push((hash.clone(), message));
+        }
+
+        if entries.is_empty() {
+            return Err(AppError::NoChanges);
+        }
 
+        println!("{}", format_per_commit_messages(&entries));
         Ok(())
     }
 
@@ -830,7 +2756,7 @@ impl App {
             .clone();
 
         // 短いハッシュを取得して表示用に使用
-        let short_hash = if hash.len() > 7 { &hash[..7] } else { &hash };
+        let short_hash = short_hash(&hash);
 
         println!(
             "{}",
@@ -855,6 +2781,9 @@ impl App {
             return Err(AppError::NoChanges);
         }
 
+        // --print-diff: プロンプトに埋め込む差分をそのまま表示
+        Self::print_diff_if_requested(cli, &diff);
+
         // 現在のコミットメッセージを表示
         let current_message = self.git.get_commit_message_by_hash(&hash)?;
         println!("{}", "Current commit message:".cyan());
@@ -864,8 +2793,7 @@ impl App {
         let prefix_mode = self.get_prefix_mode();
 
         // フォーマット検出用に直近のコミットを取得（対象コミットより新しいものを除く）
-        let recent_commits = self.git.get_recent_commits(5 + n)?;
-        let recent_commits: Vec<String> = recent_commits.into_iter().skip(n).collect();
+        let recent_commits = self.reference_commits(n)?;
 
         // Autoモードの場合のみ参照用に直近のコミットを表示
         if matches!(prefix_mode, PrefixMode::Auto) {
@@ -878,7 +2806,7 @@ impl App {
             } else {
                 println!("{}", "Recent commits (for format reference):".cyan());
                 for commit in &recent_commits {
-                    println!("  {}", commit.dimmed());
+                    println!("  {}", commit.lines().next().unwrap_or_default().dimmed());
                 }
             }
         }
@@ -897,7 +2825,7 @@ impl App {
             );
         }
 
-        let mut message = match &prefix_mode {
+        let (mut message, provider) = match &prefix_mode {
             PrefixMode::Script(_) => {
                 // スクリプトモード: プレフィックスなしで生成
                 self.ai
@@ -936,6 +2864,13 @@ impl App {
             }
         }
 
+        // 件名の長さ制限などの最終整形
+        let message = self.finalize_message(message)?;
+
+        // 元のコミットのトレーラー（Co-authored-by / Signed-off-by など）を引き継ぐ
+        let preserved = extract_trailers(&self.git.get_full_commit_message_by_hash(&hash)?);
+        let message = reattach_trailers(&message, &preserved);
+
         // 生成されたメッセージを表示
         println!();
         println!("{}", "Generated commit message:".green().bold());
@@ -947,18 +2882,25 @@ impl App {
         // ドライランモードの処理
         if cli.dry_run {
             println!("{}", "Dry run mode - commit was not reworded.".yellow());
+            println!(
+                "{}",
+                reword_plan(&self.git.rebase_target(n), n, &hash).dimmed()
+            );
             return Ok(());
         }
 
         // 確認してreword実行
         if cli.auto_confirm || self.confirm_reword(short_hash)? {
+            let head_before = self.git.get_head_hash()?;
             self.git.reword_commit_by_hash(&hash, &message)?;
+            self.record_rewrite("reword", &head_before);
             println!(
                 "{}",
                 format!("✓ Commit {} reworded successfully!", short_hash)
                     .green()
                     .bold()
             );
+            Self::print_committed_with(Some(provider));
             println!(
                 "{}",
                 "Note: You may need to force push (git push --force) if already pushed.".yellow()
@@ -971,6 +2913,289 @@ impl App {
         Ok(())
     }
 
+    /// ステージ済みの変更を指定コミットに取り込み、合わせた差分からメッセージを再生成する
+    fn run_amend_at(&self, cli: &Cli) -> Result<(), AppError> {
+        let hash = cli
+            .amend_at
+            .as_ref()
+            .ok_or(AppError::InvalidRewordTarget)?
+            .clone();
+        let short_hash = short_hash(&hash);
+
+        println!(
+            "{}",
+            format!(
+                "Amend-at mode: folding staged changes into commit {}...",
+                short_hash
+            )
+            .cyan()
+        );
+
+        // --allフラグがあれば全変更を対象にする（ステージングは確認後に行う）
+        let has_changes = if cli.stage_all {
+            self.git.has_uncommitted_changes()?
+        } else {
+            self.git.has_staged_changes()?
+        };
+        if !has_changes {
+            return Err(AppError::NoStagedChanges);
+        }
+
+        // マージコミットが含まれていないか確認（reword と同じ制約）
+        if self.git.has_merge_commits_in_range_by_hash(&hash)? {
+            return Err(AppError::HasMergeCommits);
+        }
+
+        let n = self.git.get_commit_position_by_hash(&hash)?;
+
+        // 対象コミットと取り込む変更を合わせたdiffを取得
+        let pending = if cli.stage_all {
+            self.git.get_uncommitted_diff()?
+        } else {
+            self.git.get_staged_diff(&[])?
+        };
+        let diff = format!("{}\n{}", self.git.get_commit_diff_by_hash(&hash)?, pending);
+        if diff.trim().is_empty() {
+            return Err(AppError::NoChanges);
+        }
+
+        // --print-diff: プロンプトに埋め込む差分をそのまま表示
+        Self::print_diff_if_requested(cli, &diff);
+
+        // プレフィックスモードを判定
+        let prefix_mode = self.get_prefix_mode();
+
+        // フォーマット検出用に直近のコミットを取得（対象コミットより新しいものを除く）
+        let recent_commits = self.reference_commits(n)?;
+
+        // コミットメッセージを生成
+        println!("{}", "Generating commit message...".cyan());
+
+        // デバッグモード: プロンプトを表示
+        if cli.debug {
+            self.debug_print_for_prefix_mode(
+                &diff,
+                &recent_commits,
+                &prefix_mode,
+                false,
+                cli.with_body,
+            );
+        }
+
+        let (mut message, provider) = match &prefix_mode {
+            PrefixMode::Script(_) => {
+                // スクリプトモード: プレフィックスなしで生成
+                self.ai
+                    .generate_commit_message(&diff, &[], Some("plain"), cli.with_body)?
+            }
+            PrefixMode::Rule(prefix_type) | PrefixMode::Config(prefix_type) => {
+                // ルール/設定モード: 指定されたprefix_typeで生成
+                self.ai.generate_commit_message(
+                    &diff,
+                    &recent_commits,
+                    Some(prefix_type),
+                    cli.with_body,
+                )?
+            }
+            PrefixMode::Auto => {
+                // 自動判定モード: 過去コミットから推論
+                self.ai
+                    .generate_commit_message(&diff, &recent_commits, None, cli.with_body)?
+            }
+        };
+
+        // スクリプトモードの場合はメッセージを加工
+        if let PrefixMode::Script(result) = prefix_mode {
+            match result {
+                ScriptResult::Prefix(prefix) => {
+                    message = self.apply_prefix(&message, &prefix);
+                    println!("{}", format!("Applied prefix: {}", prefix.trim()).cyan());
+                }
+                ScriptResult::Empty => {
+                    message = self.strip_type_prefix(&message);
+                    println!("{}", "No prefix applied (script returned empty).".cyan());
+                }
+                ScriptResult::Failed => {
+                    println!("{}", "Using AI-generated format.".cyan());
+                }
+            }
+        }
+
+        // 件名の長さ制限などの最終整形
+        let message = self.finalize_message(message)?;
+
+        // 対象コミットのトレーラー（Co-authored-by / Signed-off-by など）を引き継ぐ
+        let preserved = extract_trailers(&self.git.get_full_commit_message_by_hash(&hash)?);
+        let message = reattach_trailers(&message, &preserved);
+
+        // 生成されたメッセージを表示
+        println!();
+        println!("{}", "Generated commit message:".green().bold());
+        println!("{}", "─".repeat(50).dimmed());
+        println!("{}", message);
+        println!("{}", "─".repeat(50).dimmed());
+        println!();
+
+        // ドライランモードの処理
+        if cli.dry_run {
+            println!("{}", "Dry run mode - commit was not amended.".yellow());
+            println!(
+                "{}",
+                amend_at_plan(&self.git.rebase_target(n), n, &hash).dimmed()
+            );
+            return Ok(());
+        }
+
+        // 確認してamend-at実行
+        if cli.auto_confirm
+            || self.confirm_prompt(&format!("Amend commit {}? [Y/n] ", short_hash))?
+        {
+            if cli.stage_all {
+                println!("{}", "Staging all changes...".cyan());
+                self.git.stage_all()?;
+            }
+            let head_before = self.git.get_head_hash()?;
+            self.git
+                .amend_commit_at(&hash, &message, self.author.as_deref())?;
+            self.record_rewrite("amend-at", &head_before);
+            println!(
+                "{}",
+                format!("✓ Commit {} amended successfully!", short_hash)
+                    .green()
+                    .bold()
+            );
+            Self::print_committed_with(Some(provider));
+            println!(
+                "{}",
+                "Note: You may need to force push (git push --force) if already pushed.".yellow()
+            );
+        } else {
+            println!("{}", "Amend cancelled.".yellow());
+            return Err(AppError::UserCancelled);
+        }
+
+        Ok(())
+    }
+
+    /// パス指定なしでコミットする際のオプション（--author を反映）
+    fn commit_options(&self) -> CommitOptions<'_> {
+        CommitOptions {
+            author: self.author.as_deref(),
+            ..CommitOptions::default()
+        }
+    }
+
+    /// 履歴を書き換えた操作の前後の HEAD を記録（--undo 用、記録の失敗は無視）
+    fn record_rewrite(&self, action: &str, head_before: &str) {
+        let (Ok(repo), Ok(head_after)) = (self.git.get_git_dir(), self.git.get_head_hash()) else {
+            return;
+        };
+        if let Ok(mut state) = State::load_scoped(self.scope_state_to_repo) {
+            state.record_rewrite(&repo, action, head_before, &head_after);
+            let _ = state.save_scoped(self.scope_state_to_repo);
+        }
+    }
+
+    /// 直前の amend / squash / reword を取り消し、操作前の HEAD に戻す
+    fn run_undo(&self, cli: &Cli) -> Result<(), AppError> {
+        let repo = self.git.get_git_dir()?;
+        let mut state = State::load_scoped(self.scope_state_to_repo)?;
+        let rewrite = state
+            .history_rewrites
+            .get(&repo)
+            .cloned()
+            .ok_or(AppError::NothingToUndo)?;
+
+        // 記録後に HEAD が動いていれば、その後の作業を消さないよう中止
+        let head = self.git.get_head_hash()?;
+        if head != rewrite.head_after {
+            return Err(AppError::UndoNotPossible(format!(
+                "HEAD has moved since the last {} ({} -> {})",
+                rewrite.action,
+                short_hash(&rewrite.head_after),
+                short_hash(&head)
+            )));
+        }
+        println!(
+            "{}",
+            format!(
+                "Last {}: {} -> {}",
+                rewrite.action,
+                short_hash(&rewrite.head_before),
+                short_hash(&rewrite.head_after)
+            )
+            .cyan()
+        );
+
+        if cli.dry_run {
+            println!("{}", "Dry run mode - HEAD was not reset.".yellow());
+            println!(
+                "{}",
+                format!("Would run: git reset --soft {}", rewrite.head_before).dimmed()
+            );
+            return Ok(());
+        }
+
+        let prompt = format!(
+            "Reset HEAD to {} (git reset --soft)? [Y/n] ",
+            short_hash(&rewrite.head_before)
+        );
+        if cli.auto_confirm || self.confirm_prompt(&prompt)? {
+            // 取り込んだ変更は作業ツリーに残し、ステージされた状態に戻す
+            self.git.soft_reset_to(&rewrite.head_before)?;
+            state.take_rewrite(&repo);
+            let _ = state.save_scoped(self.scope_state_to_repo);
+            println!(
+                "{}",
+                format!("✓ Restored HEAD to {}", short_hash(&rewrite.head_before))
+                    .green()
+                    .bold()
+            );
+        } else {
+            println!("{}", "Undo cancelled.".yellow());
+            return Err(AppError::UserCancelled);
+        }
+
+        Ok(())
+    }
+
+    /// push 済みのコミットを amend する前に確認する
+    ///
+    /// --yes では確認を省略せず、--force-amend の指定時とドライランのみ確認なしで進む
+    fn guard_pushed_amend(&self, cli: &Cli) -> Result<(), AppError> {
+        if cli.force_amend || cli.dry_run || !self.git.is_head_pushed() {
+            return Ok(());
+        }
+
+        eprintln!(
+            "{} {}",
+            "⚠".yellow(),
+            "The last commit is already on the upstream. Amending rewrites published history; you will need `git push --force-with-lease` afterwards (or pass --force-amend)."
+                .yellow()
+        );
+        let mut tty = match open_tty() {
+            Some(tty) => tty,
+            None => {
+                eprintln!(
+                    "{}",
+                    "端末が利用できないため確認できません。push 済みのコミットを amend するには --force-amend を指定してください。"
+                        .yellow()
+                );
+                return Err(AppError::UserCancelled);
+            }
+        };
+        print!("{}", "Amend the pushed commit anyway? [y/N] ".cyan());
+        io::stdout()
+            .flush()
+            .map_err(|e| AppError::GitError(e.to_string()))?;
+        if read_explicit_confirmation(&mut tty)? {
+            Ok(())
+        } else {
+            println!("{}", "Amend cancelled.".yellow());
+            Err(AppError::UserCancelled)
+        }
+    }
+
     /// コミット確認プロンプトを表示
     fn confirm_commit(&self) -> Result<bool, AppError> {
         self.confirm_prompt("Create this commit? [Y/n] ")
@@ -992,19 +3217,28 @@ impl App {
     }
 
     /// 汎用確認プロンプト
+    ///
+    /// 入力は stdin ではなく制御端末から読み取る。端末がない場合は
+    /// 非対話環境とみなし、--yes の指定を促してキャンセル扱いにする
     fn confirm_prompt(&self, prompt: &str) -> Result<bool, AppError> {
+        let mut tty = match open_tty() {
+            Some(tty) => tty,
+            None => {
+                eprintln!(
+                    "{}",
+                    "端末が利用できないため確認できません。非対話環境では --yes を指定してください。"
+                        .yellow()
+                );
+                return Err(AppError::UserCancelled);
+            }
+        };
+
         print!("{}", prompt.cyan());
         io::stdout()
             .flush()
             .map_err(|e| AppError::GitError(e.to_string()))?;
 
-        let mut input = String::new();
-        io::stdin()
-            .read_line(&mut input)
-            .map_err(|e| AppError::GitError(e.to_string()))?;
-
-        let input = input.trim().to_lowercase();
-        Ok(input.is_empty() || input == "y" || input == "yes")
+        read_confirmation(&mut tty)
     }
 }
 
@@ -1162,6 +3396,1240 @@ mod tests {
         let _auto = PrefixMode::Auto;
     }
 
+    // ============================================================
+    // truncate_subject のテスト
+    // ============================================================
+
+    #[rstest]
+    #[case("feat: add login", 50, "feat: add login")]
+    #[case("feat: add login", 15, "feat: add login")]
+    #[case("feat: add login page for users", 20, "feat: add login…")]
+    #[case("feat: add login page", 11, "feat: add…")]
+    fn test_truncate_subject(#[case] subject: &str, #[case] max: usize, #[case] expected: &str) {
+        assert_eq!(truncate_subject(subject, max), expected);
+    }
+
+    #[test]
+    fn test_truncate_subject_multibyte_without_spaces() {
+        // 空白がない場合は文字単位で切り、バイト境界で壊れない
+        let result = truncate_subject("ログイン機能を追加しました", 8);
+        assert_eq!(result, "ログイン機能を…");
+        assert_eq!(result.chars().count(), 8);
+    }
+
+    #[test]
+    fn test_truncate_subject_multibyte_under_limit() {
+        // バイト数ではなく文字数で判定する
+        let subject = "feat: ログイン機能を追加";
+        assert_eq!(truncate_subject(subject, 16), subject);
+    }
+
+    #[test]
+    fn test_truncate_subject_long_single_word() {
+        assert_eq!(truncate_subject("abcdefghijklmnop", 6), "abcde…");
+    }
+
+    #[test]
+    fn test_truncate_subject_zero() {
+        assert_eq!(truncate_subject("feat: add login", 0), "");
+    }
+
+    #[test]
+    fn test_truncate_message_subject_preserves_body() {
+        let message =
+            "feat: add login page for users\n\n- very long body line that must stay intact";
+        assert_eq!(
+            truncate_message_subject(message, 20),
+            "feat: add login…\n\n- very long body line that must stay intact"
+        );
+    }
+
+    // ============================================================
+    // validate_max_subject_length のテスト
+    // ============================================================
+
+    #[rstest]
+    #[case(None)]
+    #[case(Some(10))]
+    #[case(Some(72))]
+    fn test_validate_max_subject_length_ok(#[case] value: Option<usize>) {
+        assert_eq!(validate_max_subject_length(value).unwrap(), value);
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(1)]
+    #[case(9)]
+    fn test_validate_max_subject_length_too_small(#[case] value: usize) {
+        let err = validate_max_subject_length(Some(value)).unwrap_err();
+        assert!(matches!(err, AppError::ConfigError(ref m)
+            if m == &format!("max_subject_length must be at least 10 (got {})", value)));
+    }
+
+    // ============================================================
+    // resolve_diff_algorithm のテスト
+    // ============================================================
+
+    #[test]
+    fn test_resolve_diff_algorithm() {
+        assert_eq!(resolve_diff_algorithm(None, None).unwrap(), None);
+        assert_eq!(
+            resolve_diff_algorithm(None, Some("Histogram")).unwrap(),
+            Some("histogram".to_string())
+        );
+        // CLI の指定が設定ファイルより優先される
+        assert_eq!(
+            resolve_diff_algorithm(Some("patience"), Some("histogram")).unwrap(),
+            Some("patience".to_string())
+        );
+        assert!(matches!(
+            resolve_diff_algorithm(None, Some("fast")),
+            Err(AppError::ConfigError(_))
+        ));
+    }
+
+    // ============================================================
+    // wrap_body のテスト
+    // ============================================================
+
+    #[test]
+    fn test_wrap_body_keeps_subject_and_short_lines() {
+        let message = "feat: add a very long subject line that is not wrapped at all\n\nshort line";
+        assert_eq!(wrap_body(message, 20), message);
+    }
+
+    #[test]
+    fn test_wrap_body_preserves_bullet_indentation() {
+        let message = "feat: add login\n\n- add the login form with email and password fields\n  - validate the input";
+        assert_eq!(
+            wrap_body(message, 24),
+            "feat: add login\n\n- add the login form\n  with email and\n  password fields\n  - validate the input"
+        );
+    }
+
+    #[test]
+    fn test_wrap_body_does_not_break_long_words() {
+        let message =
+            "fix: paths\n\nsee https://example.com/a/very/long/path/that/cannot/break for details";
+        assert_eq!(
+            wrap_body(message, 20),
+            "fix: paths\n\nsee\nhttps://example.com/a/very/long/path/that/cannot/break\nfor details"
+        );
+    }
+
+    #[test]
+    fn test_wrap_body_keeps_paragraph_boundaries_and_trailers() {
+        let message = "docs: update\n\nfirst paragraph that is long enough\n\nsecond paragraph\n\nCo-authored-by: Alice Example <alice@example.com>";
+        assert_eq!(
+            wrap_body(message, 20),
+            "docs: update\n\nfirst paragraph that\nis long enough\n\nsecond paragraph\n\nCo-authored-by: Alice Example <alice@example.com>"
+        );
+    }
+
+    #[test]
+    fn test_wrap_body_zero_disables() {
+        let message = "feat: add\n\n- a line that would otherwise be wrapped";
+        assert_eq!(wrap_body(message, 0), message);
+    }
+
+    // ============================================================
+    // with_clipboard_context のテスト
+    // ============================================================
+
+    #[test]
+    fn test_with_clipboard_context() {
+        let context = vec!["why: flaky test".to_string()];
+        assert_eq!(
+            with_clipboard_context(
+                context.clone(),
+                Some("  BUG-42: login fails on retry\n".to_string())
+            ),
+            vec!["why: flaky test", "BUG-42: login fails on retry"]
+        );
+
+        // 空のクリップボードや読み取り失敗ではコンテキストを増やさない
+        assert_eq!(
+            with_clipboard_context(context.clone(), Some(" \n".to_string())),
+            context
+        );
+        assert_eq!(
+            with_clipboard_context(Vec::new(), None),
+            Vec::<String>::new()
+        );
+
+        // クリップボードの内容はプロンプトのコンテキスト欄に入る
+        let mut ai = AiService::new();
+        ai.set_context(with_clipboard_context(
+            Vec::new(),
+            Some("BUG-42: login fails on retry".to_string()),
+        ));
+        let prompt = ai.build_prompt_for("diff --git a/a b/a\n+x", &[], None, false);
+        assert!(prompt.contains("BUG-42: login fails on retry"));
+    }
+
+    // ============================================================
+    // format_print_diff のテスト
+    // ============================================================
+
+    #[test]
+    fn test_format_print_diff_matches_prompt_diff() {
+        let diff = "diff --git a/src/lib.rs b/src/lib.rs\n@@ -1 +1 @@\n-old\n+new";
+        let printed = format_print_diff(diff);
+
+        let body = printed
+            .split_once('\n')
+            .and_then(|(header, rest)| {
+                assert_eq!(
+                    header,
+                    format!("=== Diff sent to AI ({} bytes) ===", diff.len())
+                );
+                rest.strip_suffix("\n=== END DIFF ===")
+            })
+            .unwrap();
+        assert_eq!(body, diff);
+
+        // 表示した差分がそのままプロンプトに埋め込まれる
+        let prompt = AiService::new().build_prompt_for(body, &[], None, false);
+        assert!(prompt.contains(&format!("```diff\n{}\n```", diff)));
+    }
+
+    // ============================================================
+    // resolve_fail_on_empty のテスト
+    // ============================================================
+
+    #[test]
+    fn test_resolve_fail_on_empty() {
+        assert_eq!(resolve_fail_on_empty(false, None).unwrap(), None);
+        assert_eq!(
+            resolve_fail_on_empty(false, Some("ok")).unwrap(),
+            Some(false)
+        );
+        assert_eq!(
+            resolve_fail_on_empty(false, Some("Error")).unwrap(),
+            Some(true)
+        );
+        // --fail-on-empty は設定より優先
+        assert_eq!(resolve_fail_on_empty(true, Some("ok")).unwrap(), Some(true));
+        assert!(matches!(
+            resolve_fail_on_empty(false, Some("ignore")),
+            Err(AppError::ConfigError(_))
+        ));
+    }
+
+    // ============================================================
+    // empty_changes_result のテスト
+    // ============================================================
+
+    #[test]
+    fn test_empty_changes_result_default_keeps_current_behavior() {
+        assert!(empty_changes_result(true, None).is_ok());
+        assert!(matches!(
+            empty_changes_result(false, None),
+            Err(AppError::NoStagedChanges)
+        ));
+    }
+
+    #[test]
+    fn test_empty_changes_result_ok() {
+        assert!(empty_changes_result(true, Some(false)).is_ok());
+        assert!(empty_changes_result(false, Some(false)).is_ok());
+    }
+
+    #[test]
+    fn test_empty_changes_result_error() {
+        assert!(matches!(
+            empty_changes_result(true, Some(true)),
+            Err(AppError::NoChanges)
+        ));
+        assert!(matches!(
+            empty_changes_result(false, Some(true)),
+            Err(AppError::NoStagedChanges)
+        ));
+    }
+
+    // ============================================================
+    // sort_chronologically のテスト
+    // ============================================================
+
+    #[test]
+    fn test_sort_chronologically() {
+        let times = |hash: &str| match hash {
+            "ccc" => Ok(300),
+            "aaa" => Ok(100),
+            "bbb" => Ok(200),
+            "bbb2" => Ok(200),
+            other => Err(AppError::InvalidCommitHash(other.to_string())),
+        };
+        let hashes: Vec<String> = ["ccc", "bbb2", "aaa", "bbb"]
+            .iter()
+            .map(|h| h.to_string())
+            .collect();
+
+        // 古い順、同時刻は引数の順
+        assert_eq!(
+            sort_chronologically(&hashes, times).unwrap(),
+            vec!["aaa", "bbb2", "bbb", "ccc"]
+        );
+        assert!(matches!(
+            sort_chronologically(&["zzz".to_string()], times),
+            Err(AppError::InvalidCommitHash(h)) if h == "zzz"
+        ));
+    }
+
+    // ============================================================
+    // is_shortcode_emoji_style のテスト
+    // ============================================================
+
+    #[test]
+    fn test_is_shortcode_emoji_style() {
+        assert!(!is_shortcode_emoji_style(None).unwrap());
+        assert!(!is_shortcode_emoji_style(Some("unicode")).unwrap());
+        assert!(is_shortcode_emoji_style(Some("Shortcode")).unwrap());
+        assert!(matches!(
+            is_shortcode_emoji_style(Some("ascii")),
+            Err(AppError::ConfigError(_))
+        ));
+    }
+
+    // ============================================================
+    // is_word_diff_mode のテスト
+    // ============================================================
+
+    #[test]
+    fn test_is_word_diff_mode() {
+        assert!(!is_word_diff_mode(None).unwrap());
+        assert!(!is_word_diff_mode(Some("line")).unwrap());
+        assert!(is_word_diff_mode(Some("Word")).unwrap());
+        assert!(matches!(
+            is_word_diff_mode(Some("char")),
+            Err(AppError::ConfigError(_))
+        ));
+    }
+
+    // ============================================================
+    // normalize_subject_case のテスト
+    // ============================================================
+
+    #[rstest]
+    #[case("feat: Add login", "feat: add login")]
+    #[case("feat(auth): Add OAuth flow", "feat(auth): add OAuth flow")]
+    #[case("fix: update deps", "fix: update deps")]
+    #[case("Add login", "add login")]
+    fn test_normalize_subject_case_lower(#[case] subject: &str, #[case] expected: &str) {
+        assert_eq!(
+            normalize_subject_case(subject, SubjectCase::Lower),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case("feat: add login", "feat: Add login")]
+    #[case("feat(auth)!: drop v1 API", "feat(auth)!: Drop v1 API")]
+    #[case("docs:  fix typo", "docs:  Fix typo")]
+    #[case("fix: ログインを修正", "fix: ログインを修正")]
+    #[case("chore:", "chore:")]
+    fn test_normalize_subject_case_sentence(#[case] subject: &str, #[case] expected: &str) {
+        assert_eq!(
+            normalize_subject_case(subject, SubjectCase::Sentence),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_subject_case_from_name() {
+        assert_eq!(SubjectCase::from_name("lower"), Some(SubjectCase::Lower));
+        assert_eq!(
+            SubjectCase::from_name("Sentence"),
+            Some(SubjectCase::Sentence)
+        );
+        assert_eq!(SubjectCase::from_name("title"), None);
+    }
+
+    // ============================================================
+    // filter_style_reference のテスト
+    // ============================================================
+
+    #[test]
+    fn test_filter_style_reference_drops_revert_and_merge() {
+        let commits: Vec<String> = [
+            "feat: add login",
+            "Revert \"feat: add signup\"",
+            "Merge branch 'main' into feature/login",
+            "fix: handle empty password",
+            "Merge pull request #12 from owner/feature",
+            "Revert \"fix: typo\"\n\nThis reverts commit abc1234.",
+            "Merge remote-tracking branch 'origin/main'",
+            "Merge config loaders into one module",
+            "Reverted the flaky retry logic",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        assert_eq!(
+            filter_style_reference(&commits),
+            vec![
+                "feat: add login",
+                "fix: handle empty password",
+                "Merge config loaders into one module",
+                "Reverted the flaky retry logic",
+            ]
+        );
+    }
+
+    // ============================================================
+    // fetch_style_reference のテスト
+    // ============================================================
+
+    #[test]
+    fn test_fetch_style_reference_no_recent_commits() {
+        let commits = || Ok(vec!["Update stuff".to_string(), "WIP".to_string()]);
+
+        assert_eq!(fetch_style_reference(false, commits).unwrap().len(), 2);
+
+        // 直近コミットがあっても取得せず、Auto モードは Conventional Commits を既定にする
+        let reference = fetch_style_reference(true, || -> Result<Vec<String>, AppError> {
+            panic!("recent commits must not be fetched")
+        })
+        .unwrap();
+        assert!(reference.is_empty());
+        let prompt = AiService::build_prompt("diff", &reference, "English", None, false);
+        assert!(prompt.contains("No recent commits found. Use Conventional Commits format"));
+        assert!(!prompt.contains("WIP"));
+    }
+
+    // ============================================================
+    // resolve_style_reference_commits のテスト
+    // ============================================================
+
+    #[rstest]
+    #[case(None, None, 5)]
+    #[case(None, Some(10), 10)]
+    #[case(Some(3), Some(10), 3)]
+    #[case(Some(0), None, 0)]
+    fn test_resolve_style_reference_commits(
+        #[case] cli: Option<usize>,
+        #[case] config: Option<usize>,
+        #[case] expected: usize,
+    ) {
+        assert_eq!(resolve_style_reference_commits(cli, config), expected);
+    }
+
+    // ============================================================
+    // resolve_squash_base のテスト
+    // ============================================================
+
+    #[test]
+    fn test_resolve_squash_base_explicit() {
+        let base = resolve_squash_base(Some("main"), false, || panic!("not called"));
+        assert_eq!(base.unwrap(), "main");
+    }
+
+    #[test]
+    fn test_resolve_squash_base_upstream() {
+        let base = resolve_squash_base(None, true, || Some("origin/main".to_string()));
+        assert_eq!(base.unwrap(), "origin/main");
+    }
+
+    #[test]
+    fn test_resolve_squash_base_missing_upstream() {
+        let result = resolve_squash_base(None, true, || None);
+        assert!(matches!(result, Err(AppError::NoBaseBranch)));
+    }
+
+    // ============================================================
+    // filtered_out_summary のテスト
+    // ============================================================
+
+    #[test]
+    fn test_filtered_out_summary_nothing_staged() {
+        let summary = filtered_out_summary(&[], || panic!("summary must not be fetched"));
+        assert_eq!(summary.unwrap(), None);
+    }
+
+    #[test]
+    fn test_filtered_out_summary_all_binary_uses_file_list() {
+        let files = vec!["assets/logo.png".to_string(), "assets/icon.png".to_string()];
+        let summary = filtered_out_summary(&files, || {
+            Ok(" 2 files changed\n\nA\tassets/logo.png\nA\tassets/icon.png".to_string())
+        })
+        .unwrap()
+        .unwrap();
+
+        // エラーにせず、ファイル一覧の概要からメッセージを生成する
+        let mut ai = AiService::new();
+        ai.set_stat_only(true);
+        let prompt = ai.build_prompt_for(&summary, &[], None, false);
+        assert!(prompt.contains("Changed files (summary only, source code is not included):"));
+        assert!(prompt.contains("A\tassets/logo.png\nA\tassets/icon.png"));
+        assert!(!prompt.contains("```diff"));
+    }
+
+    // ============================================================
+    // message_with_body のテスト
+    // ============================================================
+
+    #[test]
+    fn test_message_with_body() {
+        assert_eq!(
+            message_with_body("feat: add login", "- add form\n- add session\n"),
+            "feat: add login\n\n- add form\n- add session"
+        );
+        assert_eq!(
+            message_with_body("feat: add login", "  \n"),
+            "feat: add login"
+        );
+    }
+
+    // ============================================================
+    // resolve_squash_last のテスト
+    // ============================================================
+
+    #[test]
+    fn test_resolve_squash_last_base() {
+        let base = resolve_squash_last(
+            3,
+            |rev| {
+                assert_eq!(rev, "HEAD~3");
+                Some("abc1234".to_string())
+            },
+            |n| {
+                assert_eq!(n, 3);
+                Ok(false)
+            },
+        );
+        assert_eq!(base.unwrap(), "abc1234");
+    }
+
+    #[test]
+    fn test_resolve_squash_last_rejects_merges() {
+        let result = resolve_squash_last(3, |_| Some("abc1234".to_string()), |_| Ok(true));
+        assert!(matches!(result, Err(AppError::HasMergeCommits)));
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(1)]
+    fn test_resolve_squash_last_too_few(#[case] n: usize) {
+        let result = resolve_squash_last(n, |_| panic!("not called"), |_| panic!("not called"));
+        assert!(matches!(result, Err(AppError::NoCommitsToSquash)));
+    }
+
+    #[test]
+    fn test_resolve_squash_last_beyond_root() {
+        let result = resolve_squash_last(5, |_| None, |_| panic!("not called"));
+        assert!(matches!(result, Err(AppError::GitError(_))));
+    }
+
+    // ============================================================
+    // squash_plan / reword_plan のテスト
+    // ============================================================
+
+    #[test]
+    fn test_squash_plan() {
+        assert_eq!(
+            squash_plan("0123456789abcdef", 3),
+            "Would soft-reset to 0123456 and commit 3 commit(s) as one"
+        );
+    }
+
+    #[rstest]
+    #[case("HEAD~3", 3, "Would rebase -i HEAD~3 changing commit 3 (abcdef0)")]
+    #[case("--root", 2, "Would rebase -i --root changing commit 2 (abcdef0)")]
+    #[case("HEAD~1", 1, "Would amend HEAD (abcdef0)")]
+    fn test_reword_plan(#[case] target: &str, #[case] position: usize, #[case] expected: &str) {
+        assert_eq!(reword_plan(target, position, "abcdef0123456"), expected);
+    }
+
+    // ============================================================
+    // ExplainTrace / describe_prefix_mode のテスト
+    // ============================================================
+
+    #[test]
+    fn test_explain_trace_render_with_matched_rule() {
+        let trace = ExplainTrace {
+            remote_url: Some("git@github.com:owayo/app.git".to_string()),
+            branch: Some("feature/login".to_string()),
+            prefix: describe_prefix_mode(
+                &PrefixMode::Rule("conventional".to_string()),
+                Some("github\\.com[:/]owayo/"),
+            ),
+            providers: vec!["Codex CLI", "Gemini CLI"],
+            provider_used: Some("Codex CLI"),
+            diff_truncated: true,
+        };
+        assert_eq!(
+            trace.render(),
+            "[explain] remote: git@github.com:owayo/app.git\n\
+             [explain] branch: feature/login\n\
+             [explain] prefix: rule for github\\.com[:/]owayo/ → conventional\n\
+             [explain] providers: Codex CLI, Gemini CLI\n\
+             [explain] provider used: Codex CLI\n\
+             [explain] diff truncated: yes"
+        );
+    }
+
+    #[test]
+    fn test_describe_prefix_mode() {
+        assert_eq!(
+            describe_prefix_mode(
+                &PrefixMode::Script(ScriptResult::Prefix("[PROJ-1] ".to_string())),
+                Some("gitlab"),
+            ),
+            "script for gitlab → \"[PROJ-1]\""
+        );
+        assert_eq!(
+            describe_prefix_mode(&PrefixMode::Config("bracket".to_string()), None),
+            "config prefix_type → bracket"
+        );
+        assert_eq!(
+            describe_prefix_mode(&PrefixMode::Auto, None),
+            "auto (inferred from recent commits)"
+        );
+    }
+
+    // ============================================================
+    // amend_at_plan のテスト
+    // ============================================================
+
+    #[test]
+    fn test_amend_at_plan() {
+        assert_eq!(
+            amend_at_plan("HEAD~1", 1, "0123456789abcdef"),
+            "Would amend HEAD (0123456) with staged changes"
+        );
+        assert_eq!(
+            amend_at_plan("HEAD~3", 3, "0123456789abcdef"),
+            "Would rebase -i HEAD~3 stopping at commit 3 (0123456) to fold in staged changes"
+        );
+    }
+
+    // ============================================================
+    // short_hash のテスト
+    // ============================================================
+
+    #[test]
+    fn test_short_hash_shorter_than_seven() {
+        assert_eq!(short_hash("abc"), "abc");
+    }
+
+    // ============================================================
+    // split_on_scissors / insert_into_message_file のテスト
+    // ============================================================
+
+    const SCISSORS_FILE: &str = "\n# Please enter the commit message.\n# ------------------------ >8 ------------------------\n# Do not modify or remove the line above.\ndiff --git a/a.txt b/a.txt\n";
+
+    #[test]
+    fn test_split_on_scissors_present() {
+        let (above, below) = split_on_scissors(SCISSORS_FILE);
+        assert_eq!(above, "\n# Please enter the commit message.\n");
+        let below = below.unwrap();
+        assert!(below.starts_with("# ------------------------ >8 ------------------------\n"));
+        assert!(below.ends_with("diff --git a/a.txt b/a.txt\n"));
+    }
+
+    #[test]
+    fn test_split_on_scissors_absent() {
+        let content = "\n# Please enter the commit message.\n";
+        assert_eq!(split_on_scissors(content), (content.to_string(), None));
+    }
+
+    #[test]
+    fn test_insert_into_message_file_keeps_scissors_section() {
+        assert_eq!(
+            insert_into_message_file(SCISSORS_FILE, "feat: add a"),
+            "feat: add a\n\n# Please enter the commit message.\n# ------------------------ >8 ------------------------\n# Do not modify or remove the line above.\ndiff --git a/a.txt b/a.txt\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_into_message_file_empty() {
+        assert_eq!(insert_into_message_file("", "feat: add a"), "feat: add a\n");
+    }
+
+    // ============================================================
+    // fill_skeleton / read_fill_template のテスト
+    // ============================================================
+
+    #[test]
+    fn test_fill_skeleton_substitutes_summary() {
+        let skeleton = "[release] {{summary}}\n\nReviewed-by: QA team\n";
+        assert_eq!(
+            fill_skeleton(skeleton, "Bump version to 1.2.0"),
+            Some("[release] Bump version to 1.2.0\n\nReviewed-by: QA team".to_string())
+        );
+    }
+
+    #[test]
+    fn test_fill_skeleton_missing_placeholder() {
+        assert_eq!(fill_skeleton("[release] {summary}", "Bump version"), None);
+    }
+
+    #[test]
+    fn test_read_fill_template_rejects_missing_placeholder() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("skeleton.txt");
+        std::fs::write(&path, "[release] summary goes here\n").unwrap();
+        assert!(matches!(
+            App::read_fill_template(&path),
+            Err(AppError::InvalidFillTemplate(msg)) if msg.ends_with("no {{summary}} placeholder")
+        ));
+
+        std::fs::write(&path, "[release] {{summary}}\n").unwrap();
+        assert_eq!(
+            App::read_fill_template(&path).unwrap(),
+            "[release] {{summary}}\n"
+        );
+    }
+
+    // ============================================================
+    // --patch のテスト
+    // ============================================================
+
+    const SMALL_PATCH: &str = "From 1234567 Mon Sep 17 00:00:00 2001
+Subject: [PATCH] add greeting
+
+---
+diff --git a/hello.txt b/hello.txt
+--- a/hello.txt
++++ b/hello.txt
+@@ -1 +1,2 @@
+ hello
++world
+";
+
+    #[test]
+    fn test_read_patch_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("incoming.patch");
+        std::fs::write(&path, SMALL_PATCH).unwrap();
+
+        let diff = App::read_patch_file(&path).unwrap();
+        let filtered = GitService::with_repo_path(dir.path().to_path_buf())
+            .filter_external_diff(&diff)
+            .unwrap();
+        assert!(filtered.contains("+world"));
+    }
+
+    #[test]
+    fn test_read_patch_file_rejects_non_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, "release notes\n---\nnothing to see\n").unwrap();
+        assert!(matches!(
+            App::read_patch_file(&path),
+            Err(AppError::InvalidPatchFile(msg)) if msg.ends_with("not a unified diff")
+        ));
+
+        assert!(matches!(
+            App::read_patch_file(&dir.path().join("missing.patch")),
+            Err(AppError::InvalidPatchFile(_))
+        ));
+    }
+
+    #[rstest]
+    #[case("diff --git a/x b/x\n", true)]
+    #[case("--- a/x\n+++ b/x\n@@ -1 +1 @@\n", true)]
+    #[case("--- \nplain text\n", false)]
+    #[case("", false)]
+    fn test_looks_like_diff(#[case] content: &str, #[case] expected: bool) {
+        assert_eq!(looks_like_diff(content), expected);
+    }
+
+    // ============================================================
+    // render_footer / format_civil_date のテスト
+    // ============================================================
+
+    #[test]
+    fn test_render_footer_substitutes_placeholders() {
+        assert_eq!(
+            render_footer(
+                "Branch: {branch}\nRemote: {remote}\nDate: {date}\nGenerated-by: git-sc",
+                Some("feature/login"),
+                Some("git@github.com:owayo/git-smart-commit.git"),
+                "2024-05-01",
+            ),
+            "Branch: feature/login\nRemote: git@github.com:owayo/git-smart-commit.git\nDate: 2024-05-01\nGenerated-by: git-sc"
+        );
+    }
+
+    #[test]
+    fn test_render_footer_absent_branch_is_empty() {
+        assert_eq!(
+            render_footer("Branch: {branch}", None, None, "2024-05-01"),
+            "Branch: "
+        );
+    }
+
+    #[test]
+    fn test_append_trailers_skips_empty_footer_lines() {
+        let footer = render_footer("\n{remote}\n", None, None, "2024-05-01");
+        let trailers: Vec<String> = footer.lines().map(String::from).collect();
+        assert_eq!(append_trailers("feat: add", &trailers), "feat: add");
+    }
+
+    // ============================================================
+    // extract_trailers / reattach_trailers のテスト
+    // ============================================================
+
+    #[test]
+    fn test_extract_trailers_standard() {
+        let message = "fix: handle timeout\n\n- Retry once on timeout\n\nCo-authored-by: Bob <bob@example.com>\nSigned-off-by: Alice <alice@example.com>\n";
+        assert_eq!(
+            extract_trailers(message),
+            vec![
+                "Co-authored-by: Bob <bob@example.com>".to_string(),
+                "Signed-off-by: Alice <alice@example.com>".to_string(),
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case("fix: handle timeout")]
+    #[case("fix: handle timeout\n\n- Retry once on timeout")]
+    #[case("fix: handle timeout\n\nSigned-off-by: Alice <alice@example.com>\nnot a trailer")]
+    fn test_extract_trailers_none(#[case] message: &str) {
+        assert!(extract_trailers(message).is_empty());
+    }
+
+    #[test]
+    fn test_reattach_trailers() {
+        let preserved = vec!["Signed-off-by: Alice <alice@example.com>".to_string()];
+        assert_eq!(
+            reattach_trailers("feat: add login\n\n- Add form", &preserved),
+            "feat: add login\n\n- Add form\n\nSigned-off-by: Alice <alice@example.com>"
+        );
+        // 生成したメッセージに既に含まれる場合は重複させない
+        let regenerated = "feat: add login\n\nSigned-off-by: Alice <alice@example.com>";
+        assert_eq!(reattach_trailers(regenerated, &preserved), regenerated);
+        // トレーラーがなければメッセージはそのまま
+        assert_eq!(reattach_trailers("feat: add login", &[]), "feat: add login");
+    }
+
+    #[rstest]
+    #[case(0, "1970-01-01")]
+    #[case(19_844, "2024-05-01")]
+    #[case(11_016, "2000-02-29")]
+    fn test_format_civil_date(#[case] days: i64, #[case] expected: &str) {
+        assert_eq!(format_civil_date(days), expected);
+    }
+
+    // ============================================================
+    // format_per_commit_messages のテスト
+    // ============================================================
+
+    #[test]
+    fn test_format_per_commit_messages_two_hashes() {
+        let entries = vec![
+            ("abc1234def5678".to_string(), "feat: add login".to_string()),
+            (
+                "0123456789abcd".to_string(),
+                "fix: handle timeout".to_string(),
+            ),
+        ];
+        assert_eq!(
+            format_per_commit_messages(&entries),
+            "abc1234: feat: add login\n0123456: fix: handle timeout"
+        );
+    }
+
+    #[test]
+    fn test_format_per_commit_messages_with_body() {
+        let entries = vec![
+            (
+                "abc1234def5678".to_string(),
+                "feat: add login\n\n- add form".to_string(),
+            ),
+            (
+                "0123456789abcd".to_string(),
+                "fix: handle timeout".to_string(),
+            ),
+        ];
+        assert_eq!(
+            format_per_commit_messages(&entries),
+            "abc1234: feat: add login\n\n- add form\n\n0123456: fix: handle timeout"
+        );
+    }
+
+    // ============================================================
+    // is_conventional のテスト
+    // ============================================================
+
+    #[rstest]
+    #[case("feat: add login page", true)]
+    #[case("fix(auth): handle expired tokens", true)]
+    #[case("refactor!: drop legacy config", true)]
+    #[case("feat(api)!: remove v1 endpoints", true)]
+    #[case("Add login page", false)]
+    #[case("[feat] add login page", false)]
+    #[case(":sparkles: add login page", false)]
+    #[case("feat:add login page", false)]
+    #[case("Feat: add login page", false)]
+    #[case("feat: ", false)]
+    fn test_is_conventional(#[case] subject: &str, #[case] expected: bool) {
+        assert_eq!(is_conventional(subject), expected);
+    }
+
+    // ============================================================
+    // extract_issue_refs / issue_ref_footers のテスト
+    // ============================================================
+
+    #[test]
+    fn test_extract_issue_refs_unique_in_order() {
+        let diff = "\
+diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,2 +1,3 @@
+-// old workaround for #7
++// see #42 and #7
++// follow-up of #42
+ unchanged #99";
+        let pattern = Regex::new(DEFAULT_ISSUE_REF_PATTERN).unwrap();
+
+        // ブランチ名が先、削除行とコンテキスト行は対象外
+        assert_eq!(
+            extract_issue_refs(diff, Some("fix/#12-login"), &pattern, true),
+            vec!["#12", "#42", "#7"]
+        );
+        assert!(extract_issue_refs(diff, None, &Regex::new(r"GH-\d+").unwrap(), true).is_empty());
+    }
+
+    #[test]
+    fn test_extract_issue_refs_branch_only_by_default() {
+        let diff = "+// old workaround for #7";
+        let pattern = Regex::new(DEFAULT_ISSUE_REF_PATTERN).unwrap();
+        assert_eq!(
+            extract_issue_refs(diff, Some("fix/#12-login"), &pattern, false),
+            vec!["#12"]
+        );
+        assert!(extract_issue_refs(diff, None, &pattern, false).is_empty());
+    }
+
+    #[test]
+    fn test_extract_issue_refs_skips_color_codes_and_inner_hashes() {
+        let diff = "\
++.button { color: #000000; background: #ffffff; }
++.link { color: #123456; border-color: #00ff00; }
++let url = \"page#3\";
++fixes (#15)";
+        let pattern = Regex::new(DEFAULT_ISSUE_REF_PATTERN).unwrap();
+        assert_eq!(extract_issue_refs(diff, None, &pattern, true), vec!["#15"]);
+    }
+
+    #[test]
+    fn test_extract_issue_refs_custom_pattern() {
+        let pattern = Regex::new(r"GH-\d+").unwrap();
+        assert_eq!(
+            extract_issue_refs(
+                "+fix GH-45\n+GH-45 again",
+                Some("GH-3-topic"),
+                &pattern,
+                true
+            ),
+            vec!["GH-3", "GH-45"]
+        );
+    }
+
+    #[test]
+    fn test_issue_ref_footers() {
+        let refs = vec!["#12".to_string(), "#42".to_string()];
+        assert_eq!(
+            issue_ref_footers(&refs, DEFAULT_ISSUE_REF_KEYWORD),
+            vec!["Closes #12", "Closes #42"]
+        );
+        assert_eq!(
+            issue_ref_footers(&refs, "Refs"),
+            vec!["Refs #12", "Refs #42"]
+        );
+        assert_eq!(
+            append_trailers("fix: login", &issue_ref_footers(&refs, "Fixes")),
+            "fix: login\n\nFixes #12\nFixes #42"
+        );
+    }
+
+    // ============================================================
+    // committed_with のテスト
+    // ============================================================
+
+    #[rstest]
+    #[case(AiProvider::Gemini, "Committed with Gemini CLI")]
+    #[case(AiProvider::Codex, "Committed with Codex CLI")]
+    #[case(AiProvider::Claude, "Committed with Claude Code")]
+    fn test_committed_with(#[case] provider: AiProvider, #[case] expected: &str) {
+        assert_eq!(committed_with(provider), expected);
+    }
+
+    // ============================================================
+    // format_provider_entry のテスト
+    // ============================================================
+
+    #[rstest]
+    #[case(true, None, "1. Gemini CLI (installed)")]
+    #[case(false, None, "1. Gemini CLI (not installed)")]
+    #[case(true, Some(1500), "1. Gemini CLI (installed, demoted for 25m)")]
+    #[case(true, Some(61), "1. Gemini CLI (installed, demoted for 2m)")]
+    fn test_format_provider_entry(
+        #[case] installed: bool,
+        #[case] demoted_secs: Option<u64>,
+        #[case] expected: &str,
+    ) {
+        let entry = ProviderListEntry {
+            name: "Gemini CLI",
+            installed,
+            demoted_secs,
+        };
+        assert_eq!(format_provider_entry(1, &entry), expected);
+    }
+
+    // ============================================================
+    // coauthor_trailer / build_trailers のテスト
+    // ============================================================
+
+    #[rstest]
+    #[case(
+        "Alice <alice@example.com>",
+        "Co-authored-by: Alice <alice@example.com>"
+    )]
+    #[case(
+        "  Bob Smith   <bob@example.com> ",
+        "Co-authored-by: Bob Smith <bob@example.com>"
+    )]
+    fn test_coauthor_trailer(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(coauthor_trailer(input).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case("Alice alice@example.com")]
+    #[case("Alice")]
+    #[case("<alice@example.com>")]
+    #[case("Alice <alice>")]
+    #[case("Alice <alice@example.com")]
+    fn test_coauthor_trailer_rejects_malformed(#[case] input: &str) {
+        assert!(matches!(
+            coauthor_trailer(input),
+            Err(AppError::InvalidCoauthor(v)) if v == input
+        ));
+    }
+
+    #[rstest]
+    #[case("Alice <alice@example.com>", "Alice <alice@example.com>")]
+    #[case("  Bob Smith<bob@example.com> ", "Bob Smith <bob@example.com>")]
+    fn test_validate_author(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(validate_author(input).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case("alice@example.com")]
+    #[case("Alice")]
+    #[case("Alice <alice>")]
+    #[case("<alice@example.com>")]
+    fn test_validate_author_rejects_malformed(#[case] input: &str) {
+        assert!(matches!(
+            validate_author(input),
+            Err(AppError::InvalidAuthor(v)) if v == input
+        ));
+    }
+
+    #[test]
+    fn test_build_trailers_multiple_coauthors_after_append() {
+        let trailers = build_trailers(
+            &["Refs: #12".to_string()],
+            &[
+                "Alice <alice@example.com>".to_string(),
+                "Bob <bob@example.com>".to_string(),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            append_trailers("feat: pair on parser", &trailers),
+            "feat: pair on parser\n\nRefs: #12\nCo-authored-by: Alice <alice@example.com>\nCo-authored-by: Bob <bob@example.com>"
+        );
+    }
+
+    #[test]
+    fn test_build_trailers_single_coauthor() {
+        let trailers = build_trailers(&[], &["Alice <alice@example.com>".to_string()]).unwrap();
+        assert_eq!(
+            append_trailers("fix: typo\n\n- body line", &trailers),
+            "fix: typo\n\n- body line\n\nCo-authored-by: Alice <alice@example.com>"
+        );
+    }
+
+    // ============================================================
+    // append_trailers のテスト
+    // ============================================================
+
+    #[test]
+    fn test_append_trailers_single_line_to_subject_only() {
+        let result = append_trailers(
+            "feat: add login",
+            &["Reviewed-by: Alice <alice@example.com>".to_string()],
+        );
+        assert_eq!(
+            result,
+            "feat: add login\n\nReviewed-by: Alice <alice@example.com>"
+        );
+    }
+
+    #[test]
+    fn test_append_trailers_multiple() {
+        let result = append_trailers(
+            "feat: add login",
+            &[
+                "Reviewed-by: Alice <alice@example.com>".to_string(),
+                "Refs: https://example.com/issues/1".to_string(),
+            ],
+        );
+        assert_eq!(
+            result,
+            "feat: add login\n\nReviewed-by: Alice <alice@example.com>\nRefs: https://example.com/issues/1"
+        );
+    }
+
+    #[test]
+    fn test_append_trailers_with_body_no_double_blank_line() {
+        let message = "feat: add login\n\n- add form\n- add validation\n\n";
+        let result = append_trailers(message, &["Refs: #12".to_string()]);
+        assert_eq!(
+            result,
+            "feat: add login\n\n- add form\n- add validation\n\nRefs: #12"
+        );
+    }
+
+    #[test]
+    fn test_append_trailers_empty() {
+        assert_eq!(append_trailers("feat: add login", &[]), "feat: add login");
+        assert_eq!(
+            append_trailers("feat: add login", &["  ".to_string()]),
+            "feat: add login"
+        );
+    }
+
+    // ============================================================
+    // select_blocks_to_summarize / build_chunked_diff のテスト
+    // ============================================================
+
+    fn block(path: &str, size: usize) -> DiffBlock {
+        DiffBlock {
+            path: path.to_string(),
+            content: "x".repeat(size),
+        }
+    }
+
+    #[test]
+    fn test_select_blocks_to_summarize_within_limit() {
+        let blocks = vec![block("a.rs", 10), block("b.rs", 10)];
+        assert!(select_blocks_to_summarize(&blocks, 100, 5).is_empty());
+    }
+
+    #[test]
+    fn test_select_blocks_to_summarize_largest_cut_first() {
+        // a.rs は制限内、b.rs は制限をまたぎ、c.rs/d.rs は完全に切り捨てられる
+        let blocks = vec![
+            block("a.rs", 40),
+            block("b.rs", 30),
+            block("c.rs", 10),
+            block("d.rs", 50),
+        ];
+        assert_eq!(select_blocks_to_summarize(&blocks, 50, 5), vec![3, 1, 2]);
+    }
+
+    #[test]
+    fn test_select_blocks_to_summarize_respects_max_files() {
+        let blocks = vec![block("a.rs", 100), block("b.rs", 30), block("c.rs", 60)];
+        assert_eq!(select_blocks_to_summarize(&blocks, 10, 2), vec![0, 2]);
+    }
+
+    #[test]
+    fn test_build_chunked_diff() {
+        let summaries = vec![
+            ("src/big.rs".to_string(), "Rewrite parser".to_string()),
+            ("src/huge.rs".to_string(), "Add tables".to_string()),
+        ];
+        let result = build_chunked_diff(&summaries, "diff --git a/x b/x\n+x");
+        assert_eq!(
+            result,
+            "# Summaries of large files that are cut off below:\n# src/big.rs: Rewrite parser\n# src/huge.rs: Add tables\n\ndiff --git a/x b/x\n+x"
+        );
+    }
+
+    // ============================================================
+    // resolve_template_prefix_type のテスト
+    // ============================================================
+
+    #[rstest]
+    #[case("gitmoji", "emoji", None, "emoji")]
+    #[case("gitmoji", "emoji", Some("emoji"), "emoji")]
+    #[case("angular", "conventional", Some("conventional"), "conventional")]
+    fn test_resolve_template_prefix_type(
+        #[case] template: &str,
+        #[case] preset: &str,
+        #[case] configured: Option<&str>,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(
+            resolve_template_prefix_type(template, preset, configured).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_resolve_template_prefix_type_conflict() {
+        let result = resolve_template_prefix_type("gitmoji", "emoji", Some("bracket"));
+        assert!(matches!(
+            result,
+            Err(AppError::TemplateConflict(t, p)) if t == "gitmoji" && p == "bracket"
+        ));
+    }
+
+    // ============================================================
+    // fixup_message のテスト
+    // ============================================================
+
+    #[rstest]
+    #[case("feat: add login", "fixup! feat: add login")]
+    #[case("  fix: trim spaces  ", "fixup! fix: trim spaces")]
+    #[case("ログイン機能を追加", "fixup! ログイン機能を追加")]
+    fn test_fixup_message(#[case] subject: &str, #[case] expected: &str) {
+        assert_eq!(fixup_message(subject), expected);
+    }
+
+    // ============================================================
+    // read_confirmation のテスト
+    // ============================================================
+
+    #[rstest]
+    #[case("\n", true)]
+    #[case("y\n", true)]
+    #[case("Y\n", true)]
+    #[case("yes\n", true)]
+    #[case("  YES  \n", true)]
+    #[case("n\n", false)]
+    #[case("no\n", false)]
+    #[case("maybe\n", false)]
+    fn test_read_confirmation(#[case] input: &str, #[case] expected: bool) {
+        let mut reader = io::Cursor::new(input.as_bytes());
+        assert_eq!(read_confirmation(&mut reader).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_read_confirmation_eof_is_cancel() {
+        let mut reader = io::Cursor::new(&b""[..]);
+        assert!(!read_confirmation(&mut reader).unwrap());
+    }
+
+    #[rstest]
+    #[case("y\n", true)]
+    #[case("YES\n", true)]
+    #[case("\n", false)]
+    #[case("n\n", false)]
+    #[case("", false)]
+    fn test_read_explicit_confirmation(#[case] input: &str, #[case] expected: bool) {
+        let mut reader = io::Cursor::new(input.as_bytes());
+        assert_eq!(read_explicit_confirmation(&mut reader).unwrap(), expected);
+    }
+
     // ============================================================
     // is_valid_prefix_type のテスト
     // ============================================================
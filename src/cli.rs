@@ -1,4 +1,10 @@
-use clap::Parser;
+use std::path::PathBuf;
+
+use clap::builder::RangedU64ValueParser;
+use clap::{Parser, Subcommand};
+
+/// 件名の最大文字数として指定できる最小値（これより短いと件名が失われる）
+pub const MIN_SUBJECT_LENGTH: usize = 10;
 
 /// AI-powered smart commit message generator using coding agents (Gemini CLI, Codex CLI, or Claude Code)
 #[derive(Parser, Debug)]
@@ -12,41 +18,321 @@ pub struct Cli {
     #[arg(short = 'y', long = "yes")]
     pub auto_confirm: bool,
 
-    /// Show generated message without committing
-    #[arg(short = 'n', long = "dry-run")]
+    /// Show generated message without committing (cannot be combined with --yes)
+    #[arg(short = 'n', long = "dry-run", conflicts_with = "auto_confirm")]
     pub dry_run: bool,
 
     /// Stage all changes including unstaged and commit
     #[arg(short = 'a', long = "all")]
     pub stage_all: bool,
 
+    /// Choose hunks to stage with `git add -p` before generating
+    #[arg(
+        short = 'i',
+        long = "interactive",
+        conflicts_with_all = ["stage_all", "amend", "amend_staged", "amend_all", "amend_at", "regen_body", "squash", "squash_upstream", "squash_last", "reword", "fixup", "generate_for", "patch", "undo"]
+    )]
+    pub interactive: bool,
+
     /// Amend the last commit with a newly generated message
     #[arg(long = "amend")]
     pub amend: bool,
 
+    /// Fold staged changes into the last commit and regenerate its message from the combined diff
+    #[arg(
+        long = "amend-staged",
+        conflicts_with_all = ["amend", "squash", "reword", "fixup", "pathspec", "offline", "generate_for"]
+    )]
+    pub amend_staged: bool,
+
+    /// Stage all changes, fold them into the last commit and regenerate its message
+    #[arg(
+        long = "amend-all",
+        conflicts_with_all = ["amend", "amend_staged", "squash", "reword", "fixup", "pathspec", "offline", "generate_for", "message_file", "allow_empty", "stat_only"]
+    )]
+    pub amend_all: bool,
+
+    /// Amend even if the last commit is already on the upstream (skips the confirmation)
+    #[arg(long = "force-amend")]
+    pub force_amend: bool,
+
+    /// Fold staged changes into an earlier commit and regenerate its message (rebase)
+    #[arg(
+        long = "amend-at",
+        value_name = "HASH",
+        conflicts_with_all = ["amend", "amend_staged", "amend_all", "squash", "squash_upstream", "reword", "fixup", "pathspec", "offline", "generate_for", "allow_empty", "message_file", "fill", "undo", "print_prompt", "stat_only"]
+    )]
+    pub amend_at: Option<String>,
+
+    /// Keep the last commit's subject and regenerate only its body
+    #[arg(
+        long = "regen-body",
+        conflicts_with_all = ["amend", "amend_staged", "amend_all", "amend_at", "squash", "reword", "fixup", "pathspec", "offline", "generate_for"]
+    )]
+    pub regen_body: bool,
+
+    /// With --amend, keep the last commit's subject and regenerate only its body (same as --regen-body)
+    #[arg(
+        long = "keep-subject",
+        requires = "amend",
+        conflicts_with_all = ["subject_only", "fill", "message_file"]
+    )]
+    pub keep_subject: bool,
+
+    /// Keep the original commit date when amending (--amend, --amend-staged, --regen-body)
+    #[arg(long = "keep-date")]
+    pub keep_date: bool,
+
     /// Squash all commits in branch into one with a new message (specify base branch)
     #[arg(long = "squash", value_name = "BASE")]
     pub squash: Option<String>,
 
+    /// Squash against the upstream (tracking branch) of the current branch
+    #[arg(
+        long = "squash-upstream",
+        conflicts_with_all = ["squash", "amend", "amend_staged", "amend_all", "regen_body", "reword", "fixup", "pathspec", "offline", "generate_for", "allow_empty", "message_file", "undo", "stat_only"]
+    )]
+    pub squash_upstream: bool,
+
+    /// Squash only the last N commits into one with a new message (no merge commits allowed)
+    #[arg(
+        long = "squash-last",
+        value_name = "N",
+        conflicts_with_all = ["squash", "squash_upstream", "amend", "amend_staged", "amend_all", "amend_at", "regen_body", "reword", "fixup", "pathspec", "offline", "generate_for", "allow_empty", "message_file", "undo", "stat_only", "fill"]
+    )]
+    pub squash_last: Option<usize>,
+
     /// Regenerate commit message for specified commit hash (uses git rebase)
     #[arg(long = "reword", value_name = "HASH")]
     pub reword: Option<String>,
 
+    /// Create a "fixup!" commit of staged changes for the specified commit (no AI call)
+    #[arg(
+        long = "fixup",
+        value_name = "HASH",
+        conflicts_with_all = ["amend", "squash", "reword", "generate_for"]
+    )]
+    pub fixup: Option<String>,
+
+    /// Limit the message and the commit to the given paths (multiple allowed)
+    #[arg(
+        long = "pathspec",
+        value_name = "GLOB",
+        num_args = 1..,
+        conflicts_with_all = ["amend", "squash", "reword", "fixup", "generate_for"]
+    )]
+    pub pathspec: Vec<String>,
+
+    /// Generate a basic message from the diffstat without calling any AI provider
+    #[arg(
+        long = "offline",
+        conflicts_with_all = ["amend", "squash", "reword", "fixup", "generate_for"]
+    )]
+    pub offline: bool,
+
+    /// Send only file names and change sizes to the AI provider (never the code)
+    #[arg(
+        long = "stat-only",
+        conflicts_with_all = ["amend", "amend_staged", "amend_all", "regen_body", "squash", "reword", "fixup", "offline", "generate_for"]
+    )]
+    pub stat_only: bool,
+
     /// Generate message from diff of specified commit hash(es) (output only, multiple allowed)
     #[arg(short = 'g', long = "generate-for", value_name = "HASH", num_args = 1..)]
     pub generate_for: Option<Vec<String>>,
 
+    /// With --generate-for, generate one message per commit (printed as "hash: message")
+    #[arg(long = "per-commit", requires = "generate_for")]
+    pub per_commit: bool,
+
+    /// With --generate-for, order the commits oldest to newest instead of argument order
+    #[arg(long = "chronological", requires = "generate_for")]
+    pub chronological: bool,
+
     /// Generate commit message with body
     #[arg(short = 'b', long = "body")]
     pub with_body: bool,
 
+    /// Keep only the subject line even if the AI adds a body
+    #[arg(long = "subject-only", conflicts_with_all = ["with_body", "regen_body"])]
+    pub subject_only: bool,
+
+    /// Body format used with --body
+    #[arg(long = "body-style", value_name = "STYLE", value_parser = ["bullets", "paragraph"])]
+    pub body_style: Option<String>,
+
     /// Language for commit message (overrides config file)
     #[arg(short = 'l', long = "lang")]
     pub language: Option<String>,
 
+    /// Style preset that sets the prefix type and extra prompt rules
+    #[arg(long = "template", value_name = "NAME", value_parser = ["conventional", "gitmoji", "angular"])]
+    pub template: Option<String>,
+
+    /// Append a trailer line to the generated message (can be repeated)
+    #[arg(long = "append", value_name = "TEXT")]
+    pub append: Vec<String>,
+
+    /// Set the commit author, formatted as "Name <email>" (passed to git commit --author)
+    #[arg(long = "author", value_name = "NAME <EMAIL>")]
+    pub author: Option<String>,
+
+    /// Add a Co-authored-by trailer, formatted as "Name <email>" (can be repeated)
+    #[arg(long = "coauthor", value_name = "NAME <EMAIL>")]
+    pub coauthor: Vec<String>,
+
+    /// Extra context for the AI, e.g. why the change was made (can be repeated)
+    #[arg(long = "context", value_name = "TEXT")]
+    pub context: Vec<String>,
+
+    /// Use the clipboard text as extra context for the AI (same as --context)
+    #[cfg(feature = "clipboard")]
+    #[arg(long = "from-clipboard")]
+    pub from_clipboard: bool,
+
+    /// Include whitespace-only changes in the diff sent to AI
+    #[arg(long = "include-whitespace")]
+    pub include_whitespace: bool,
+
+    /// Diff algorithm used for the diff sent to AI (overrides config file)
+    #[arg(long = "diff-algorithm", value_name = "ALGO", value_parser = ["myers", "minimal", "patience", "histogram"])]
+    pub diff_algorithm: Option<String>,
+
+    /// Send the diff without binary/ignore/whitespace filtering (secrets are still redacted, length still capped)
+    #[arg(long = "no-filter")]
+    pub no_filter: bool,
+
+    /// Truncate the subject line to at most N characters (N >= 10; overrides config file)
+    #[arg(
+        long = "max-subject",
+        value_name = "N",
+        value_parser = RangedU64ValueParser::<usize>::new().range(MIN_SUBJECT_LENGTH as u64..)
+    )]
+    pub max_subject: Option<usize>,
+
+    /// Wrap body lines at N columns, keeping bullet indentation (0 disables; overrides config file)
+    #[arg(long = "wrap", value_name = "N")]
+    pub wrap: Option<usize>,
+
+    /// Fail instead of committing when the subject is not a Conventional Commits subject
+    #[arg(long = "force-conventional")]
+    pub force_conventional: bool,
+
+    /// Include full recent commit messages (not just subjects) as style reference
+    #[arg(long = "recent-bodies")]
+    pub recent_bodies: bool,
+
+    /// Include the current branch name in the prompt as extra context
+    #[arg(long = "use-branch-context")]
+    pub use_branch_context: bool,
+
+    /// Number of recent commits shown to AI as style reference (overrides config)
+    #[arg(long = "ref-commits", value_name = "N")]
+    pub ref_commits: Option<usize>,
+
+    /// Do not show recent commits to AI as style reference (Auto mode uses Conventional Commits)
+    #[arg(long = "no-recent-commits")]
+    pub no_recent_commits: bool,
+
+    /// Ignore global and project config files (use defaults and command-line options only)
+    #[arg(long = "no-config")]
+    pub no_config: bool,
+
+    /// Cycle through the provider list up to N times before giving up (overrides config file)
+    #[arg(long = "retry-providers", value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub retry_providers: Option<u32>,
+
+    /// Create a commit even when nothing is staged (message describes intent from recent commits)
+    #[arg(
+        long = "allow-empty",
+        conflicts_with_all = ["amend", "amend_staged", "regen_body", "squash", "reword", "fixup", "generate_for"]
+    )]
+    pub allow_empty: bool,
+
+    /// Exit with an error when there is nothing to commit, also with --all
+    #[arg(long = "fail-on-empty", conflicts_with = "allow_empty")]
+    pub fail_on_empty: bool,
+
+    /// Write the message into a commit message file instead of committing (for prepare-commit-msg hooks)
+    #[arg(
+        long = "message-file",
+        value_name = "PATH",
+        conflicts_with_all = ["amend", "amend_staged", "regen_body", "squash", "reword", "fixup", "generate_for"]
+    )]
+    pub message_file: Option<PathBuf>,
+
+    /// Fill a fixed message skeleton: only the {{summary}} placeholder is generated by AI
+    #[arg(
+        long = "fill",
+        value_name = "FILE",
+        conflicts_with_all = ["with_body", "amend", "amend_staged", "amend_all", "regen_body", "squash", "squash_upstream", "reword", "fixup", "generate_for", "undo"]
+    )]
+    pub fill: Option<PathBuf>,
+
+    /// Generate a message from a unified diff file (.patch / .diff) and print it (output only)
+    #[arg(
+        long = "patch",
+        value_name = "FILE",
+        conflicts_with_all = ["amend", "amend_staged", "amend_all", "amend_at", "regen_body", "squash", "squash_upstream", "squash_last", "reword", "fixup", "pathspec", "offline", "stat_only", "generate_for", "stage_all", "allow_empty", "message_file", "fill", "undo", "print_prompt"]
+    )]
+    pub patch: Option<PathBuf>,
+
+    /// Undo the last amend, squash or reword by resetting to the previous HEAD
+    #[arg(
+        long = "undo",
+        conflicts_with_all = ["amend", "amend_staged", "amend_all", "regen_body", "squash", "reword", "fixup", "generate_for", "print_prompt"]
+    )]
+    pub undo: bool,
+
+    /// List providers in the order they will be tried, with install and cooldown status
+    #[arg(long = "list-providers")]
+    pub list_providers: bool,
+
+    /// Print why the prefix mode and provider were chosen (remote, branch, provider order, truncation) to stderr
+    #[arg(long = "explain")]
+    pub explain: bool,
+
+    /// Print the prompt that would be sent to AI and exit (no AI call, no commit)
+    #[arg(long = "print-prompt", conflicts_with_all = ["fixup", "offline"])]
+    pub print_prompt: bool,
+
     /// Debug mode (show prompt sent to AI)
     #[arg(short = 'd', long = "debug")]
     pub debug: bool,
+
+    /// Print the filtered diff sent to the AI to stderr before generating
+    #[arg(long = "print-diff")]
+    pub print_diff: bool,
+
+    /// Print elapsed time of each phase to stderr
+    #[arg(long = "verbose")]
+    pub verbose: bool,
+
+    /// Show the provider output as it arrives
+    #[arg(long = "stream")]
+    pub stream: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// サブコマンド
+#[derive(Subcommand, Debug, PartialEq)]
+pub enum Command {
+    /// Diagnose the environment (git, AI providers, config and state files)
+    Doctor,
+    /// Show the state file: last successful provider and generations per provider
+    State,
+    /// Set up the `git sc` alias (alias.sc = !git-sc) in git config
+    InstallAlias {
+        /// Write the alias to the repository config instead of the global one
+        #[arg(long = "local")]
+        local: bool,
+
+        /// Overwrite an existing alias.sc with a different value
+        #[arg(long = "force")]
+        force: bool,
+    },
 }
 
 #[cfg(test)]
@@ -63,13 +349,61 @@ mod tests {
         assert!(!cli.auto_confirm);
         assert!(!cli.dry_run);
         assert!(!cli.stage_all);
+        assert!(!cli.interactive);
+        assert!(!cli.fail_on_empty);
+        assert!(!cli.force_amend);
+        assert!(!cli.print_diff);
         assert!(!cli.amend);
+        assert!(!cli.amend_staged);
+        assert!(!cli.amend_all);
+        assert!(!cli.regen_body);
+        assert!(!cli.keep_date);
+        assert!(!cli.stat_only);
+        assert!(!cli.print_prompt);
+        assert!(!cli.list_providers);
+        assert!(!cli.undo);
         assert!(cli.squash.is_none());
+        assert!(!cli.keep_subject);
+        assert!(!cli.squash_upstream);
+        assert!(cli.squash_last.is_none());
         assert!(cli.reword.is_none());
+        assert!(cli.fixup.is_none());
+        assert!(cli.pathspec.is_empty());
+        assert!(!cli.offline);
         assert!(cli.generate_for.is_none());
+        assert!(!cli.per_commit);
+        assert!(!cli.chronological);
         assert!(!cli.with_body);
+        assert!(!cli.subject_only);
         assert!(cli.language.is_none());
+        assert!(cli.max_subject.is_none());
+        assert!(cli.wrap.is_none());
+        assert!(!cli.force_conventional);
+        assert!(!cli.recent_bodies);
+        assert!(!cli.use_branch_context);
+        assert!(cli.ref_commits.is_none());
+        assert!(!cli.no_recent_commits);
+        assert!(!cli.no_config);
+        assert!(cli.retry_providers.is_none());
+        assert!(!cli.allow_empty);
+        assert!(cli.message_file.is_none());
+        assert!(cli.fill.is_none());
+        assert!(cli.patch.is_none());
+        assert!(cli.amend_at.is_none());
+        assert!(!cli.explain);
+        assert!(cli.append.is_empty());
+        assert!(cli.coauthor.is_empty());
+        assert!(cli.author.is_none());
+        assert!(cli.context.is_empty());
+        assert!(cli.template.is_none());
+        assert!(cli.body_style.is_none());
+        assert!(!cli.include_whitespace);
+        assert!(cli.diff_algorithm.is_none());
+        assert!(!cli.no_filter);
         assert!(!cli.debug);
+        assert!(!cli.verbose);
+        assert!(!cli.stream);
+        assert_eq!(cli.command, None);
     }
 
     #[test]
@@ -96,6 +430,26 @@ mod tests {
         assert!(cli.dry_run);
     }
 
+    #[test]
+    fn test_cli_dry_run_conflicts_with_yes() {
+        // --yes は --dry-run では意味を持たないため、どのモードでも組み合わせをエラーにする
+        for args in [
+            vec!["git-sc", "--dry-run", "--yes"],
+            vec!["git-sc", "-n", "-y"],
+            vec!["git-sc", "--amend", "-n", "-y"],
+            vec!["git-sc", "--squash", "main", "-n", "-y"],
+            vec!["git-sc", "--reword", "abc123", "-n", "-y"],
+        ] {
+            let err = Cli::try_parse_from(&args).unwrap_err();
+            assert_eq!(
+                err.kind(),
+                clap::error::ErrorKind::ArgumentConflict,
+                "{:?}",
+                args
+            );
+        }
+    }
+
     #[test]
     fn test_cli_stage_all_short() {
         let cli = Cli::parse_from(["git-sc", "-a"]);
@@ -120,6 +474,29 @@ mod tests {
         assert_eq!(cli.squash, Some("origin/main".to_string()));
     }
 
+    #[test]
+    fn test_cli_squash_upstream() {
+        let cli = Cli::parse_from(["git-sc", "--squash-upstream", "-y"]);
+        assert!(cli.squash_upstream);
+        assert!(cli.squash.is_none());
+        assert!(Cli::try_parse_from(["git-sc", "--squash-upstream", "--squash", "main"]).is_err());
+        assert!(Cli::try_parse_from(["git-sc", "--squash-upstream", "--stat-only"]).is_err());
+        assert!(Cli::try_parse_from(["git-sc", "--squash-upstream", "src/"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_squash_last() {
+        let cli = Cli::parse_from(["git-sc", "--squash-last", "3", "-y"]);
+        assert_eq!(cli.squash_last, Some(3));
+        assert!(Cli::try_parse_from(["git-sc", "--squash-last", "3", "--squash", "main"]).is_err());
+        assert!(
+            Cli::try_parse_from(["git-sc", "--squash-last", "3", "--squash-upstream"]).is_err()
+        );
+        assert!(Cli::try_parse_from(["git-sc", "--squash-last", "many"]).is_err());
+        assert!(Cli::try_parse_from(["git-sc", "--squash-last", "3", "--stat-only"]).is_err());
+        assert!(Cli::try_parse_from(["git-sc", "--squash-last", "3", "--fill", "t.txt"]).is_err());
+    }
+
     #[test]
     fn test_cli_squash_with_feature_branch() {
         let cli = Cli::parse_from(["git-sc", "--squash", "origin/feature/test"]);
@@ -160,6 +537,206 @@ mod tests {
         assert!(cli.dry_run);
     }
 
+    #[test]
+    fn test_cli_doctor_subcommand() {
+        let cli = Cli::parse_from(["git-sc", "doctor"]);
+        assert_eq!(cli.command, Some(Command::Doctor));
+    }
+
+    #[test]
+    fn test_cli_state_subcommand() {
+        let cli = Cli::parse_from(["git-sc", "state"]);
+        assert_eq!(cli.command, Some(Command::State));
+    }
+
+    #[test]
+    fn test_cli_install_alias_subcommand() {
+        let cli = Cli::parse_from(["git-sc", "install-alias"]);
+        assert_eq!(
+            cli.command,
+            Some(Command::InstallAlias {
+                local: false,
+                force: false
+            })
+        );
+
+        let cli = Cli::parse_from(["git-sc", "install-alias", "--local", "--force"]);
+        assert_eq!(
+            cli.command,
+            Some(Command::InstallAlias {
+                local: true,
+                force: true
+            })
+        );
+    }
+
+    #[test]
+    fn test_cli_print_diff() {
+        let cli = Cli::parse_from(["git-sc", "--print-diff", "-n"]);
+        assert!(cli.print_diff);
+        assert!(cli.dry_run);
+    }
+
+    #[test]
+    fn test_cli_force_amend() {
+        let cli = Cli::parse_from(["git-sc", "--amend", "--force-amend", "-y"]);
+        assert!(cli.amend);
+        assert!(cli.force_amend);
+        assert!(cli.auto_confirm);
+    }
+
+    #[test]
+    fn test_cli_fail_on_empty() {
+        let cli = Cli::parse_from(["git-sc", "-a", "--fail-on-empty"]);
+        assert!(cli.fail_on_empty);
+        assert!(cli.stage_all);
+
+        let result = Cli::try_parse_from(["git-sc", "--fail-on-empty", "--allow-empty"]);
+        assert_eq!(
+            result.unwrap_err().kind(),
+            clap::error::ErrorKind::ArgumentConflict
+        );
+    }
+
+    #[test]
+    fn test_cli_interactive() {
+        let cli = Cli::parse_from(["git-sc", "-i", "-n"]);
+        assert!(cli.interactive);
+        assert!(cli.dry_run);
+        assert!(Cli::parse_from(["git-sc", "--interactive"]).interactive);
+    }
+
+    #[test]
+    fn test_cli_interactive_conflicts_with_stage_all() {
+        let result = Cli::try_parse_from(["git-sc", "-i", "-a"]);
+        assert_eq!(
+            result.unwrap_err().kind(),
+            clap::error::ErrorKind::ArgumentConflict
+        );
+    }
+
+    #[test]
+    fn test_cli_interactive_conflicts_with_fixup() {
+        let result = Cli::try_parse_from(["git-sc", "-i", "--fixup", "abc123"]);
+        assert_eq!(
+            result.unwrap_err().kind(),
+            clap::error::ErrorKind::ArgumentConflict
+        );
+    }
+
+    #[test]
+    fn test_cli_amend_staged() {
+        let cli = Cli::parse_from(["git-sc", "--amend-staged", "-a"]);
+        assert!(cli.amend_staged);
+        assert!(cli.stage_all);
+        assert!(!cli.amend);
+    }
+
+    #[test]
+    fn test_cli_amend_staged_conflicts_with_amend() {
+        let result = Cli::try_parse_from(["git-sc", "--amend-staged", "--amend"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_cli_amend_all() {
+        let cli = Cli::parse_from(["git-sc", "--amend-all", "-n"]);
+        assert!(cli.amend_all);
+        assert!(cli.dry_run);
+        assert!(!cli.amend);
+    }
+
+    #[test]
+    fn test_cli_amend_all_conflicts_with_squash() {
+        let result = Cli::try_parse_from(["git-sc", "--amend-all", "--squash", "main"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_cli_amend_all_conflicts_with_hook_options() {
+        // フックから呼ばれた場合に直前のコミットを書き換えないよう拒否する
+        for args in [
+            [
+                "git-sc",
+                "--amend-all",
+                "--message-file",
+                ".git/COMMIT_EDITMSG",
+            ]
+            .as_slice(),
+            ["git-sc", "--amend-all", "--allow-empty"].as_slice(),
+            ["git-sc", "--amend-all", "--stat-only"].as_slice(),
+        ] {
+            assert_eq!(
+                Cli::try_parse_from(args).unwrap_err().kind(),
+                clap::error::ErrorKind::ArgumentConflict,
+                "{:?}",
+                args
+            );
+        }
+    }
+
+    #[test]
+    fn test_cli_print_prompt_with_modes() {
+        let cli = Cli::parse_from(["git-sc", "--print-prompt", "-b", "-l", "English"]);
+        assert!(cli.print_prompt);
+        assert!(cli.with_body);
+
+        let cli = Cli::parse_from(["git-sc", "--print-prompt", "--squash", "main"]);
+        assert!(cli.print_prompt);
+        assert_eq!(cli.squash, Some("main".to_string()));
+
+        assert!(Cli::try_parse_from(["git-sc", "--print-prompt", "--offline"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_undo() {
+        let cli = Cli::parse_from(["git-sc", "--undo", "-y"]);
+        assert!(cli.undo);
+        assert!(cli.auto_confirm);
+        assert!(Cli::try_parse_from(["git-sc", "--undo", "--amend"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_list_providers() {
+        let cli = Cli::parse_from(["git-sc", "--list-providers"]);
+        assert!(cli.list_providers);
+    }
+
+    #[test]
+    fn test_cli_stat_only() {
+        let cli = Cli::parse_from(["git-sc", "--stat-only", "-a"]);
+        assert!(cli.stat_only);
+        assert!(Cli::try_parse_from(["git-sc", "--stat-only", "--offline"]).is_err());
+        // 作業ツリー全体の差分を送る --amend-all とは併用できない
+        assert!(Cli::try_parse_from(["git-sc", "--stat-only", "--amend-all"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_keep_date() {
+        let cli = Cli::parse_from(["git-sc", "--amend", "--keep-date"]);
+        assert!(cli.amend);
+        assert!(cli.keep_date);
+    }
+
+    #[test]
+    fn test_cli_regen_body() {
+        let cli = Cli::parse_from(["git-sc", "--regen-body", "-y"]);
+        assert!(cli.regen_body);
+        assert!(Cli::try_parse_from(["git-sc", "--regen-body", "--amend"]).is_err());
+        assert!(Cli::try_parse_from(["git-sc", "--regen-body", "--amend-at", "abc123"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_keep_subject() {
+        let cli = Cli::parse_from(["git-sc", "--amend", "--keep-subject", "-b"]);
+        assert!(cli.keep_subject);
+        assert!(cli.amend);
+        assert!(Cli::try_parse_from(["git-sc", "--keep-subject"]).is_err());
+        assert!(
+            Cli::try_parse_from(["git-sc", "--amend", "--keep-subject", "--subject-only"]).is_err()
+        );
+    }
+
     #[test]
     fn test_cli_amend_with_options() {
         let cli = Cli::parse_from(["git-sc", "--amend", "-y", "-l", "English"]);
@@ -187,6 +764,20 @@ mod tests {
         assert!(cli.dry_run);
     }
 
+    #[test]
+    fn test_cli_verbose() {
+        let cli = Cli::parse_from(["git-sc", "--verbose", "-n"]);
+        assert!(cli.verbose);
+        assert!(cli.dry_run);
+    }
+
+    #[test]
+    fn test_cli_stream() {
+        let cli = Cli::parse_from(["git-sc", "--stream", "-b"]);
+        assert!(cli.stream);
+        assert!(cli.with_body);
+    }
+
     #[test]
     fn test_cli_reword() {
         let cli = Cli::parse_from(["git-sc", "--reword", "abc1234"]);
@@ -220,6 +811,298 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_cli_subject_only() {
+        let cli = Cli::parse_from(["git-sc", "--subject-only"]);
+        assert!(cli.subject_only);
+        assert!(Cli::try_parse_from(["git-sc", "--subject-only", "-b"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_body_style() {
+        let cli = Cli::parse_from(["git-sc", "-b", "--body-style", "paragraph"]);
+        assert_eq!(cli.body_style, Some("paragraph".to_string()));
+        assert!(Cli::try_parse_from(["git-sc", "--body-style", "prose"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_template() {
+        for name in ["conventional", "gitmoji", "angular"] {
+            let cli = Cli::parse_from(["git-sc", "--template", name]);
+            assert_eq!(cli.template, Some(name.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_cli_template_invalid() {
+        let result = Cli::try_parse_from(["git-sc", "--template", "unknown"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_cli_append_multiple() {
+        let cli = Cli::parse_from([
+            "git-sc",
+            "--append",
+            "Reviewed-by: Alice <alice@example.com>",
+            "--append",
+            "Refs: https://example.com/issues/1",
+        ]);
+        assert_eq!(
+            cli.append,
+            vec![
+                "Reviewed-by: Alice <alice@example.com>".to_string(),
+                "Refs: https://example.com/issues/1".to_string(),
+            ]
+        );
+    }
+
+    #[cfg(feature = "clipboard")]
+    #[test]
+    fn test_cli_from_clipboard() {
+        assert!(!Cli::parse_from(["git-sc"]).from_clipboard);
+        assert!(Cli::parse_from(["git-sc", "--from-clipboard"]).from_clipboard);
+    }
+
+    #[test]
+    fn test_cli_context_multiple() {
+        let cli = Cli::parse_from([
+            "git-sc",
+            "--context",
+            "part of the Q3 auth migration",
+            "--context",
+            "keeps the old endpoint",
+        ]);
+        assert_eq!(
+            cli.context,
+            vec!["part of the Q3 auth migration", "keeps the old endpoint"]
+        );
+    }
+
+    #[test]
+    fn test_cli_coauthor_multiple() {
+        let cli = Cli::parse_from([
+            "git-sc",
+            "--coauthor",
+            "Alice <alice@example.com>",
+            "--coauthor",
+            "Bob <bob@example.com>",
+        ]);
+        assert_eq!(
+            cli.coauthor,
+            vec![
+                "Alice <alice@example.com>".to_string(),
+                "Bob <bob@example.com>".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cli_no_filter() {
+        let cli = Cli::parse_from(["git-sc", "--no-filter", "-n"]);
+        assert!(cli.no_filter);
+        assert!(cli.dry_run);
+    }
+
+    #[test]
+    fn test_cli_diff_algorithm() {
+        let cli = Cli::parse_from(["git-sc", "--diff-algorithm", "histogram"]);
+        assert_eq!(cli.diff_algorithm, Some("histogram".to_string()));
+        assert!(Cli::try_parse_from(["git-sc", "--diff-algorithm", "fast"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_include_whitespace() {
+        let cli = Cli::parse_from(["git-sc", "--include-whitespace"]);
+        assert!(cli.include_whitespace);
+    }
+
+    #[test]
+    fn test_cli_max_subject() {
+        let cli = Cli::parse_from(["git-sc", "--max-subject", "50"]);
+        assert_eq!(cli.max_subject, Some(50));
+    }
+
+    #[test]
+    fn test_cli_patch() {
+        let cli = Cli::parse_from(["git-sc", "--patch", "incoming.patch", "-b"]);
+        assert_eq!(cli.patch, Some(PathBuf::from("incoming.patch")));
+        assert!(Cli::try_parse_from(["git-sc", "--patch", "a.patch", "--amend"]).is_err());
+        assert!(Cli::try_parse_from(["git-sc", "--patch", "a.patch", "-g", "abc"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_wrap() {
+        let cli = Cli::parse_from(["git-sc", "-b", "--wrap", "72"]);
+        assert_eq!(cli.wrap, Some(72));
+        let cli = Cli::parse_from(["git-sc", "--wrap", "0"]);
+        assert_eq!(cli.wrap, Some(0));
+        assert!(Cli::try_parse_from(["git-sc", "--wrap", "wide"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_force_conventional() {
+        let cli = Cli::parse_from(["git-sc", "--yes", "--force-conventional"]);
+        assert!(cli.auto_confirm);
+        assert!(cli.force_conventional);
+    }
+
+    #[test]
+    fn test_cli_ref_commits() {
+        let cli = Cli::parse_from(["git-sc", "--ref-commits", "10"]);
+        assert_eq!(cli.ref_commits, Some(10));
+        assert!(Cli::try_parse_from(["git-sc", "--ref-commits", "many"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_no_recent_commits() {
+        let cli = Cli::parse_from(["git-sc", "--no-recent-commits"]);
+        assert!(cli.no_recent_commits);
+    }
+
+    #[test]
+    fn test_cli_use_branch_context() {
+        let cli = Cli::parse_from(["git-sc", "--use-branch-context"]);
+        assert!(cli.use_branch_context);
+    }
+
+    #[test]
+    fn test_cli_recent_bodies() {
+        let cli = Cli::parse_from(["git-sc", "--recent-bodies", "-b"]);
+        assert!(cli.recent_bodies);
+        assert!(cli.with_body);
+    }
+
+    #[test]
+    fn test_cli_no_config_with_lang() {
+        let cli = Cli::parse_from(["git-sc", "--no-config", "-l", "English"]);
+        assert!(cli.no_config);
+        assert_eq!(cli.language, Some("English".to_string()));
+    }
+
+    #[test]
+    fn test_cli_allow_empty() {
+        let cli = Cli::parse_from(["git-sc", "--allow-empty", "-y"]);
+        assert!(cli.allow_empty);
+
+        assert!(Cli::try_parse_from(["git-sc", "--allow-empty", "--amend"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_retry_providers() {
+        let cli = Cli::parse_from(["git-sc", "--retry-providers", "3"]);
+        assert_eq!(cli.retry_providers, Some(3));
+
+        assert!(Cli::try_parse_from(["git-sc", "--retry-providers", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_message_file() {
+        let cli = Cli::parse_from(["git-sc", "--message-file", ".git/COMMIT_EDITMSG"]);
+        assert_eq!(cli.message_file, Some(PathBuf::from(".git/COMMIT_EDITMSG")));
+
+        assert!(Cli::try_parse_from(["git-sc", "--message-file", "msg", "--amend"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_amend_at() {
+        let cli = Cli::parse_from(["git-sc", "--amend-at", "abc1234", "-y"]);
+        assert_eq!(cli.amend_at, Some("abc1234".to_string()));
+
+        assert!(Cli::try_parse_from(["git-sc", "--amend-at", "abc1234", "--amend"]).is_err());
+        assert!(
+            Cli::try_parse_from(["git-sc", "--amend-at", "abc1234", "--reword", "def"]).is_err()
+        );
+        assert!(Cli::try_parse_from(["git-sc", "--amend-at", "abc1234", "--stat-only"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_author() {
+        let cli = Cli::parse_from(["git-sc", "--author", "Alice <alice@example.com>"]);
+        assert_eq!(cli.author, Some("Alice <alice@example.com>".to_string()));
+    }
+
+    #[test]
+    fn test_cli_explain() {
+        let cli = Cli::parse_from(["git-sc", "--explain", "--dry-run"]);
+        assert!(cli.explain);
+    }
+
+    #[test]
+    fn test_cli_fill() {
+        let cli = Cli::parse_from(["git-sc", "--fill", "skeleton.txt"]);
+        assert_eq!(cli.fill, Some(PathBuf::from("skeleton.txt")));
+
+        assert!(Cli::try_parse_from(["git-sc", "--fill", "skeleton.txt", "-b"]).is_err());
+        assert!(Cli::try_parse_from(["git-sc", "--fill", "skeleton.txt", "--amend"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_max_subject_invalid() {
+        let result = Cli::try_parse_from(["git-sc", "--max-subject", "abc"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_cli_max_subject_too_small() {
+        for value in ["0", "1", "9"] {
+            let result = Cli::try_parse_from(["git-sc", "--max-subject", value]);
+            assert_eq!(
+                result.unwrap_err().kind(),
+                clap::error::ErrorKind::ValueValidation
+            );
+        }
+        assert_eq!(
+            Cli::parse_from(["git-sc", "--max-subject", "10"]).max_subject,
+            Some(10)
+        );
+    }
+
+    #[test]
+    fn test_cli_pathspec_multiple() {
+        let cli = Cli::parse_from(["git-sc", "--pathspec", "src/", "*.md", "-y"]);
+        assert_eq!(cli.pathspec, vec!["src/".to_string(), "*.md".to_string()]);
+        assert!(cli.auto_confirm);
+    }
+
+    #[test]
+    fn test_cli_pathspec_conflicts_with_amend() {
+        let result = Cli::try_parse_from(["git-sc", "--pathspec", "src/", "--amend"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_cli_offline() {
+        let cli = Cli::parse_from(["git-sc", "--offline", "-a"]);
+        assert!(cli.offline);
+        assert!(cli.stage_all);
+    }
+
+    #[test]
+    fn test_cli_offline_conflicts_with_reword() {
+        let result = Cli::try_parse_from(["git-sc", "--offline", "--reword", "abc1234"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_cli_fixup() {
+        let cli = Cli::parse_from(["git-sc", "--fixup", "abc1234"]);
+        assert_eq!(cli.fixup, Some("abc1234".to_string()));
+    }
+
+    #[test]
+    fn test_cli_fixup_with_stage_all() {
+        let cli = Cli::parse_from(["git-sc", "--fixup", "abc1234", "-a"]);
+        assert_eq!(cli.fixup, Some("abc1234".to_string()));
+        assert!(cli.stage_all);
+    }
+
+    #[test]
+    fn test_cli_fixup_conflicts_with_amend() {
+        let result = Cli::try_parse_from(["git-sc", "--fixup", "abc1234", "--amend"]);
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_cli_body_short() {
         let cli = Cli::parse_from(["git-sc", "-b"]);
@@ -252,6 +1135,24 @@ mod tests {
         assert_eq!(cli.generate_for, Some(vec!["abc1234def5678".to_string()]));
     }
 
+    #[test]
+    fn test_cli_chronological() {
+        let cli = Cli::parse_from(["git-sc", "-g", "def5678", "abc1234", "--chronological"]);
+        assert!(cli.chronological);
+
+        // --generate-for なしでは指定できない
+        assert!(Cli::try_parse_from(["git-sc", "--chronological"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_per_commit() {
+        let cli = Cli::parse_from(["git-sc", "-g", "abc1234", "def5678", "--per-commit"]);
+        assert!(cli.per_commit);
+
+        // --generate-for なしでは指定できない
+        assert!(Cli::try_parse_from(["git-sc", "--per-commit"]).is_err());
+    }
+
     #[test]
     fn test_cli_generate_for_multiple() {
         let cli = Cli::parse_from(["git-sc", "-g", "abc1234", "def5678", "ghi9012"]);
@@ -1,26 +1,88 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
+use toml_edit::{DocumentMut, Item, Table};
 
 use crate::error::AppError;
 
+/// Gemini の既定のモデル
+const DEFAULT_GEMINI_MODEL: &str = "flash";
+/// Codex の既定のモデル
+const DEFAULT_CODEX_MODEL: &str = "gpt-5.1-codex-mini";
+/// Claude の既定のモデル
+const DEFAULT_CLAUDE_MODEL: &str = "haiku";
+
 /// 各プロバイダーのモデル設定
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// 未指定（None）のプロバイダーは既定のモデルを使う。プロジェクト設定で一部のみ指定した場合も
+/// 残りはグローバル設定の値を引き継げるよう、指定の有無を保持する
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ModelsConfig {
-    pub gemini: String,
-    pub codex: String,
-    pub claude: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gemini: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub codex: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claude: Option<String>,
 }
 
-impl Default for ModelsConfig {
-    fn default() -> Self {
+impl ModelsConfig {
+    /// 既定のモデルをすべて明示した設定（新規作成する設定ファイル用）
+    pub fn with_defaults() -> Self {
         Self {
-            gemini: "flash".to_string(),
-            codex: "gpt-5.1-codex-mini".to_string(),
-            claude: "haiku".to_string(),
+            gemini: Some(DEFAULT_GEMINI_MODEL.to_string()),
+            codex: Some(DEFAULT_CODEX_MODEL.to_string()),
+            claude: Some(DEFAULT_CLAUDE_MODEL.to_string()),
         }
     }
+
+    /// Gemini のモデル（未指定なら既定）
+    pub fn gemini(&self) -> &str {
+        self.gemini.as_deref().unwrap_or(DEFAULT_GEMINI_MODEL)
+    }
+
+    /// Codex のモデル（未指定なら既定）
+    pub fn codex(&self) -> &str {
+        self.codex.as_deref().unwrap_or(DEFAULT_CODEX_MODEL)
+    }
+
+    /// Claude のモデル（未指定なら既定）
+    pub fn claude(&self) -> &str {
+        self.claude.as_deref().unwrap_or(DEFAULT_CLAUDE_MODEL)
+    }
+}
+
+/// 各プロバイダーのCLIに追加で渡す引数
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProviderArgsConfig {
+    #[serde(default)]
+    pub gemini: Vec<String>,
+    #[serde(default)]
+    pub codex: Vec<String>,
+    #[serde(default)]
+    pub claude: Vec<String>,
+}
+
+/// 各プロバイダーの実行ファイルのパス（未指定ならコマンド名で PATH から探す）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProviderPathsConfig {
+    #[serde(default)]
+    pub gemini: Option<String>,
+    #[serde(default)]
+    pub codex: Option<String>,
+    #[serde(default)]
+    pub claude: Option<String>,
+}
+
+/// 優先度付きプロバイダー設定
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProviderPriorityConfig {
+    /// プロバイダー名（gemini, codex, claude）
+    pub name: String,
+    /// 優先度（大きいほど先に試行、省略時は 0）
+    #[serde(default)]
+    pub priority: i32,
 }
 
 /// プレフィックススクリプト設定
@@ -44,15 +106,24 @@ pub struct PrefixRuleConfig {
 /// アプリケーション設定
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
-    /// AIプロバイダーの優先順序
+    /// AIプロバイダーの優先順序（未指定なら既定の順序、空の指定はエラー）
+    #[serde(default)]
+    pub providers: Option<Vec<String>>,
+    /// 優先度付きのプロバイダー設定（指定時は providers より優先）
     #[serde(default)]
-    pub providers: Vec<String>,
+    pub providers_detailed: Vec<ProviderPriorityConfig>,
     /// コミットメッセージの言語
     #[serde(default = "default_language")]
     pub language: String,
     /// 各プロバイダーのモデル
     #[serde(default)]
     pub models: ModelsConfig,
+    /// 各プロバイダーのCLIに追加で渡す引数
+    #[serde(default)]
+    pub providers_args: ProviderArgsConfig,
+    /// 各プロバイダーの実行ファイルのパス
+    #[serde(default)]
+    pub provider_paths: ProviderPathsConfig,
     /// プレフィックス生成スクリプト設定（オプション）
     #[serde(default)]
     pub prefix_scripts: Vec<PrefixScriptConfig>,
@@ -62,12 +133,108 @@ pub struct Config {
     /// プロバイダーエラー時のクールダウン時間（分）
     #[serde(default = "default_provider_cooldown_minutes")]
     pub provider_cooldown_minutes: u64,
+    /// 全プロバイダーが失敗したときにプロバイダー一覧を何周まで試行するか
+    #[serde(default)]
+    pub provider_passes: Option<u32>,
+    /// クールダウン中のプロバイダーを末尾に回すのではなく試行対象から外すか
+    #[serde(default)]
+    pub skip_demoted: Option<bool>,
     /// コミットメッセージの形式（conventional, bracket, colon, emoji, plain）
     #[serde(default)]
     pub prefix_type: Option<String>,
     /// 自動プッシュの有効/無効
     #[serde(default)]
     pub auto_push: Option<bool>,
+    /// 大きな差分をファイル単位で要約してから生成するか（追加のAI呼び出しが発生）
+    #[serde(default)]
+    pub chunk_large_diffs: Option<bool>,
+    /// AI が本文を付けても件名（最初の空でない行）のみをコミットメッセージにするか
+    #[serde(default)]
+    pub subject_only: Option<bool>,
+    /// 変更ファイルの共通ディレクトリを Conventional Commits のスコープ候補としてAIに示すか
+    #[serde(default)]
+    pub suggest_scope: Option<bool>,
+    /// 差分取得時に空白のみの変更を無視するか
+    #[serde(default = "default_ignore_whitespace")]
+    pub ignore_whitespace: bool,
+    /// 差分取得時のアルゴリズム（myers / minimal / patience / histogram）
+    #[serde(default)]
+    pub diff_algorithm: Option<String>,
+    /// AIに送る差分の単位（"line" / "word"、デフォルト: "line"）
+    #[serde(default)]
+    pub diff_mode: Option<String>,
+    /// コミットメッセージに含めてはいけない語（大文字小文字を区別しない）
+    #[serde(default)]
+    pub blocked_terms: Vec<String>,
+    /// Conventional Commits で使用を許可する種類（空なら制限なし）
+    #[serde(default)]
+    pub allowed_types: Vec<String>,
+    /// 再生成後も許可されていない種類ならエラーにするか（無効時は警告のみ）
+    #[serde(default)]
+    pub strict_allowed_types: Option<bool>,
+    /// 件名の最大文字数（超過分は単語境界で切り詰め）
+    #[serde(default)]
+    pub max_subject_length: Option<usize>,
+    /// ステージ済みファイルの一覧をプロンプトに含めるか（デフォルト: true）
+    #[serde(default)]
+    pub list_changed_files: Option<bool>,
+    /// 現在のブランチ名をプロンプトに含めるか（デフォルト: false）
+    #[serde(default)]
+    pub use_branch_context: Option<bool>,
+    /// コミットする変更がないときの扱い（"ok" / "error"、未指定なら --all のときのみ正常終了）
+    #[serde(default)]
+    pub empty_changes_behavior: Option<String>,
+    /// 書式参照から自動生成の revert・マージコミットを除くか（デフォルト: true）
+    #[serde(default)]
+    pub filter_style_reference: Option<bool>,
+    /// プロンプトの Instructions の末尾に追加する指示
+    #[serde(default)]
+    pub prompt_suffix: Option<String>,
+    /// 本文を折り返す桁数（0 なら折り返さない）
+    #[serde(default)]
+    pub body_wrap_width: Option<usize>,
+    /// 件名（type の後ろ）の先頭文字の大文字・小文字（"lower" / "sentence"）
+    #[serde(default)]
+    pub subject_case: Option<String>,
+    /// 絵文字プレフィックスの表記（"unicode" / "shortcode"、デフォルト: "unicode"）
+    #[serde(default)]
+    pub emoji_style: Option<String>,
+    /// 1ファイルあたりの変更行数の上限（超えたファイルの差分は省略）
+    #[serde(default)]
+    pub max_file_diff_lines: Option<usize>,
+    /// プロバイダーのクールダウン状態をリポジトリ単位（.git/git-sc-state）で管理するか
+    #[serde(default)]
+    pub scope_state_to_repo: Option<bool>,
+    /// 差分に秘密情報らしき値があれば伏せ字にせずエラーにするか
+    #[serde(default)]
+    pub strict_secret_scan: Option<bool>,
+    /// amend 時に元のコミット日時を維持するか
+    #[serde(default)]
+    pub amend_keep_date: Option<bool>,
+    /// 書式参照用の直近コミットに本文も含めるか（件名のみでは本文の書式を学習できないため）
+    #[serde(default)]
+    pub recent_commit_bodies: Option<bool>,
+    /// 書式参照用に AI に渡す直近コミットの件数（未指定時は5件）
+    #[serde(default)]
+    pub style_reference_commits: Option<usize>,
+    /// 直近コミットを書式参照としてAIに示さないか（Auto モードは Conventional Commits になる）
+    #[serde(default)]
+    pub no_recent_commits: Option<bool>,
+    /// メッセージ末尾に追加するフッターのテンプレート（{branch}, {remote}, {date} を置換）
+    #[serde(default)]
+    pub footer_template: Option<String>,
+    /// ブランチ名の課題番号参照から "Closes #N" フッターを追加するか
+    #[serde(default)]
+    pub issue_refs: Option<bool>,
+    /// 差分の追加行も課題番号参照の検索対象にするか（デフォルト: false）
+    #[serde(default)]
+    pub issue_refs_from_diff: Option<bool>,
+    /// 課題番号参照の正規表現（未指定時は語の先頭の `#N`、`ref` という名前のグループがあればその部分を使う）
+    #[serde(default)]
+    pub issue_ref_pattern: Option<String>,
+    /// 課題番号フッターのキーワード（Closes / Fixes / Refs など、未指定時は Closes）
+    #[serde(default)]
+    pub issue_ref_keyword: Option<String>,
 }
 
 /// デフォルトのクールダウン時間（60分 = 1時間）
@@ -75,6 +242,11 @@ fn default_provider_cooldown_minutes() -> u64 {
     60
 }
 
+/// デフォルトでは空白のみの変更を無視する
+fn default_ignore_whitespace() -> bool {
+    true
+}
+
 /// デフォルトの言語
 fn default_language() -> String {
     "Japanese".to_string()
@@ -83,18 +255,53 @@ fn default_language() -> String {
 impl Default for Config {
     fn default() -> Self {
         Self {
-            providers: vec![
+            providers: Some(vec![
                 "gemini".to_string(),
                 "codex".to_string(),
                 "claude".to_string(),
-            ],
+            ]),
+            providers_detailed: Vec::new(),
             language: default_language(),
-            models: ModelsConfig::default(),
+            models: ModelsConfig::with_defaults(),
+            providers_args: ProviderArgsConfig::default(),
+            provider_paths: ProviderPathsConfig::default(),
             prefix_scripts: Vec::new(),
             prefix_rules: Vec::new(),
             provider_cooldown_minutes: default_provider_cooldown_minutes(),
+            provider_passes: None,
+            skip_demoted: None,
             prefix_type: None,
             auto_push: None,
+            chunk_large_diffs: None,
+            subject_only: None,
+            suggest_scope: None,
+            ignore_whitespace: default_ignore_whitespace(),
+            diff_algorithm: None,
+            diff_mode: None,
+            blocked_terms: Vec::new(),
+            allowed_types: Vec::new(),
+            strict_allowed_types: None,
+            max_subject_length: None,
+            list_changed_files: None,
+            use_branch_context: None,
+            empty_changes_behavior: None,
+            filter_style_reference: None,
+            prompt_suffix: None,
+            body_wrap_width: None,
+            subject_case: None,
+            emoji_style: None,
+            max_file_diff_lines: None,
+            scope_state_to_repo: None,
+            strict_secret_scan: None,
+            amend_keep_date: None,
+            recent_commit_bodies: None,
+            style_reference_commits: None,
+            no_recent_commits: None,
+            footer_template: None,
+            issue_refs: None,
+            issue_refs_from_diff: None,
+            issue_ref_pattern: None,
+            issue_ref_keyword: None,
         }
     }
 }
@@ -129,6 +336,16 @@ impl Config {
         }
     }
 
+    /// 設定ファイルを読み込んで構文を検証（doctor 用）
+    pub fn validate_file(path: &Path) -> Result<(), AppError> {
+        let content = fs::read_to_string(path).map_err(|e| {
+            AppError::ConfigError(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        toml::from_str::<Self>(&content).map(|_| ()).map_err(|e| {
+            AppError::ConfigError(format!("Failed to parse {}: {}", path.display(), e))
+        })
+    }
+
     /// グローバル設定を読み込む
     fn load_global() -> Result<Option<Self>, AppError> {
         let path = Self::global_config_path()?;
@@ -178,22 +395,41 @@ impl Config {
 
     /// 2つの設定をマージ（other が優先）
     pub fn merge_with(&mut self, other: Self) {
-        // Vec フィールド: other が空でなければ完全置換
-        if !other.providers.is_empty() {
+        // providers: 指定されていれば（空でも）完全置換
+        if other.providers.is_some() {
             self.providers = other.providers;
         }
+
+        // Vec フィールド: other が空でなければ完全置換
+        if !other.providers_detailed.is_empty() {
+            self.providers_detailed = other.providers_detailed;
+        }
         if !other.prefix_scripts.is_empty() {
             self.prefix_scripts = other.prefix_scripts;
         }
         if !other.prefix_rules.is_empty() {
             self.prefix_rules = other.prefix_rules;
         }
+        if !other.blocked_terms.is_empty() {
+            self.blocked_terms = other.blocked_terms;
+        }
+        if !other.allowed_types.is_empty() {
+            self.allowed_types = other.allowed_types;
+        }
+        if other.strict_allowed_types.is_some() {
+            self.strict_allowed_types = other.strict_allowed_types;
+        }
 
         // String フィールド: other がデフォルトでなければ上書き
         if other.language != default_language() {
             self.language = other.language;
         }
 
+        // bool フィールド: other がデフォルトでなければ上書き
+        if other.ignore_whitespace != default_ignore_whitespace() {
+            self.ignore_whitespace = other.ignore_whitespace;
+        }
+
         // Option フィールド: Some で上書き
         if other.prefix_type.is_some() {
             self.prefix_type = other.prefix_type;
@@ -201,18 +437,124 @@ impl Config {
         if other.auto_push.is_some() {
             self.auto_push = other.auto_push;
         }
+        if other.chunk_large_diffs.is_some() {
+            self.chunk_large_diffs = other.chunk_large_diffs;
+        }
+        if other.subject_only.is_some() {
+            self.subject_only = other.subject_only;
+        }
+        if other.suggest_scope.is_some() {
+            self.suggest_scope = other.suggest_scope;
+        }
+        if other.max_subject_length.is_some() {
+            self.max_subject_length = other.max_subject_length;
+        }
+        if other.diff_mode.is_some() {
+            self.diff_mode = other.diff_mode;
+        }
+        if other.diff_algorithm.is_some() {
+            self.diff_algorithm = other.diff_algorithm;
+        }
+        if other.empty_changes_behavior.is_some() {
+            self.empty_changes_behavior = other.empty_changes_behavior;
+        }
+        if other.list_changed_files.is_some() {
+            self.list_changed_files = other.list_changed_files;
+        }
+        if other.use_branch_context.is_some() {
+            self.use_branch_context = other.use_branch_context;
+        }
+        if other.filter_style_reference.is_some() {
+            self.filter_style_reference = other.filter_style_reference;
+        }
+        if other.prompt_suffix.is_some() {
+            self.prompt_suffix = other.prompt_suffix;
+        }
+        if other.body_wrap_width.is_some() {
+            self.body_wrap_width = other.body_wrap_width;
+        }
+        if other.emoji_style.is_some() {
+            self.emoji_style = other.emoji_style;
+        }
+        if other.subject_case.is_some() {
+            self.subject_case = other.subject_case;
+        }
+        if other.max_file_diff_lines.is_some() {
+            self.max_file_diff_lines = other.max_file_diff_lines;
+        }
+        if other.scope_state_to_repo.is_some() {
+            self.scope_state_to_repo = other.scope_state_to_repo;
+        }
+        if other.strict_secret_scan.is_some() {
+            self.strict_secret_scan = other.strict_secret_scan;
+        }
+        if other.amend_keep_date.is_some() {
+            self.amend_keep_date = other.amend_keep_date;
+        }
+        if other.recent_commit_bodies.is_some() {
+            self.recent_commit_bodies = other.recent_commit_bodies;
+        }
+        if other.style_reference_commits.is_some() {
+            self.style_reference_commits = other.style_reference_commits;
+        }
+        if other.no_recent_commits.is_some() {
+            self.no_recent_commits = other.no_recent_commits;
+        }
+        if other.footer_template.is_some() {
+            self.footer_template = other.footer_template;
+        }
+        if other.issue_refs.is_some() {
+            self.issue_refs = other.issue_refs;
+        }
+        if other.issue_refs_from_diff.is_some() {
+            self.issue_refs_from_diff = other.issue_refs_from_diff;
+        }
+        if other.issue_ref_pattern.is_some() {
+            self.issue_ref_pattern = other.issue_ref_pattern;
+        }
+        if other.issue_ref_keyword.is_some() {
+            self.issue_ref_keyword = other.issue_ref_keyword;
+        }
+        if other.provider_passes.is_some() {
+            self.provider_passes = other.provider_passes;
+        }
+        if other.skip_demoted.is_some() {
+            self.skip_demoted = other.skip_demoted;
+        }
 
-        // ModelsConfig: 個別フィールドをマージ
-        if other.models.gemini != ModelsConfig::default().gemini {
+        // ModelsConfig: 指定されたプロバイダーのみ上書き（既定値と同じ値の指定も反映する）
+        if other.models.gemini.is_some() {
             self.models.gemini = other.models.gemini;
         }
-        if other.models.codex != ModelsConfig::default().codex {
+        if other.models.codex.is_some() {
             self.models.codex = other.models.codex;
         }
-        if other.models.claude != ModelsConfig::default().claude {
+        if other.models.claude.is_some() {
             self.models.claude = other.models.claude;
         }
 
+        // ProviderArgsConfig: 指定されたプロバイダーのみ上書き
+        if !other.providers_args.gemini.is_empty() {
+            self.providers_args.gemini = other.providers_args.gemini;
+        }
+        if !other.providers_args.codex.is_empty() {
+            self.providers_args.codex = other.providers_args.codex;
+        }
+        if !other.providers_args.claude.is_empty() {
+            self.providers_args.claude = other.providers_args.claude;
+        }
+
+        // ProviderPathsConfig: 指定されたプロバイダーのみ上書き
+        if other.provider_paths.gemini.is_some() {
+            self.provider_paths.gemini = other.provider_paths.gemini;
+        }
+        if other.provider_paths.codex.is_some() {
+            self.provider_paths.codex = other.provider_paths.codex;
+        }
+        if other.provider_paths.claude.is_some() {
+            self.provider_paths.claude = other.provider_paths.claude;
+        }
+
         // provider_cooldown_minutes: デフォルトでなければ上書き
         if other.provider_cooldown_minutes != default_provider_cooldown_minutes() {
             self.provider_cooldown_minutes = other.provider_cooldown_minutes;
@@ -240,18 +582,116 @@ impl Config {
         Ok(config)
     }
 
+    /// 設定ファイルを作成せずに読み込む（doctor / state 用）
+    ///
+    /// グローバル設定がなければデフォルトを使い、load と違って ~/.git-sc を書き出さない
+    pub fn load_without_saving() -> Result<Self, AppError> {
+        let mut config = Self::load_global()?.unwrap_or_default();
+        if let Some(project_config) = Self::load_project()? {
+            config.merge_with(project_config);
+        }
+        Ok(config)
+    }
+
+    /// 設定を読み込む（no_config が true なら設定ファイルを読まずデフォルトを使用）
+    pub fn load_with(no_config: bool) -> Result<Self, AppError> {
+        if no_config {
+            return Ok(Config::default());
+        }
+        Self::load()
+    }
+
     /// 設定をファイルに保存
+    ///
+    /// 既存のファイルがあれば各項目を set_field で更新し、ユーザーのコメントや書式を保持する
     pub fn save(&self) -> Result<(), AppError> {
         let path = Self::global_config_path()?;
 
-        let content = toml::to_string_pretty(self)
-            .map_err(|e| AppError::ConfigError(format!("Failed to serialize config: {}", e)))?;
+        let existing = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => {
+                return Err(AppError::ConfigError(format!(
+                    "Failed to read config: {}",
+                    e
+                )))
+            }
+        };
+        let content = self.update_document(&existing)?;
 
         fs::write(&path, content)
             .map_err(|e| AppError::ConfigError(format!("Failed to write config: {}", e)))?;
 
         Ok(())
     }
+
+    /// 既存の TOML 文書にこの設定の値を書き込む（コメントや書式は保持）
+    fn update_document(&self, existing: &str) -> Result<String, AppError> {
+        let serialized = toml::to_string_pretty(self)
+            .map_err(|e| AppError::ConfigError(format!("Failed to serialize config: {}", e)))?;
+        let values = parse_document(&serialized)?;
+        let mut doc = parse_document(existing)?;
+
+        let mut fields = Vec::new();
+        collect_fields(values.as_table(), "", &mut fields);
+        for (path, value) in fields {
+            Self::set_field(&mut doc, &path, value)?;
+        }
+
+        Ok(doc.to_string())
+    }
+
+    /// TOML 文書の1項目を更新（`models.gemini` のようなドット区切りのパスで指定）
+    ///
+    /// 途中のテーブルがなければ作成する。既存の値を置き換える場合は
+    /// 前後のコメントや空白を引き継ぎ、文書の他の部分には手を加えない
+    pub fn set_field(doc: &mut DocumentMut, path: &str, value: Item) -> Result<(), AppError> {
+        let invalid = || AppError::ConfigError(format!("Invalid config key: {}", path));
+        let mut keys: Vec<&str> = path.split('.').collect();
+        let last = keys.pop().filter(|k| !k.is_empty()).ok_or_else(invalid)?;
+
+        let mut table = doc.as_table_mut() as &mut dyn toml_edit::TableLike;
+        for key in keys {
+            table = table
+                .entry(key)
+                .or_insert(Item::Table(Table::new()))
+                .as_table_like_mut()
+                .ok_or_else(invalid)?;
+        }
+
+        match (table.get_mut(last), value) {
+            (Some(Item::Value(old)), Item::Value(mut new)) => {
+                *new.decor_mut() = old.decor().clone();
+                *old = new;
+            }
+            (_, value) => {
+                table.insert(last, value);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// TOML 文字列を編集可能な文書として読み込む
+fn parse_document(content: &str) -> Result<DocumentMut, AppError> {
+    content
+        .parse::<DocumentMut>()
+        .map_err(|e| AppError::ConfigError(format!("Failed to parse config: {}", e)))
+}
+
+/// テーブルの末端の項目をドット区切りのパスと値の組で列挙（配列のテーブルは1項目として扱う）
+fn collect_fields(table: &Table, prefix: &str, fields: &mut Vec<(String, Item)>) {
+    for (key, item) in table.iter() {
+        let path = if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match item.as_table() {
+            Some(child) => collect_fields(child, &path, fields),
+            None => fields.push((path, item.clone())),
+        }
+    }
 }
 
 /// テスト用ヘルパー関数
@@ -276,11 +716,11 @@ mod tests {
 
         assert_eq!(
             config.providers,
-            vec![
+            Some(vec![
                 "gemini".to_string(),
                 "codex".to_string(),
                 "claude".to_string()
-            ]
+            ])
         );
         assert_eq!(config.language, "Japanese");
         assert!(config.prefix_scripts.is_empty());
@@ -292,9 +732,14 @@ mod tests {
     fn test_default_models_config() {
         let models = ModelsConfig::default();
 
-        assert_eq!(models.gemini, "flash");
-        assert_eq!(models.codex, "gpt-5.1-codex-mini");
-        assert_eq!(models.claude, "haiku");
+        assert_eq!(models.gemini(), "flash");
+        assert_eq!(models.codex(), "gpt-5.1-codex-mini");
+        assert_eq!(models.claude(), "haiku");
+        // 新規作成する設定ファイルには既定のモデルを明示する
+        let explicit = ModelsConfig::with_defaults();
+        assert_eq!(explicit.gemini.as_deref(), Some("flash"));
+        assert_eq!(explicit.codex.as_deref(), Some("gpt-5.1-codex-mini"));
+        assert_eq!(explicit.claude.as_deref(), Some("haiku"));
     }
 
     #[test]
@@ -306,10 +751,10 @@ language = "English"
 
         let config = Config::from_str(toml).unwrap();
 
-        assert_eq!(config.providers, vec!["gemini".to_string()]);
+        assert_eq!(config.providers, Some(vec!["gemini".to_string()]));
         assert_eq!(config.language, "English");
         // デフォルト値が使用される
-        assert_eq!(config.models.gemini, "flash");
+        assert_eq!(config.models.gemini(), "flash");
         assert!(config.prefix_scripts.is_empty());
         assert!(config.prefix_rules.is_empty());
         assert_eq!(config.provider_cooldown_minutes, 60);
@@ -435,16 +880,16 @@ prefix_type = "conventional"
 
         assert_eq!(
             config.providers,
-            vec![
+            Some(vec![
                 "claude".to_string(),
                 "gemini".to_string(),
                 "codex".to_string()
-            ]
+            ])
         );
         assert_eq!(config.language, "English");
-        assert_eq!(config.models.gemini, "pro");
-        assert_eq!(config.models.codex, "gpt-4");
-        assert_eq!(config.models.claude, "opus");
+        assert_eq!(config.models.gemini(), "pro");
+        assert_eq!(config.models.codex(), "gpt-4");
+        assert_eq!(config.models.claude(), "opus");
         assert_eq!(config.prefix_scripts.len(), 1);
         assert_eq!(config.prefix_rules.len(), 1);
     }
@@ -459,7 +904,7 @@ prefix_type = "conventional"
 
         assert_eq!(config.providers, deserialized.providers);
         assert_eq!(config.language, deserialized.language);
-        assert_eq!(config.models.gemini, deserialized.models.gemini);
+        assert_eq!(config.models.gemini(), deserialized.models.gemini());
     }
 
     // ============================================================
@@ -524,23 +969,27 @@ language = "Japanese"
 
     #[test]
     fn test_merge_with_empty_project_config() {
-        let mut global = Config::default();
-        global.providers = vec!["gemini".to_string(), "claude".to_string()];
-        global.language = "English".to_string();
-        global.prefix_type = Some("conventional".to_string());
-        global.auto_push = Some(true);
+        let mut global = Config {
+            providers: Some(vec!["gemini".to_string(), "claude".to_string()]),
+            language: "English".to_string(),
+            prefix_type: Some("conventional".to_string()),
+            auto_push: Some(true),
+            ..Config::default()
+        };
 
-        // 空の providers を持つプロジェクト設定を作成
-        let mut project = Config::default();
-        project.providers = Vec::new(); // 明示的に空にする
-        project.language = default_language(); // デフォルト言語（マージ時に上書きされない）
+        // providers を指定しないプロジェクト設定を作成
+        let project = Config {
+            providers: None,
+            language: default_language(), // デフォルト言語（マージ時に上書きされない）
+            ..Config::default()
+        };
 
         global.merge_with(project);
 
-        // プロジェクト設定の providers が空なので、グローバル設定が維持される
+        // プロジェクト設定に providers がないので、グローバル設定が維持される
         assert_eq!(
             global.providers,
-            vec!["gemini".to_string(), "claude".to_string()]
+            Some(vec!["gemini".to_string(), "claude".to_string()])
         );
         assert_eq!(global.language, "English");
         // Option フィールドは None の場合維持される
@@ -550,25 +999,52 @@ language = "Japanese"
 
     #[test]
     fn test_merge_with_project_overrides_providers() {
-        let mut global = Config::default();
-        global.providers = vec!["gemini".to_string(), "claude".to_string()];
+        let mut global = Config {
+            providers: Some(vec!["gemini".to_string(), "claude".to_string()]),
+            ..Config::default()
+        };
 
-        let mut project = Config::default();
-        project.providers = vec!["codex".to_string()];
+        let project = Config {
+            providers: Some(vec!["codex".to_string()]),
+            ..Config::default()
+        };
 
         global.merge_with(project);
 
         // プロジェクト設定の providers が完全に置換される
-        assert_eq!(global.providers, vec!["codex".to_string()]);
+        assert_eq!(global.providers, Some(vec!["codex".to_string()]));
     }
 
     #[test]
-    fn test_merge_with_project_overrides_language() {
+    fn test_parse_config_providers_missing_vs_empty() {
+        assert_eq!(
+            Config::from_str("language = \"English\"")
+                .unwrap()
+                .providers,
+            None
+        );
+        assert_eq!(
+            Config::from_str("providers = []").unwrap().providers,
+            Some(Vec::new())
+        );
+
+        // 明示的に空にしたプロジェクト設定はグローバル設定を上書きする（生成時にエラーになる）
         let mut global = Config::default();
-        global.language = "English".to_string();
+        global.merge_with(Config::from_str("providers = []").unwrap());
+        assert_eq!(global.providers, Some(Vec::new()));
+    }
 
-        let mut project = Config::default();
-        project.language = "French".to_string();
+    #[test]
+    fn test_merge_with_project_overrides_language() {
+        let mut global = Config {
+            language: "English".to_string(),
+            ..Config::default()
+        };
+
+        let project = Config {
+            language: "French".to_string(),
+            ..Config::default()
+        };
 
         global.merge_with(project);
 
@@ -578,11 +1054,15 @@ language = "Japanese"
 
     #[test]
     fn test_merge_with_project_overrides_prefix_type() {
-        let mut global = Config::default();
-        global.prefix_type = Some("conventional".to_string());
+        let mut global = Config {
+            prefix_type: Some("conventional".to_string()),
+            ..Config::default()
+        };
 
-        let mut project = Config::default();
-        project.prefix_type = Some("bracket".to_string());
+        let project = Config {
+            prefix_type: Some("bracket".to_string()),
+            ..Config::default()
+        };
 
         global.merge_with(project);
 
@@ -592,11 +1072,15 @@ language = "Japanese"
 
     #[test]
     fn test_merge_with_project_overrides_auto_push() {
-        let mut global = Config::default();
-        global.auto_push = Some(true);
+        let mut global = Config {
+            auto_push: Some(true),
+            ..Config::default()
+        };
 
-        let mut project = Config::default();
-        project.auto_push = Some(false);
+        let project = Config {
+            auto_push: Some(false),
+            ..Config::default()
+        };
 
         global.merge_with(project);
 
@@ -605,10 +1089,379 @@ language = "Japanese"
     }
 
     #[test]
-    fn test_merge_with_project_none_preserves_global() {
+    fn test_parse_config_with_providers_detailed() {
+        let toml_str = r#"
+[[providers_detailed]]
+name = "claude"
+priority = 10
+
+[[providers_detailed]]
+name = "gemini"
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.providers_detailed.len(), 2);
+        assert_eq!(config.providers_detailed[0].name, "claude");
+        assert_eq!(config.providers_detailed[0].priority, 10);
+        // priority 省略時は 0
+        assert_eq!(config.providers_detailed[1].priority, 0);
+    }
+
+    #[test]
+    fn test_merge_with_providers_detailed_override() {
         let mut global = Config::default();
-        global.prefix_type = Some("conventional".to_string());
-        global.auto_push = Some(true);
+        let project = Config {
+            providers_detailed: vec![ProviderPriorityConfig {
+                name: "codex".to_string(),
+                priority: 5,
+            }],
+            ..Config::default()
+        };
+
+        global.merge_with(project);
+
+        assert_eq!(global.providers_detailed.len(), 1);
+        assert_eq!(global.providers_detailed[0].name, "codex");
+    }
+
+    #[test]
+    fn test_parse_config_with_chunk_large_diffs() {
+        let config: Config = toml::from_str("chunk_large_diffs = true").unwrap();
+        assert_eq!(config.chunk_large_diffs, Some(true));
+
+        let config: Config = toml::from_str("").unwrap();
+        assert_eq!(config.chunk_large_diffs, None);
+    }
+
+    #[test]
+    fn test_parse_config_with_subject_only() {
+        let config: Config = toml::from_str("subject_only = true").unwrap();
+        assert_eq!(config.subject_only, Some(true));
+        assert_eq!(Config::default().subject_only, None);
+    }
+
+    #[test]
+    fn test_parse_config_with_suggest_scope() {
+        let config: Config = toml::from_str("suggest_scope = true").unwrap();
+        assert_eq!(config.suggest_scope, Some(true));
+        assert_eq!(Config::default().suggest_scope, None);
+    }
+
+    #[test]
+    fn test_parse_config_ignore_whitespace() {
+        let config: Config = toml::from_str("").unwrap();
+        assert!(config.ignore_whitespace);
+
+        let config: Config = toml::from_str("ignore_whitespace = false").unwrap();
+        assert!(!config.ignore_whitespace);
+    }
+
+    #[test]
+    fn test_merge_with_project_disables_ignore_whitespace() {
+        let mut global = Config::default();
+        let project = Config {
+            ignore_whitespace: false,
+            ..Config::default()
+        };
+
+        global.merge_with(project);
+
+        assert!(!global.ignore_whitespace);
+    }
+
+    #[test]
+    fn test_validate_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let valid = dir.path().join("valid");
+        std::fs::write(&valid, "language = \"English\"\n").unwrap();
+        assert!(Config::validate_file(&valid).is_ok());
+
+        let invalid = dir.path().join("invalid");
+        std::fs::write(&invalid, "language = [\n").unwrap();
+        assert!(matches!(
+            Config::validate_file(&invalid),
+            Err(AppError::ConfigError(msg)) if msg.starts_with("Failed to parse")
+        ));
+
+        assert!(Config::validate_file(&dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_parse_config_with_blocked_terms() {
+        let config: Config = toml::from_str(r#"blocked_terms = ["falcon", "damn"]"#).unwrap();
+        assert_eq!(config.blocked_terms, vec!["falcon", "damn"]);
+    }
+
+    #[test]
+    fn test_parse_config_with_allowed_types() {
+        let config: Config = toml::from_str(
+            r#"
+allowed_types = ["feat", "fix", "chore"]
+strict_allowed_types = true
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.allowed_types, vec!["feat", "fix", "chore"]);
+        assert_eq!(config.strict_allowed_types, Some(true));
+
+        let default = Config::default();
+        assert!(default.allowed_types.is_empty());
+        assert_eq!(default.strict_allowed_types, None);
+    }
+
+    #[test]
+    fn test_merge_with_blocked_terms_override() {
+        let mut global = Config {
+            blocked_terms: vec!["falcon".to_string()],
+            ..Config::default()
+        };
+        global.merge_with(Config::default());
+        // 空のプロジェクト設定ではグローバルを維持
+        assert_eq!(global.blocked_terms, vec!["falcon"]);
+
+        global.merge_with(Config {
+            blocked_terms: vec!["eagle".to_string()],
+            ..Config::default()
+        });
+        assert_eq!(global.blocked_terms, vec!["eagle"]);
+    }
+
+    #[test]
+    fn test_parse_config_with_max_subject_length() {
+        let toml_str = r#"
+max_subject_length = 72
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.max_subject_length, Some(72));
+    }
+
+    #[test]
+    fn test_parse_config_with_diff_algorithm() {
+        let config: Config = toml::from_str("diff_algorithm = \"histogram\"").unwrap();
+        assert_eq!(config.diff_algorithm.as_deref(), Some("histogram"));
+        assert_eq!(Config::default().diff_algorithm, None);
+    }
+
+    #[test]
+    fn test_parse_config_with_empty_changes_behavior() {
+        let config: Config = toml::from_str("empty_changes_behavior = \"error\"").unwrap();
+        assert_eq!(config.empty_changes_behavior.as_deref(), Some("error"));
+        assert_eq!(Config::default().empty_changes_behavior, None);
+    }
+
+    #[test]
+    fn test_parse_config_with_list_changed_files() {
+        let config: Config = toml::from_str("list_changed_files = false").unwrap();
+        assert_eq!(config.list_changed_files, Some(false));
+        assert_eq!(Config::default().list_changed_files, None);
+    }
+
+    #[test]
+    fn test_parse_config_with_use_branch_context() {
+        let config: Config = toml::from_str("use_branch_context = true").unwrap();
+        assert_eq!(config.use_branch_context, Some(true));
+        assert_eq!(Config::default().use_branch_context, None);
+    }
+
+    #[test]
+    fn test_parse_config_with_filter_style_reference() {
+        let config: Config = toml::from_str("filter_style_reference = false").unwrap();
+        assert_eq!(config.filter_style_reference, Some(false));
+        assert_eq!(Config::default().filter_style_reference, None);
+    }
+
+    #[test]
+    fn test_parse_config_with_prompt_suffix() {
+        let toml_str = r#"
+prompt_suffix = """
+- Always mention the ticket number
+- No trailing period
+"""
+"#;
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            config.prompt_suffix.as_deref(),
+            Some("- Always mention the ticket number\n- No trailing period\n")
+        );
+        assert_eq!(Config::default().prompt_suffix, None);
+    }
+
+    #[test]
+    fn test_parse_config_with_body_wrap_width() {
+        let config: Config = toml::from_str("body_wrap_width = 72").unwrap();
+        assert_eq!(config.body_wrap_width, Some(72));
+        assert_eq!(Config::default().body_wrap_width, None);
+    }
+
+    #[test]
+    fn test_parse_config_with_diff_mode() {
+        let config: Config = toml::from_str("diff_mode = \"word\"").unwrap();
+        assert_eq!(config.diff_mode.as_deref(), Some("word"));
+        assert_eq!(Config::default().diff_mode, None);
+    }
+
+    #[test]
+    fn test_parse_config_with_emoji_style() {
+        let config: Config = toml::from_str("emoji_style = \"shortcode\"").unwrap();
+        assert_eq!(config.emoji_style.as_deref(), Some("shortcode"));
+        assert_eq!(Config::default().emoji_style, None);
+    }
+
+    #[test]
+    fn test_parse_config_with_subject_case() {
+        let config: Config = toml::from_str("subject_case = \"lower\"").unwrap();
+        assert_eq!(config.subject_case.as_deref(), Some("lower"));
+        assert_eq!(Config::default().subject_case, None);
+    }
+
+    #[test]
+    fn test_parse_config_with_max_file_diff_lines() {
+        let config: Config = toml::from_str("max_file_diff_lines = 500").unwrap();
+        assert_eq!(config.max_file_diff_lines, Some(500));
+    }
+
+    #[test]
+    fn test_parse_config_with_scope_state_to_repo() {
+        let config: Config = toml::from_str("scope_state_to_repo = true").unwrap();
+        assert_eq!(config.scope_state_to_repo, Some(true));
+        assert_eq!(Config::default().scope_state_to_repo, None);
+    }
+
+    #[test]
+    fn test_parse_config_with_strict_secret_scan() {
+        let config: Config = toml::from_str("strict_secret_scan = true").unwrap();
+        assert_eq!(config.strict_secret_scan, Some(true));
+    }
+
+    #[test]
+    fn test_parse_config_with_amend_keep_date() {
+        let config: Config = toml::from_str("amend_keep_date = true").unwrap();
+        assert_eq!(config.amend_keep_date, Some(true));
+        assert_eq!(Config::default().amend_keep_date, None);
+    }
+
+    #[test]
+    fn test_parse_config_with_recent_commit_bodies() {
+        let config: Config = toml::from_str("recent_commit_bodies = true").unwrap();
+        assert_eq!(config.recent_commit_bodies, Some(true));
+        assert_eq!(Config::default().recent_commit_bodies, None);
+    }
+
+    #[test]
+    fn test_parse_config_with_style_reference_commits() {
+        let config: Config = toml::from_str("style_reference_commits = 10").unwrap();
+        assert_eq!(config.style_reference_commits, Some(10));
+        assert_eq!(Config::default().style_reference_commits, None);
+    }
+
+    #[test]
+    fn test_parse_config_with_no_recent_commits() {
+        let config: Config = toml::from_str("no_recent_commits = true").unwrap();
+        assert_eq!(config.no_recent_commits, Some(true));
+        assert_eq!(Config::default().no_recent_commits, None);
+    }
+
+    #[test]
+    fn test_parse_config_with_footer_template() {
+        let config: Config =
+            toml::from_str(r#"footer_template = "Branch: {branch}\nGenerated-by: git-sc""#)
+                .unwrap();
+        assert_eq!(
+            config.footer_template,
+            Some("Branch: {branch}\nGenerated-by: git-sc".to_string())
+        );
+        assert_eq!(Config::default().footer_template, None);
+    }
+
+    #[test]
+    fn test_parse_config_with_issue_refs() {
+        let config: Config = toml::from_str(
+            r#"
+issue_refs = true
+issue_refs_from_diff = true
+issue_ref_pattern = "GH-\\d+"
+issue_ref_keyword = "Fixes"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.issue_refs, Some(true));
+        assert_eq!(config.issue_refs_from_diff, Some(true));
+        assert_eq!(config.issue_ref_pattern, Some(r"GH-\d+".to_string()));
+        assert_eq!(config.issue_ref_keyword, Some("Fixes".to_string()));
+
+        let default = Config::default();
+        assert_eq!(default.issue_refs, None);
+        assert_eq!(default.issue_refs_from_diff, None);
+        assert_eq!(default.issue_ref_pattern, None);
+        assert_eq!(default.issue_ref_keyword, None);
+    }
+
+    #[test]
+    fn test_merge_issue_ref_keyword_keeps_base_pattern() {
+        let mut base = Config {
+            issue_refs: Some(true),
+            issue_ref_pattern: Some(r"GH-\d+".to_string()),
+            ..Default::default()
+        };
+        base.merge_with(Config {
+            issue_ref_keyword: Some("Refs".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(base.issue_refs, Some(true));
+        assert_eq!(base.issue_ref_pattern, Some(r"GH-\d+".to_string()));
+        assert_eq!(base.issue_ref_keyword, Some("Refs".to_string()));
+    }
+
+    #[test]
+    fn test_parse_config_with_provider_passes() {
+        let config: Config = toml::from_str("provider_passes = 3").unwrap();
+        assert_eq!(config.provider_passes, Some(3));
+        assert_eq!(Config::default().provider_passes, None);
+    }
+
+    #[test]
+    fn test_parse_config_with_skip_demoted() {
+        let config: Config = toml::from_str("skip_demoted = true").unwrap();
+        assert_eq!(config.skip_demoted, Some(true));
+        assert_eq!(Config::default().skip_demoted, None);
+    }
+
+    #[test]
+    fn test_load_with_no_config_uses_defaults() {
+        let config = Config::load_with(true).unwrap();
+        let default = Config::default();
+        assert_eq!(config.providers, default.providers);
+        assert_eq!(config.language, default.language);
+        assert_eq!(config.models.gemini(), default.models.gemini());
+        assert_eq!(config.prefix_type, None);
+        assert_eq!(config.auto_push, None);
+        assert!(config.prefix_rules.is_empty());
+        assert!(config.prefix_scripts.is_empty());
+    }
+
+    #[test]
+    fn test_merge_with_project_overrides_max_subject_length() {
+        let mut global = Config {
+            max_subject_length: Some(72),
+            ..Config::default()
+        };
+
+        let project = Config {
+            max_subject_length: Some(50),
+            ..Config::default()
+        };
+
+        global.merge_with(project);
+
+        assert_eq!(global.max_subject_length, Some(50));
+    }
+
+    #[test]
+    fn test_merge_with_project_none_preserves_global() {
+        let mut global = Config {
+            prefix_type: Some("conventional".to_string()),
+            auto_push: Some(true),
+            ..Config::default()
+        };
 
         let project = Config::default();
         // project.prefix_type と project.auto_push は None
@@ -620,36 +1473,150 @@ language = "Japanese"
         assert_eq!(global.auto_push, Some(true));
     }
 
+    #[test]
+    fn test_parse_config_with_providers_args() {
+        let config: Config = toml::from_str(
+            r#"
+[providers_args]
+gemini = ["--temperature", "0.2"]
+codex = ["-c", "model_reasoning_effort=low"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.providers_args.gemini, vec!["--temperature", "0.2"]);
+        assert_eq!(
+            config.providers_args.codex,
+            vec!["-c", "model_reasoning_effort=low"]
+        );
+        assert!(config.providers_args.claude.is_empty());
+    }
+
+    #[test]
+    fn test_parse_config_with_provider_paths() {
+        let content = r#"
+[provider_paths]
+gemini = "gemini-2"
+claude = "/opt/tools/claude"
+"#;
+        let config: Config = toml::from_str(content).unwrap();
+        assert_eq!(config.provider_paths.gemini.as_deref(), Some("gemini-2"));
+        assert_eq!(config.provider_paths.codex, None);
+        assert_eq!(
+            config.provider_paths.claude.as_deref(),
+            Some("/opt/tools/claude")
+        );
+    }
+
+    #[test]
+    fn test_merge_with_provider_paths_override() {
+        let mut global = Config::default();
+        global.provider_paths.gemini = Some("gemini-2".to_string());
+        global.provider_paths.claude = Some("/usr/bin/claude".to_string());
+
+        let mut project = Config::default();
+        project.provider_paths.claude = Some("/opt/tools/claude".to_string());
+
+        global.merge_with(project);
+        assert_eq!(global.provider_paths.gemini.as_deref(), Some("gemini-2"));
+        assert_eq!(
+            global.provider_paths.claude.as_deref(),
+            Some("/opt/tools/claude")
+        );
+    }
+
+    #[test]
+    fn test_merge_with_providers_args_override() {
+        let mut global = Config::default();
+        global.providers_args.gemini = vec!["--a".to_string()];
+        global.providers_args.claude = vec!["--b".to_string()];
+
+        let mut project = Config::default();
+        project.providers_args.claude = vec!["--c".to_string()];
+
+        global.merge_with(project);
+
+        assert_eq!(global.providers_args.gemini, vec!["--a"]);
+        assert_eq!(global.providers_args.claude, vec!["--c"]);
+    }
+
+    #[test]
+    fn test_merge_with_partial_project_models_keeps_global() {
+        let global_toml = r#"
+[models]
+gemini = "flash"
+codex = "gpt-4"
+claude = "opus"
+"#;
+        let project_toml = r#"
+[models]
+gemini = "pro"
+"#;
+        let mut global = Config::from_str(global_toml).unwrap();
+        let project = Config::from_str(project_toml).unwrap();
+
+        global.merge_with(project);
+
+        // プロジェクトで指定したモデルのみ上書きし、残りはグローバルの値（既定値ではない）を保つ
+        assert_eq!(global.models.gemini(), "pro");
+        assert_eq!(global.models.codex(), "gpt-4");
+        assert_eq!(global.models.claude(), "opus");
+    }
+
+    #[test]
+    fn test_merge_with_project_models_equal_to_default() {
+        let mut global = Config::from_str("[models]\ncodex = \"gpt-4\"").unwrap();
+        let project = Config::from_str("[models]\ncodex = \"gpt-5.1-codex-mini\"").unwrap();
+
+        global.merge_with(project);
+
+        // 既定値と同じ値でも明示的に指定されていればプロジェクト設定を優先
+        assert_eq!(global.models.codex(), "gpt-5.1-codex-mini");
+    }
+
+    #[test]
+    fn test_merge_with_project_without_models_keeps_global() {
+        let mut global = Config::from_str("[models]\nclaude = \"opus\"").unwrap();
+        let project = Config::from_str("language = \"English\"").unwrap();
+
+        global.merge_with(project);
+
+        assert_eq!(global.models.claude(), "opus");
+    }
+
     #[test]
     fn test_merge_with_models_override() {
         let mut global = Config::default();
 
         let mut project = Config::default();
-        project.models.gemini = "pro".to_string();
-        project.models.claude = "opus".to_string();
+        project.models.gemini = Some("pro".to_string());
+        project.models.claude = Some("opus".to_string());
 
         global.merge_with(project);
 
         // プロジェクト設定のモデルが上書きされる
-        assert_eq!(global.models.gemini, "pro");
-        assert_eq!(global.models.claude, "opus");
+        assert_eq!(global.models.gemini(), "pro");
+        assert_eq!(global.models.claude(), "opus");
         // 変更されていないモデルはデフォルトのまま
-        assert_eq!(global.models.codex, "gpt-5.1-codex-mini");
+        assert_eq!(global.models.codex(), "gpt-5.1-codex-mini");
     }
 
     #[test]
     fn test_merge_with_prefix_rules_override() {
-        let mut global = Config::default();
-        global.prefix_rules = vec![PrefixRuleConfig {
-            url_pattern: "github.com".to_string(),
-            prefix_type: "conventional".to_string(),
-        }];
+        let mut global = Config {
+            prefix_rules: vec![PrefixRuleConfig {
+                url_pattern: "github.com".to_string(),
+                prefix_type: "conventional".to_string(),
+            }],
+            ..Config::default()
+        };
 
-        let mut project = Config::default();
-        project.prefix_rules = vec![PrefixRuleConfig {
-            url_pattern: "gitlab.com".to_string(),
-            prefix_type: "bracket".to_string(),
-        }];
+        let project = Config {
+            prefix_rules: vec![PrefixRuleConfig {
+                url_pattern: "gitlab.com".to_string(),
+                prefix_type: "bracket".to_string(),
+            }],
+            ..Config::default()
+        };
 
         global.merge_with(project);
 
@@ -661,11 +1628,15 @@ language = "Japanese"
 
     #[test]
     fn test_merge_with_cooldown_override() {
-        let mut global = Config::default();
-        global.provider_cooldown_minutes = 60;
+        let mut global = Config {
+            provider_cooldown_minutes: 60,
+            ..Config::default()
+        };
 
-        let mut project = Config::default();
-        project.provider_cooldown_minutes = 30;
+        let project = Config {
+            provider_cooldown_minutes: 30,
+            ..Config::default()
+        };
 
         global.merge_with(project);
 
@@ -708,13 +1679,97 @@ claude = "haiku"
         global.merge_with(project);
 
         // すべてのフィールドがプロジェクト設定で上書きされる
-        assert_eq!(global.providers, vec!["codex".to_string()]);
+        assert_eq!(global.providers, Some(vec!["codex".to_string()]));
         assert_eq!(global.language, "French");
         assert_eq!(global.prefix_type, Some("bracket".to_string()));
         assert_eq!(global.auto_push, Some(false));
         assert_eq!(global.provider_cooldown_minutes, 15);
-        assert_eq!(global.models.gemini, "pro");
+        assert_eq!(global.models.gemini(), "pro");
         // claude は変更されていないのでグローバル設定のまま（両方 haiku）
-        assert_eq!(global.models.claude, "haiku");
+        assert_eq!(global.models.claude(), "haiku");
+    }
+
+    // ============================================================
+    // set_field / update_document のテスト
+    // ============================================================
+
+    const COMMENTED_CONFIG: &str = r#"# 好みの順に並べる
+providers = ["claude", "gemini"] # 速い順
+
+[models]
+# 品質重視
+gemini = "pro"
+codex = "gpt-5.1-codex-mini"
+claude = "haiku"
+"#;
+
+    #[test]
+    fn test_set_field_preserves_comments() {
+        let mut doc = parse_document(COMMENTED_CONFIG).unwrap();
+        Config::set_field(&mut doc, "models.gemini", toml_edit::value("flash")).unwrap();
+
+        assert_eq!(
+            doc.to_string(),
+            r#"# 好みの順に並べる
+providers = ["claude", "gemini"] # 速い順
+
+[models]
+# 品質重視
+gemini = "flash"
+codex = "gpt-5.1-codex-mini"
+claude = "haiku"
+"#
+        );
+    }
+
+    #[test]
+    fn test_set_field_creates_missing_table() {
+        let mut doc = parse_document(COMMENTED_CONFIG).unwrap();
+        Config::set_field(
+            &mut doc,
+            "provider_paths.claude",
+            toml_edit::value("/opt/claude"),
+        )
+        .unwrap();
+
+        let content = doc.to_string();
+        assert!(content.starts_with(COMMENTED_CONFIG));
+        assert!(content.contains("[provider_paths]\nclaude = \"/opt/claude\""));
+    }
+
+    #[test]
+    fn test_set_field_invalid_path() {
+        let mut doc = parse_document(COMMENTED_CONFIG).unwrap();
+        assert!(Config::set_field(&mut doc, "models.", toml_edit::value("x")).is_err());
+        // テーブルではない値の下には設定できない
+        assert!(Config::set_field(&mut doc, "providers.gemini", toml_edit::value("x")).is_err());
+    }
+
+    #[test]
+    fn test_update_document_keeps_comments_and_round_trips() {
+        let mut config = Config::from_str(COMMENTED_CONFIG).unwrap();
+        config.auto_push = Some(true);
+
+        let content = config.update_document(COMMENTED_CONFIG).unwrap();
+        assert!(content.contains("# 好みの順に並べる"));
+        assert!(content.contains("# 速い順"));
+        assert!(content.contains("# 品質重視"));
+
+        let reloaded = Config::from_str(&content).unwrap();
+        assert_eq!(reloaded.auto_push, Some(true));
+        assert_eq!(reloaded.models.gemini(), "pro");
+        assert_eq!(
+            reloaded.providers,
+            Some(vec!["claude".to_string(), "gemini".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_update_document_from_empty_matches_defaults() {
+        let content = Config::default().update_document("").unwrap();
+        let reloaded = Config::from_str(&content).unwrap();
+        assert_eq!(reloaded.language, Config::default().language);
+        assert_eq!(reloaded.models.codex(), "gpt-5.1-codex-mini");
+        assert_eq!(reloaded.providers, Config::default().providers);
     }
 }
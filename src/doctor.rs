@@ -0,0 +1,292 @@
+use std::path::Path;
+
+use colored::Colorize;
+
+use crate::ai::{AiService, ProviderStatus};
+use crate::config::Config;
+use crate::git::GitService;
+use crate::state::State;
+
+/// 診断項目1件の結果
+#[derive(Debug, Clone, PartialEq)]
+struct Check {
+    passed: bool,
+    label: String,
+    /// 失敗時の対処方法（成功時は補足情報）
+    hint: Option<String>,
+}
+
+impl Check {
+    fn pass(label: impl Into<String>) -> Self {
+        Self {
+            passed: true,
+            label: label.into(),
+            hint: None,
+        }
+    }
+
+    fn fail(label: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            passed: false,
+            label: label.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+/// チェック結果を1行に整形（色付けは表示時に行う）
+fn format_check(check: &Check) -> String {
+    let mark = if check.passed { "✓" } else { "✗" };
+    match &check.hint {
+        Some(hint) => format!("{} {} — {}", mark, check.label, hint),
+        None => format!("{} {}", mark, check.label),
+    }
+}
+
+/// プロバイダーのインストール状況をチェック結果に変換
+fn provider_check(status: &ProviderStatus) -> Check {
+    let label = format!("{} ({})", status.name, status.command);
+    if status.installed {
+        Check::pass(label)
+    } else {
+        Check::fail(
+            format!("{} not found", label),
+            format!("install: {}", status.install_hint),
+        )
+    }
+}
+
+/// 少なくとも1つのプロバイダーが使えるかのまとめ
+fn providers_summary(statuses: &[ProviderStatus]) -> Check {
+    let installed: Vec<&str> = statuses
+        .iter()
+        .filter(|s| s.installed)
+        .map(|s| s.command)
+        .collect();
+    if installed.is_empty() {
+        Check::fail(
+            "No AI provider available",
+            "install at least one of gemini, codex, claude (or use --offline)",
+        )
+    } else {
+        Check::pass(format!("AI providers available: {}", installed.join(", ")))
+    }
+}
+
+/// 設定ファイルの構文チェック（存在しなければ成功扱い）
+fn config_check(kind: &str, path: &Path) -> Check {
+    if !path.exists() {
+        return Check::pass(format!(
+            "{} config: {} (not found, defaults used)",
+            kind,
+            path.display()
+        ));
+    }
+    match Config::validate_file(path) {
+        Ok(()) => Check::pass(format!("{} config: {}", kind, path.display())),
+        Err(e) => Check::fail(
+            format!("{} config: {}", kind, path.display()),
+            format!("{} (fix the TOML syntax)", e),
+        ),
+    }
+}
+
+/// 状態ファイルが読み込めるかを確認
+fn state_check(path: &Path) -> Check {
+    if !path.exists() {
+        return Check::pass(format!("State: {} (not created yet)", path.display()));
+    }
+    match State::load_from(path) {
+        Ok(_) => Check::pass(format!("State: {}", path.display())),
+        Err(e) => Check::fail(
+            format!("State: {}", path.display()),
+            format!("{} (delete the file to reset provider cooldowns)", e),
+        ),
+    }
+}
+
+/// 診断項目を表示
+fn print_section(title: &str, checks: &[Check]) {
+    println!("{}", title.cyan().bold());
+    for check in checks {
+        let line = format_check(check);
+        if check.passed {
+            println!("  {}", line.green());
+        } else {
+            println!("  {}", line.red());
+        }
+    }
+    println!();
+}
+
+/// 環境を診断してチェックリストを表示
+///
+/// 全項目が成功した場合は true を返す。リポジトリ外でも実行でき、その場合はリポジトリ関連の項目を省略する
+pub fn run() -> bool {
+    let mut all_passed = true;
+    let mut record = |checks: &[Check]| {
+        all_passed &= checks.iter().all(|c| c.passed);
+    };
+
+    // Git
+    let mut git_checks = vec![match GitService::git_version() {
+        Some(version) => Check::pass(version),
+        None => Check::fail("git not found", "install git and make sure it is on PATH"),
+    }];
+    let in_repo = GitService::new().verify_repository().is_ok();
+    if in_repo {
+        git_checks.push(Check::pass("Inside a git repository"));
+    }
+    print_section("Git", &git_checks);
+    record(&git_checks);
+    if !in_repo {
+        println!(
+            "  {}\n",
+            "Not inside a git repository; repository checks skipped.".dimmed()
+        );
+    }
+
+    // provider_paths と scope_state_to_repo を反映するため設定を読み込む（読み込めなければ既定値で確認）
+    // 未作成の ~/.git-sc を書き出すと下の「not found」の確認が意味をなさないため、保存しない読み込みを使う
+    let config = Config::load_without_saving().ok();
+
+    // AI プロバイダー
+    let provider_paths = config
+        .as_ref()
+        .map(|config| config.provider_paths.clone())
+        .unwrap_or_default();
+    let statuses = AiService::provider_statuses(&provider_paths);
+    let mut provider_checks: Vec<Check> = statuses.iter().map(provider_check).collect();
+    let summary = providers_summary(&statuses);
+    // 個々の未インストールは失敗扱いにせず、1つも使えない場合のみ失敗とする
+    record(std::slice::from_ref(&summary));
+    provider_checks.push(summary);
+    print_section("AI providers", &provider_checks);
+
+    // 設定ファイル
+    let mut config_checks = Vec::new();
+    match Config::global_config_path() {
+        Ok(path) => config_checks.push(config_check("Global", &path)),
+        Err(e) => config_checks.push(Check::fail("Global config", e.to_string())),
+    }
+    if in_repo {
+        if let Ok(Some(path)) = Config::project_config_path() {
+            config_checks.push(config_check("Project", &path));
+        }
+    }
+    // scope_state_to_repo が有効ならリポジトリ単位の状態ファイルを確認する
+    let scope_to_repo = config
+        .as_ref()
+        .and_then(|config| config.scope_state_to_repo)
+        .unwrap_or(false);
+    match State::scoped_path(scope_to_repo) {
+        Ok(path) => config_checks.push(state_check(&path)),
+        Err(e) => config_checks.push(Check::fail("State", e.to_string())),
+    }
+    print_section("Configuration", &config_checks);
+    record(&config_checks);
+
+    if all_passed {
+        println!("{}", "✓ All checks passed.".green().bold());
+    } else {
+        println!("{}", "✗ Some checks failed. See hints above.".red().bold());
+    }
+    all_passed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn status(command: &'static str, installed: bool) -> ProviderStatus {
+        ProviderStatus {
+            name: "Gemini CLI",
+            command,
+            install_hint: "npm install -g @google/gemini-cli",
+            installed,
+        }
+    }
+
+    // ============================================================
+    // プロバイダー表示のテスト
+    // ============================================================
+
+    #[test]
+    fn test_format_provider_installed() {
+        let check = provider_check(&status("gemini", true));
+        assert_eq!(format_check(&check), "✓ Gemini CLI (gemini)");
+    }
+
+    #[test]
+    fn test_format_provider_missing_with_hint() {
+        let check = provider_check(&status("gemini", false));
+        assert!(!check.passed);
+        assert_eq!(
+            format_check(&check),
+            "✗ Gemini CLI (gemini) not found — install: npm install -g @google/gemini-cli"
+        );
+    }
+
+    #[test]
+    fn test_providers_summary_lists_installed() {
+        let summary = providers_summary(&[status("gemini", false), status("claude", true)]);
+        assert_eq!(format_check(&summary), "✓ AI providers available: claude");
+    }
+
+    #[test]
+    fn test_providers_summary_none_installed() {
+        let summary = providers_summary(&[status("gemini", false), status("codex", false)]);
+        assert!(!summary.passed);
+        assert!(format_check(&summary).starts_with("✗ No AI provider available — "));
+    }
+
+    // ============================================================
+    // 設定ファイルチェックのテスト
+    // ============================================================
+
+    #[test]
+    fn test_config_check_missing_file_passes() {
+        let dir = tempfile::tempdir().unwrap();
+        let check = config_check("Global", &dir.path().join(".git-sc"));
+        assert!(check.passed);
+        assert!(check.label.ends_with("(not found, defaults used)"));
+    }
+
+    #[test]
+    fn test_config_check_invalid_file_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".git-sc");
+        std::fs::write(&path, "providers = \"gemini").unwrap();
+        let check = config_check("Project", &path);
+        assert!(!check.passed);
+        assert!(check.hint.unwrap().ends_with("(fix the TOML syntax)"));
+    }
+
+    // ============================================================
+    // 状態ファイルチェックのテスト
+    // ============================================================
+
+    #[test]
+    fn test_state_check_missing_file_passes() {
+        let dir = tempfile::tempdir().unwrap();
+        let check = state_check(&dir.path().join("git-sc-state"));
+        assert!(check.passed);
+        assert!(check.label.ends_with("(not created yet)"));
+    }
+
+    #[test]
+    fn test_state_check_invalid_repo_state_fails() {
+        // scope_state_to_repo で使う .git/git-sc-state が壊れている場合
+        let dir = tempfile::tempdir().unwrap();
+        let path = State::resolve_path(true, Some(dir.path())).unwrap();
+        std::fs::write(&path, "provider_failures = [").unwrap();
+        let check = state_check(&path);
+        assert!(!check.passed);
+        assert_eq!(check.label, format!("State: {}", path.display()));
+        assert!(check
+            .hint
+            .unwrap()
+            .ends_with("(delete the file to reset provider cooldowns)"));
+    }
+}
@@ -18,6 +18,9 @@ pub enum AppError {
     #[error("{0}")]
     AiProviderError(String),
 
+    #[error("gitがインストールされていません。https://git-scm.com/downloads からインストールし、PATHに追加してください。")]
+    GitNotInstalled,
+
     #[error("Gitコマンドが失敗しました: {0}")]
     GitError(String),
 
@@ -50,6 +53,62 @@ pub enum AppError {
 
     #[error("--generate-for と --{0} は同時に使用できません")]
     ConflictingOptions(String),
+
+    #[error("--template {0} は prefix_type \"{1}\" と同時に使用できません")]
+    TemplateConflict(String, String),
+
+    #[error("--coauthor の形式が不正です: \"{0}\"（\"Name <email>\" の形式で指定してください）")]
+    InvalidCoauthor(String),
+
+    #[error("--author の形式が不正です: \"{0}\"（\"Name <email>\" の形式で指定してください）")]
+    InvalidAuthor(String),
+
+    #[error("ステージ済みの変更がないため --amend-staged を実行できません。メッセージのみ再生成する場合は --amend を使用してください。")]
+    NothingToAmendStaged,
+
+    #[error("差分に秘密情報の可能性がある値が含まれています（{0}）。AIには送信しません。")]
+    PotentialSecretDetected(String),
+
+    #[error("コミットメッセージが Conventional Commits 形式ではありません: \"{0}\"")]
+    InvalidCommitFormat(String),
+
+    #[error("コミットの種類 \"{0}\" は許可されていません（allowed_types: {1}）")]
+    DisallowedCommitType(String, String),
+
+    #[error("コミットメッセージファイルを読み書きできません: {0}")]
+    MessageFileError(String),
+
+    #[error("取り消せる操作がありません。--undo は git-sc が amend・squash・reword した直後にのみ使用できます。")]
+    NothingToUndo,
+
+    #[error("--undo を実行できません: {0}")]
+    UndoNotPossible(String),
+
+    #[error("--fill のひな形を使用できません: {0}")]
+    InvalidFillTemplate(String),
+
+    #[error("--patch のファイルを使用できません: {0}")]
+    InvalidPatchFile(String),
+
+    #[error("alias.sc は既に \"{0}\" として設定されています。上書きするには --force を指定してください。")]
+    AliasConflict(String),
+}
+
+impl AppError {
+    /// プロセスの終了コード
+    ///
+    /// スクリプトから「ユーザーが中断した」と「処理が失敗した」を区別できるようにする
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            // Gitリポジトリ外では何もせず正常終了する
+            AppError::NotGitRepository => 0,
+            AppError::UserCancelled => 2,
+            AppError::NoChanges | AppError::NoStagedChanges | AppError::NothingToAmendStaged => 3,
+            AppError::NoAiProviderInstalled => 4,
+            AppError::AiProviderError(_) => 5,
+            _ => 1,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -87,6 +146,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_error_git_not_installed() {
+        let err = AppError::GitNotInstalled;
+        assert_eq!(
+            err.to_string(),
+            "gitがインストールされていません。https://git-scm.com/downloads からインストールし、PATHに追加してください。"
+        );
+    }
+
     #[test]
     fn test_error_no_ai_provider_installed() {
         let err = AppError::NoAiProviderInstalled;
@@ -198,4 +266,139 @@ mod tests {
             "--generate-for と --amend は同時に使用できません"
         );
     }
+
+    #[test]
+    fn test_error_template_conflict() {
+        let err = AppError::TemplateConflict("gitmoji".to_string(), "bracket".to_string());
+        assert_eq!(
+            err.to_string(),
+            "--template gitmoji は prefix_type \"bracket\" と同時に使用できません"
+        );
+    }
+
+    #[test]
+    fn test_error_invalid_coauthor() {
+        let err = AppError::InvalidCoauthor("Alice".to_string());
+        assert_eq!(
+            err.to_string(),
+            "--coauthor の形式が不正です: \"Alice\"（\"Name <email>\" の形式で指定してください）"
+        );
+    }
+
+    #[test]
+    fn test_error_invalid_author() {
+        let err = AppError::InvalidAuthor("alice@example.com".to_string());
+        assert_eq!(
+            err.to_string(),
+            "--author の形式が不正です: \"alice@example.com\"（\"Name <email>\" の形式で指定してください）"
+        );
+    }
+
+    #[test]
+    fn test_error_potential_secret_detected() {
+        let err = AppError::PotentialSecretDetected("line 6: AWS access key".to_string());
+        assert_eq!(
+            err.to_string(),
+            "差分に秘密情報の可能性がある値が含まれています（line 6: AWS access key）。AIには送信しません。"
+        );
+    }
+
+    #[test]
+    fn test_error_invalid_commit_format() {
+        let err = AppError::InvalidCommitFormat("Add login page".to_string());
+        assert_eq!(
+            err.to_string(),
+            "コミットメッセージが Conventional Commits 形式ではありません: \"Add login page\""
+        );
+    }
+
+    #[test]
+    fn test_error_nothing_to_amend_staged() {
+        let err = AppError::NothingToAmendStaged;
+        assert_eq!(
+            err.to_string(),
+            "ステージ済みの変更がないため --amend-staged を実行できません。メッセージのみ再生成する場合は --amend を使用してください。"
+        );
+    }
+
+    #[test]
+    fn test_error_message_file_error() {
+        let err = AppError::MessageFileError(".git/COMMIT_EDITMSG: Permission denied".to_string());
+        assert_eq!(
+            err.to_string(),
+            "コミットメッセージファイルを読み書きできません: .git/COMMIT_EDITMSG: Permission denied"
+        );
+    }
+
+    #[test]
+    fn test_error_disallowed_commit_type() {
+        let err = AppError::DisallowedCommitType("docs".to_string(), "feat, fix".to_string());
+        assert_eq!(
+            err.to_string(),
+            "コミットの種類 \"docs\" は許可されていません（allowed_types: feat, fix）"
+        );
+    }
+
+    #[test]
+    fn test_error_undo() {
+        assert_eq!(
+            AppError::NothingToUndo.to_string(),
+            "取り消せる操作がありません。--undo は git-sc が amend・squash・reword した直後にのみ使用できます。"
+        );
+        assert_eq!(
+            AppError::UndoNotPossible("uncommitted changes".to_string()).to_string(),
+            "--undo を実行できません: uncommitted changes"
+        );
+    }
+
+    #[test]
+    fn test_error_invalid_fill_template() {
+        let err =
+            AppError::InvalidFillTemplate("skeleton.txt: no {{summary}} placeholder".to_string());
+        assert_eq!(
+            err.to_string(),
+            "--fill のひな形を使用できません: skeleton.txt: no {{summary}} placeholder"
+        );
+    }
+
+    #[test]
+    fn test_error_invalid_patch_file() {
+        let err = AppError::InvalidPatchFile("notes.txt: not a unified diff".to_string());
+        assert_eq!(
+            err.to_string(),
+            "--patch のファイルを使用できません: notes.txt: not a unified diff"
+        );
+    }
+
+    #[test]
+    fn test_error_alias_conflict() {
+        let err = AppError::AliasConflict("status --short".to_string());
+        assert_eq!(
+            err.to_string(),
+            "alias.sc は既に \"status --short\" として設定されています。上書きするには --force を指定してください。"
+        );
+    }
+
+    // ============================================================
+    // exit_code のテスト
+    // ============================================================
+
+    #[test]
+    fn test_exit_codes() {
+        let cases = [
+            (AppError::NotGitRepository, 0),
+            (AppError::UserCancelled, 2),
+            (AppError::NoChanges, 3),
+            (AppError::NoStagedChanges, 3),
+            (AppError::NothingToAmendStaged, 3),
+            (AppError::NoAiProviderInstalled, 4),
+            (AppError::AiProviderError("rate limited".to_string()), 5),
+            (AppError::GitError("fatal".to_string()), 1),
+            (AppError::ConfigError("bad".to_string()), 1),
+            (AppError::GitNotInstalled, 1),
+        ];
+        for (err, code) in cases {
+            assert_eq!(err.exit_code(), code, "{:?}", err);
+        }
+    }
 }
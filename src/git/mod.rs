@@ -1,4 +1,7 @@
 // Git operations module
+pub mod secrets;
 pub mod service;
 
-pub use service::{GitService, ScriptResult};
+pub use service::{
+    CommitOptions, DiffBlock, GitService, ScriptResult, DIFF_ALGORITHMS, MAX_DIFF_CHARS,
+};
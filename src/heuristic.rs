@@ -0,0 +1,151 @@
+/// メッセージに列挙するファイル数の上限
+const MAX_LISTED_FILES: usize = 3;
+
+/// ドキュメントとみなすファイルかどうか
+fn is_doc_file(path: &str) -> bool {
+    let lower = path.to_lowercase();
+    lower.starts_with("docs/")
+        || lower.ends_with(".md")
+        || lower.ends_with(".rst")
+        || lower.ends_with(".txt")
+}
+
+/// `git diff --name-status` の1行からステータス文字とパスを取得
+///
+/// リネーム・コピー（R100, C075 など）は変更後のパスを使用
+fn parse_entry(line: &str) -> Option<(char, &str)> {
+    let mut fields = line.split('\t');
+    let status = fields.next()?.trim().chars().next()?;
+    let path = fields.next_back()?.trim();
+    if path.is_empty() {
+        return None;
+    }
+    Some((status, path))
+}
+
+/// `git diff --name-status` の出力から決定的なコミットメッセージを生成
+///
+/// 最も多い変更の種類から type と動詞を決め、先頭のファイルを列挙する
+/// 例: `chore: update 3 files (src/app.rs, src/cli.rs, src/main.rs)`
+pub fn heuristic_message(name_status: &str) -> String {
+    let entries: Vec<(char, &str)> = name_status.lines().filter_map(parse_entry).collect();
+
+    if entries.is_empty() {
+        return "chore: update files".to_string();
+    }
+
+    let count = |kinds: &[char]| entries.iter().filter(|(s, _)| kinds.contains(s)).count();
+    // 同数の場合は配列の先頭（変更 → 追加 → 削除 → リネーム）を優先
+    let kinds = [
+        (count(&['M', 'T']), "update"),
+        (count(&['A']), "add"),
+        (count(&['D']), "remove"),
+        (count(&['R', 'C']), "rename"),
+    ];
+    let verb = kinds
+        .iter()
+        .fold(
+            (0, "update"),
+            |best, &(n, verb)| {
+                if n > best.0 {
+                    (n, verb)
+                } else {
+                    best
+                }
+            },
+        )
+        .1;
+
+    let commit_type = if entries.iter().all(|(_, path)| is_doc_file(path)) {
+        "docs"
+    } else {
+        match verb {
+            "add" => "feat",
+            "rename" => "refactor",
+            _ => "chore",
+        }
+    };
+
+    if entries.len() == 1 {
+        return format!("{}: {} {}", commit_type, verb, entries[0].1);
+    }
+
+    let mut listed: Vec<&str> = entries
+        .iter()
+        .take(MAX_LISTED_FILES)
+        .map(|(_, path)| *path)
+        .collect();
+    if entries.len() > MAX_LISTED_FILES {
+        listed.push("...");
+    }
+
+    format!(
+        "{}: {} {} files ({})",
+        commit_type,
+        verb,
+        entries.len(),
+        listed.join(", ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    // ============================================================
+    // heuristic_message のテスト
+    // ============================================================
+
+    #[rstest]
+    #[case("M\tsrc/app.rs\n", "chore: update src/app.rs")]
+    #[case("A\tsrc/heuristic.rs\n", "feat: add src/heuristic.rs")]
+    #[case("D\told.rs\n", "chore: remove old.rs")]
+    #[case("R100\tsrc/a.rs\tsrc/b.rs\n", "refactor: rename src/b.rs")]
+    #[case("M\tREADME.md\n", "docs: update README.md")]
+    fn test_heuristic_message_single_file(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(heuristic_message(input), expected);
+    }
+
+    #[test]
+    fn test_heuristic_message_multi_file() {
+        let input = "M\tsrc/app.rs\nM\tsrc/git/service.rs\nA\tsrc/heuristic.rs\n";
+        assert_eq!(
+            heuristic_message(input),
+            "chore: update 3 files (src/app.rs, src/git/service.rs, src/heuristic.rs)"
+        );
+    }
+
+    #[test]
+    fn test_heuristic_message_many_files_truncated_list() {
+        let input = "A\ta.rs\nA\tb.rs\nA\tc.rs\nA\td.rs\nM\te.rs\n";
+        assert_eq!(
+            heuristic_message(input),
+            "feat: add 5 files (a.rs, b.rs, c.rs, ...)"
+        );
+    }
+
+    #[test]
+    fn test_heuristic_message_tie_prefers_update() {
+        let input = "A\tnew.rs\nM\told.rs\n";
+        assert_eq!(
+            heuristic_message(input),
+            "chore: update 2 files (new.rs, old.rs)"
+        );
+    }
+
+    #[test]
+    fn test_heuristic_message_docs_only() {
+        let input = "M\tREADME.md\nA\tdocs/guide.rst\n";
+        assert_eq!(
+            heuristic_message(input),
+            "docs: update 2 files (README.md, docs/guide.rst)"
+        );
+    }
+
+    #[test]
+    fn test_heuristic_message_empty() {
+        assert_eq!(heuristic_message(""), "chore: update files");
+    }
+}
@@ -1,35 +1,66 @@
 mod ai;
+mod alias;
 mod app;
 mod cli;
 mod config;
+mod doctor;
 mod error;
 mod git;
+mod heuristic;
 mod state;
 
 use clap::Parser;
 use colored::Colorize;
 
 use app::App;
-use cli::Cli;
+use cli::{Cli, Command};
 use error::AppError;
 
 fn main() {
     let cli = Cli::parse();
 
-    let app = match App::new(&cli) {
-        Ok(app) => app,
-        Err(e) => {
-            eprintln!("{} {}", "Error:".red().bold(), e);
-            std::process::exit(1);
+    // サブコマンドは設定の読み込みやリポジトリ確認より前に処理する
+    match cli.command {
+        Some(Command::Doctor) => {
+            let passed = doctor::run();
+            std::process::exit(if passed { 0 } else { 1 });
+        }
+        Some(Command::State) => {
+            // scope_state_to_repo を反映するため設定を読み込む（読み込めなければグローバル）
+            // 状態の表示だけで ~/.git-sc を作成しないよう、保存しない読み込みを使う
+            let scope_to_repo = config::Config::load_without_saving()
+                .ok()
+                .and_then(|config| config.scope_state_to_repo)
+                .unwrap_or(false);
+            if let Err(e) = state::State::print_summary(scope_to_repo) {
+                exit_with_error(e);
+            }
+            return;
+        }
+        Some(Command::InstallAlias { local, force }) => {
+            if let Err(e) = alias::install(local, force) {
+                exit_with_error(e);
+            }
+            return;
         }
+        None => {}
+    }
+
+    let mut app = match App::new(&cli) {
+        Ok(app) => app,
+        Err(e) => exit_with_error(e),
     };
 
     if let Err(e) = app.run(&cli) {
-        // Gitリポジトリでない場合は何も表示せず正常終了
-        if matches!(e, AppError::NotGitRepository) {
-            std::process::exit(0);
-        }
+        exit_with_error(e);
+    }
+}
+
+/// エラーを表示し、種類に応じた終了コードで終了
+fn exit_with_error(e: AppError) -> ! {
+    // Gitリポジトリでない場合は何も表示せず正常終了
+    if !matches!(e, AppError::NotGitRepository) {
         eprintln!("{} {}", "Error:".red().bold(), e);
-        std::process::exit(1);
     }
+    std::process::exit(e.exit_code());
 }
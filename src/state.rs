@@ -1,12 +1,17 @@
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use serde::{Deserialize, Serialize};
 
+use crate::config::ProviderPriorityConfig;
 use crate::error::AppError;
 
+/// リポジトリ単位の状態ファイル名（git ディレクトリ配下に作成）
+const REPO_STATE_FILE: &str = "git-sc-state";
+
 /// プロバイダーの失敗情報
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProviderFailure {
@@ -14,12 +19,45 @@ pub struct ProviderFailure {
     pub failed_at: u64,
 }
 
+/// 最後に成功したプロバイダーの情報
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderSuccess {
+    /// プロバイダー名
+    pub provider: String,
+    /// 生成にかかった時間（ミリ秒）
+    pub elapsed_ms: u64,
+    /// 成功した時刻（UNIXタイムスタンプ、秒）
+    pub succeeded_at: u64,
+}
+
+/// 履歴を書き換えた操作の記録（--undo 用）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryRewrite {
+    /// 操作名（amend, squash, reword など）
+    pub action: String,
+    /// 操作前の HEAD
+    pub head_before: String,
+    /// 操作後の HEAD（--undo 時に HEAD が動いていないかの確認に使う）
+    pub head_after: String,
+    /// 操作した時刻（UNIXタイムスタンプ、秒）
+    pub rewritten_at: u64,
+}
+
 /// アプリケーション状態
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct State {
     /// プロバイダーごとの失敗情報
     #[serde(default)]
     pub provider_failures: HashMap<String, ProviderFailure>,
+    /// 最後に成功したプロバイダーとそのレイテンシ
+    #[serde(default)]
+    pub last_success: Option<ProviderSuccess>,
+    /// リポジトリ（git ディレクトリ）ごとの直近の履歴書き換え
+    #[serde(default)]
+    pub history_rewrites: HashMap<String, HistoryRewrite>,
+    /// プロバイダーごとの生成成功回数
+    #[serde(default)]
+    pub provider_usage: HashMap<String, u64>,
 }
 
 impl State {
@@ -30,30 +68,114 @@ impl State {
             .ok_or_else(|| AppError::ConfigError("Could not find home directory".to_string()))
     }
 
+    /// 状態ファイルのパスを選択
+    ///
+    /// scope_to_repo が有効かつリポジトリ内であれば git ディレクトリ配下、それ以外はグローバル
+    pub fn resolve_path(scope_to_repo: bool, git_dir: Option<&Path>) -> Result<PathBuf, AppError> {
+        match git_dir {
+            Some(dir) if scope_to_repo => Ok(dir.join(REPO_STATE_FILE)),
+            _ => Self::state_path(),
+        }
+    }
+
+    /// 設定に応じた状態ファイルのパスを取得（現在のリポジトリを基準にする）
+    pub fn scoped_path(scope_to_repo: bool) -> Result<PathBuf, AppError> {
+        Self::resolve_path(scope_to_repo, Self::git_dir().as_deref())
+    }
+
+    /// 現在のリポジトリの git ディレクトリを取得（リポジトリ外では None）
+    fn git_dir() -> Option<PathBuf> {
+        let output = Command::new("git")
+            .args(["rev-parse", "--absolute-git-dir"])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let dir = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if dir.is_empty() {
+            None
+        } else {
+            Some(PathBuf::from(dir))
+        }
+    }
+
+    /// 設定に応じて状態を読み込む
+    ///
+    /// リポジトリ単位の状態があればそれを優先し、なければグローバルの状態を引き継ぐ
+    pub fn load_scoped(scope_to_repo: bool) -> Result<Self, AppError> {
+        let path = Self::scoped_path(scope_to_repo)?;
+        if path.exists() {
+            Self::load_from(&path)
+        } else {
+            Self::load()
+        }
+    }
+
+    /// 状態の要約（保存先・直近の成功・プロバイダーの利用回数）を表示
+    pub fn print_summary(scope_to_repo: bool) -> Result<(), AppError> {
+        let path = Self::scoped_path(scope_to_repo)?;
+        let state = Self::load_scoped(scope_to_repo)?;
+
+        println!("State: {}", path.display());
+        match &state.last_success {
+            Some(success) => println!(
+                "Last success: {} ({} ms)",
+                success.provider, success.elapsed_ms
+            ),
+            None => println!("Last success: none"),
+        }
+        println!("Provider usage:");
+        let usage = state.format_provider_usage();
+        if usage.is_empty() {
+            println!("  (no successful generations yet)");
+        }
+        for line in usage {
+            println!("  {}", line);
+        }
+        Ok(())
+    }
+
+    /// 設定に応じた場所に状態を保存
+    pub fn save_scoped(&self, scope_to_repo: bool) -> Result<(), AppError> {
+        let path = Self::scoped_path(scope_to_repo)?;
+        self.save_to(&path)
+    }
+
     /// ファイルから状態を読み込み、存在しない場合はデフォルトを返す
     pub fn load() -> Result<Self, AppError> {
-        let path = Self::state_path()?;
+        Self::load_from(&Self::state_path()?)
+    }
 
+    /// 指定したパスから状態を読み込み、存在しない場合はデフォルトを返す
+    pub fn load_from(path: &Path) -> Result<Self, AppError> {
         if !path.exists() {
             return Ok(State::default());
         }
 
-        let content = fs::read_to_string(&path)
+        let content = fs::read_to_string(path)
             .map_err(|e| AppError::ConfigError(format!("Failed to read state: {}", e)))?;
 
         toml::from_str(&content)
             .map_err(|e| AppError::ConfigError(format!("Failed to parse state: {}", e)))
     }
 
-    /// 状態をファイルに保存
-    pub fn save(&self) -> Result<(), AppError> {
-        let path = Self::state_path()?;
-
+    /// 指定したパスに状態を保存
+    ///
+    /// 同時に実行された別の git-sc が書きかけのファイルを読まないよう、一時ファイルに書いてから置き換える
+    pub fn save_to(&self, path: &Path) -> Result<(), AppError> {
         let content = toml::to_string_pretty(self)
             .map_err(|e| AppError::ConfigError(format!("Failed to serialize state: {}", e)))?;
 
-        fs::write(&path, content)
-            .map_err(|e| AppError::ConfigError(format!("Failed to write state: {}", e)))?;
+        let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+        temp_name.push(format!(".{}.tmp", std::process::id()));
+        let temp_path = path.with_file_name(temp_name);
+        fs::write(&temp_path, content)
+            .and_then(|_| fs::rename(&temp_path, path))
+            .map_err(|e| {
+                let _ = fs::remove_file(&temp_path);
+                AppError::ConfigError(format!("Failed to write state: {}", e))
+            })?;
 
         Ok(())
     }
@@ -76,6 +198,51 @@ impl State {
         );
     }
 
+    /// プロバイダーの成功とレイテンシを記録し、利用回数を加算
+    pub fn record_success(&mut self, provider: &str, elapsed_ms: u64) {
+        let provider = provider.to_lowercase();
+        *self.provider_usage.entry(provider.clone()).or_insert(0) += 1;
+        self.last_success = Some(ProviderSuccess {
+            provider,
+            elapsed_ms,
+            succeeded_at: Self::now(),
+        });
+    }
+
+    /// プロバイダーごとの利用回数を多い順（同数は名前順）に整形
+    pub fn format_provider_usage(&self) -> Vec<String> {
+        let mut usage: Vec<(&String, &u64)> = self.provider_usage.iter().collect();
+        usage.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        usage
+            .into_iter()
+            .map(|(provider, count)| format!("{}: {}", provider, count))
+            .collect()
+    }
+
+    /// 履歴を書き換えた操作を記録（リポジトリごとに直近の1件のみ保持）
+    pub fn record_rewrite(
+        &mut self,
+        repo: &str,
+        action: &str,
+        head_before: &str,
+        head_after: &str,
+    ) {
+        self.history_rewrites.insert(
+            repo.to_string(),
+            HistoryRewrite {
+                action: action.to_string(),
+                head_before: head_before.to_string(),
+                head_after: head_after.to_string(),
+                rewritten_at: Self::now(),
+            },
+        );
+    }
+
+    /// リポジトリの直近の履歴書き換えを取り出して記録から削除
+    pub fn take_rewrite(&mut self, repo: &str) -> Option<HistoryRewrite> {
+        self.history_rewrites.remove(repo)
+    }
+
     /// クールダウン中のプロバイダーのリストを取得
     pub fn get_demoted_providers(&self, cooldown_minutes: u64) -> Vec<String> {
         let now = Self::now();
@@ -91,6 +258,15 @@ impl State {
             .collect()
     }
 
+    /// クールダウン中であれば解除までの残り秒数を取得
+    pub fn cooldown_remaining_secs(&self, provider: &str, cooldown_minutes: u64) -> Option<u64> {
+        let failure = self.provider_failures.get(&provider.to_lowercase())?;
+        let elapsed = Self::now().saturating_sub(failure.failed_at);
+        (cooldown_minutes * 60)
+            .checked_sub(elapsed)
+            .filter(|remaining| *remaining > 0)
+    }
+
     /// 期限切れの失敗記録をクリーンアップ
     pub fn cleanup_expired(&mut self, cooldown_minutes: u64) {
         let now = Self::now();
@@ -122,6 +298,41 @@ impl State {
         normal.append(&mut demoted_providers);
         normal
     }
+
+    /// クールダウン中のプロバイダーを除外（skip_demoted 用）
+    ///
+    /// すべてが降格中の場合は何も試せなくなるため、元の一覧をそのまま返す
+    pub fn skip_demoted_providers(
+        &self,
+        providers: Vec<String>,
+        cooldown_minutes: u64,
+    ) -> Vec<String> {
+        let demoted = self.get_demoted_providers(cooldown_minutes);
+        let available: Vec<String> = providers
+            .iter()
+            .filter(|p| !demoted.contains(&p.to_lowercase()))
+            .cloned()
+            .collect();
+
+        if available.is_empty() {
+            providers
+        } else {
+            available
+        }
+    }
+
+    /// プロバイダーを優先度順（降順、同値は記述順）に並べてから降格を適用
+    pub fn reorder_providers_with_priority(
+        &self,
+        providers: &[ProviderPriorityConfig],
+        cooldown_minutes: u64,
+    ) -> Vec<String> {
+        let mut sorted: Vec<&ProviderPriorityConfig> = providers.iter().collect();
+        sorted.sort_by_key(|p| std::cmp::Reverse(p.priority));
+
+        let names = sorted.iter().map(|p| p.name.clone()).collect();
+        self.reorder_providers(names, cooldown_minutes)
+    }
 }
 
 #[cfg(test)]
@@ -134,6 +345,51 @@ mod tests {
         assert!(state.provider_failures.is_empty());
     }
 
+    // ============================================================
+    // 状態ファイルのパス選択と読み書きのテスト
+    // ============================================================
+
+    #[test]
+    fn test_resolve_path_scoped_in_repo() {
+        let git_dir = PathBuf::from("/tmp/repo/.git");
+        assert_eq!(
+            State::resolve_path(true, Some(&git_dir)).unwrap(),
+            PathBuf::from("/tmp/repo/.git/git-sc-state")
+        );
+    }
+
+    #[test]
+    fn test_resolve_path_falls_back_to_global() {
+        let git_dir = PathBuf::from("/tmp/repo/.git");
+        let global = State::state_path().unwrap();
+        // フラグ無効、またはリポジトリ外ではグローバル
+        assert_eq!(State::resolve_path(false, Some(&git_dir)).unwrap(), global);
+        assert_eq!(State::resolve_path(true, None).unwrap(), global);
+    }
+
+    #[test]
+    fn test_save_and_load_at_repo_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = State::resolve_path(true, Some(dir.path())).unwrap();
+
+        let mut state = State::default();
+        state.record_failure("gemini");
+        state.record_success("claude", 1200);
+        state.save_to(&path).unwrap();
+
+        let loaded = State::load_from(&path).unwrap();
+        assert!(loaded.provider_failures.contains_key("gemini"));
+        assert_eq!(loaded.last_success.unwrap().provider, "claude");
+    }
+
+    #[test]
+    fn test_load_from_missing_path_is_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = State::load_from(&dir.path().join(REPO_STATE_FILE)).unwrap();
+        assert!(state.provider_failures.is_empty());
+        assert!(state.last_success.is_none());
+    }
+
     #[test]
     fn test_record_failure() {
         let mut state = State::default();
@@ -184,6 +440,23 @@ mod tests {
         assert!(demoted.is_empty());
     }
 
+    #[test]
+    fn test_cooldown_remaining_secs() {
+        let mut state = State::default();
+        state.provider_failures.insert(
+            "gemini".to_string(),
+            ProviderFailure {
+                failed_at: State::now() - 10 * 60,
+            },
+        );
+
+        let remaining = state.cooldown_remaining_secs("Gemini", 60).unwrap();
+        assert!((49 * 60..=50 * 60).contains(&remaining));
+        // 期限切れ、または失敗記録なし
+        assert_eq!(state.cooldown_remaining_secs("gemini", 5), None);
+        assert_eq!(state.cooldown_remaining_secs("claude", 60), None);
+    }
+
     #[test]
     fn test_reorder_providers_no_demoted() {
         let state = State::default();
@@ -238,6 +511,136 @@ mod tests {
         assert!(reordered.contains(&"codex".to_string()));
     }
 
+    fn detailed(entries: &[(&str, i32)]) -> Vec<ProviderPriorityConfig> {
+        entries
+            .iter()
+            .map(|(name, priority)| ProviderPriorityConfig {
+                name: name.to_string(),
+                priority: *priority,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_skip_demoted_providers_filters_instead_of_reordering() {
+        let mut state = State::default();
+        state.record_failure("gemini");
+
+        let providers = vec![
+            "gemini".to_string(),
+            "codex".to_string(),
+            "claude".to_string(),
+        ];
+        // 並び替えでは末尾に残り、除外では一覧から外れる
+        assert_eq!(
+            state.reorder_providers(providers.clone(), 60),
+            vec!["codex", "claude", "gemini"]
+        );
+        assert_eq!(
+            state.skip_demoted_providers(providers, 60),
+            vec!["codex", "claude"]
+        );
+    }
+
+    #[test]
+    fn test_skip_demoted_providers_all_demoted_keeps_list() {
+        let mut state = State::default();
+        state.record_failure("gemini");
+        state.record_failure("claude");
+
+        let providers = vec!["claude".to_string(), "gemini".to_string()];
+        assert_eq!(
+            state.skip_demoted_providers(providers, 60),
+            vec!["claude", "gemini"]
+        );
+    }
+
+    #[test]
+    fn test_skip_demoted_providers_after_cooldown_expired() {
+        let mut state = State::default();
+        state.provider_failures.insert(
+            "gemini".to_string(),
+            ProviderFailure {
+                failed_at: State::now() - 2 * 60 * 60,
+            },
+        );
+
+        let providers = vec!["gemini".to_string(), "codex".to_string()];
+        assert_eq!(
+            state.skip_demoted_providers(providers, 60),
+            vec!["gemini", "codex"]
+        );
+    }
+
+    #[test]
+    fn test_reorder_providers_with_priority_sorts_descending() {
+        let state = State::default();
+        let providers = detailed(&[("gemini", 0), ("codex", -10), ("claude", 10)]);
+
+        let reordered = state.reorder_providers_with_priority(&providers, 60);
+        assert_eq!(
+            reordered,
+            vec![
+                "claude".to_string(),
+                "gemini".to_string(),
+                "codex".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reorder_providers_with_priority_stable_for_equal() {
+        let state = State::default();
+        let providers = detailed(&[("codex", 0), ("gemini", 0), ("claude", 0)]);
+
+        let reordered = state.reorder_providers_with_priority(&providers, 60);
+        assert_eq!(
+            reordered,
+            vec![
+                "codex".to_string(),
+                "gemini".to_string(),
+                "claude".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reorder_providers_with_priority_and_demotion() {
+        let mut state = State::default();
+        state.record_failure("claude");
+
+        let providers = detailed(&[("gemini", 0), ("codex", -10), ("claude", 10)]);
+
+        // 最優先の claude でもクールダウン中は末尾へ、残りは優先度順
+        let reordered = state.reorder_providers_with_priority(&providers, 60);
+        assert_eq!(
+            reordered,
+            vec![
+                "gemini".to_string(),
+                "codex".to_string(),
+                "claude".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reorder_providers_with_priority_after_cooldown_expired() {
+        let mut state = State::default();
+        let two_hours_ago = State::now() - (2 * 60 * 60);
+        state.provider_failures.insert(
+            "claude".to_string(),
+            ProviderFailure {
+                failed_at: two_hours_ago,
+            },
+        );
+
+        let providers = detailed(&[("gemini", 0), ("claude", 10)]);
+
+        // クールダウンが切れた最優先プロバイダーは先頭に戻る
+        let reordered = state.reorder_providers_with_priority(&providers, 60);
+        assert_eq!(reordered, vec!["claude".to_string(), "gemini".to_string()]);
+    }
+
     #[test]
     fn test_cleanup_expired() {
         let mut state = State::default();
@@ -261,6 +664,124 @@ mod tests {
         assert!(!state.provider_failures.contains_key("codex"));
     }
 
+    #[test]
+    fn test_record_success() {
+        let mut state = State::default();
+        state.record_success("Claude", 2300);
+
+        let success = state.last_success.as_ref().unwrap();
+        assert_eq!(success.provider, "claude");
+        assert_eq!(success.elapsed_ms, 2300);
+        assert!(success.succeeded_at > 0);
+    }
+
+    #[test]
+    fn test_record_success_increments_usage() {
+        let mut state = State::default();
+        state.record_success("gemini", 1000);
+        state.record_success("Gemini", 900);
+        state.record_success("claude", 500);
+
+        assert_eq!(state.provider_usage["gemini"], 2);
+        assert_eq!(state.provider_usage["claude"], 1);
+        // 失敗は利用回数に含めない
+        state.record_failure("codex");
+        assert!(!state.provider_usage.contains_key("codex"));
+    }
+
+    #[test]
+    fn test_provider_usage_persists_through_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(REPO_STATE_FILE);
+
+        let mut state = State::default();
+        state.record_success("codex", 800);
+        state.save_to(&path).unwrap();
+
+        let mut loaded = State::load_from(&path).unwrap();
+        loaded.record_success("codex", 700);
+        loaded.save_to(&path).unwrap();
+
+        let reloaded = State::load_from(&path).unwrap();
+        assert_eq!(reloaded.provider_usage["codex"], 2);
+        // 一時ファイルは残らない
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_format_provider_usage_sorted_by_count() {
+        let mut state = State::default();
+        state.provider_usage.insert("claude".to_string(), 3);
+        state.provider_usage.insert("gemini".to_string(), 12);
+        state.provider_usage.insert("codex".to_string(), 3);
+
+        assert_eq!(
+            state.format_provider_usage(),
+            vec!["gemini: 12", "claude: 3", "codex: 3"]
+        );
+    }
+
+    #[test]
+    fn test_record_success_overwrites_previous() {
+        let mut state = State::default();
+        state.record_success("gemini", 1000);
+        state.record_success("codex", 500);
+
+        assert_eq!(state.last_success.unwrap().provider, "codex");
+    }
+
+    #[test]
+    fn test_state_serialization_with_last_success() {
+        let mut state = State::default();
+        state.record_success("gemini", 1234);
+
+        let serialized = toml::to_string_pretty(&state).unwrap();
+        let deserialized: State = toml::from_str(&serialized).unwrap();
+
+        let success = deserialized.last_success.unwrap();
+        assert_eq!(success.provider, "gemini");
+        assert_eq!(success.elapsed_ms, 1234);
+    }
+
+    #[test]
+    fn test_record_rewrite_per_repo() {
+        let mut state = State::default();
+        state.record_rewrite("/repo/a/.git", "amend", "aaa", "bbb");
+        state.record_rewrite("/repo/a/.git", "squash", "bbb", "ccc");
+        state.record_rewrite("/repo/b/.git", "reword", "111", "222");
+
+        // 同じリポジトリでは直近の操作で上書きされる
+        let rewrite = &state.history_rewrites["/repo/a/.git"];
+        assert_eq!(rewrite.action, "squash");
+        assert_eq!(rewrite.head_before, "bbb");
+        assert_eq!(rewrite.head_after, "ccc");
+        assert!(rewrite.rewritten_at > 0);
+        assert_eq!(state.history_rewrites["/repo/b/.git"].head_before, "111");
+    }
+
+    #[test]
+    fn test_take_rewrite_removes_record() {
+        let mut state = State::default();
+        state.record_rewrite("/repo/.git", "amend", "aaa", "bbb");
+
+        assert_eq!(state.take_rewrite("/repo/.git").unwrap().head_before, "aaa");
+        assert!(state.take_rewrite("/repo/.git").is_none());
+    }
+
+    #[test]
+    fn test_state_serialization_with_history_rewrites() {
+        let mut state = State::default();
+        state.record_rewrite("/repo/.git", "amend", "aaa", "bbb");
+
+        let serialized = toml::to_string_pretty(&state).unwrap();
+        let deserialized: State = toml::from_str(&serialized).unwrap();
+
+        assert_eq!(
+            deserialized.history_rewrites.get("/repo/.git"),
+            state.history_rewrites.get("/repo/.git")
+        );
+    }
+
     #[test]
     fn test_state_serialization() {
         let mut state = State::default();